@@ -0,0 +1,34 @@
+//! `search` latency as the schema grows. Run with `cargo bench --bench search`.
+
+use criterion::{BenchmarkId, Criterion, criterion_group, criterion_main};
+use qview::{Cursor, Suggestion, dataset::wide_database};
+
+fn search_latency(criterion: &mut Criterion) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("runtime");
+    let mut group = criterion.benchmark_group("search");
+    for tables in [10, 100, 1_000] {
+        let db = wide_database(tables, 50);
+        let sql = format!("SELECT  FROM t{} x JOIN t0 ON true", tables - 1);
+        let qualified = format!("SELECT x. FROM t{} x JOIN t0 ON true", tables - 1);
+        group.bench_with_input(BenchmarkId::new("unqualified", tables), &sql, |b, sql| {
+            b.iter(|| runtime.block_on(Suggestion::search(sql, Cursor::new(7, None), &db)))
+        });
+        group.bench_with_input(
+            BenchmarkId::new("qualified", tables),
+            &qualified,
+            |b, sql| {
+                b.iter(|| runtime.block_on(Suggestion::search(sql, Cursor::new(9, None), &db)))
+            },
+        );
+    }
+    group.finish();
+}
+
+criterion_group! {
+    name = benches;
+    config = Criterion::default().sample_size(50);
+    targets = search_latency
+}
+criterion_main!(benches);
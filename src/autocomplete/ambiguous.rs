@@ -0,0 +1,112 @@
+//! Ambiguous column detection for lint tooling.
+//!
+//! Flags unqualified projection columns that more than one table in scope
+//! provides (`SELECT id FROM a JOIN b ON ...` with `a.id` and `b.id`), which
+//! the database would reject as ambiguous.
+
+use crate::sql::{keyword::Keyword, token::Token, token_kind::TokenKind, tokenizer::tokenize};
+use crate::*;
+
+/// Unqualified columns in the projection of the SELECT scope enclosing
+/// `cursor` that exist in more than one of its table references.
+///
+/// Names are returned as written, once each, in projection order. Qualified
+/// references (`a.id`), function names (`count(`), output aliases (`AS n`
+/// or a bare `expr n`) and the words of `AT TIME ZONE` are not column
+/// references; columns are matched case-insensitively. No enclosing scope yields an empty list.
+pub async fn ambiguous_columns(sql: &str, cursor: Cursor, meta: &Database) -> Vec<String> {
+    let tokens = tokenize(sql);
+    let cursor_pos = sql.floor_char_boundary(cursor.start());
+    let Some(scope) = Scope::at(&tokens, cursor_pos) else {
+        return vec![];
+    };
+    if !tokens[scope.select_idx].is_keyword(Keyword::Select) {
+        return vec![];
+    }
+    // Inside an `ON` condition `Scope::at` narrows the refs; use the full clause.
+    let Some(scope) = Scope::from_select(&tokens, scope.select_idx) else {
+        return vec![];
+    };
+
+    let mut columns = Vec::with_capacity(scope.refs.len());
+    for r in &scope.refs {
+        columns.push(column_names(meta, &tokens, r).await);
+    }
+    let mut ambiguous: Vec<String> = Vec::new();
+    for item in scope.projection(&tokens) {
+        for name in unqualified_columns(item) {
+            let providers = columns
+                .iter()
+                .filter(|cols| cols.iter().any(|c| c.eq_ignore_ascii_case(name)))
+                .count();
+            if providers > 1 && !ambiguous.iter().any(|a| a.eq_ignore_ascii_case(name)) {
+                ambiguous.push(name.to_string());
+            }
+        }
+    }
+    ambiguous
+}
+
+/// Identifiers of a projection item that are unqualified column references.
+fn unqualified_columns(item: &[Token]) -> impl Iterator<Item = &str> {
+    item.iter().enumerate().filter_map(|(i, t)| {
+        let name = t.ident()?;
+        let prev = i.checked_sub(1).map(|p| &item[p].kind);
+        let next = item.get(i + 1).map(|n| &n.kind);
+        let qualified =
+            matches!(prev, Some(TokenKind::Dot)) || matches!(next, Some(TokenKind::Dot));
+        let call = matches!(next, Some(TokenKind::ParenOpen));
+        let alias = matches!(
+            prev,
+            Some(TokenKind::Keyword(Keyword::As) | TokenKind::Ident(_) | TokenKind::ParenClose)
+        ) && !ends_at_time_zone(&item[..i]);
+        let phrase = ends_at_time_zone(&item[..=i]); // `AT`, `TIME`, `ZONE`
+        (!qualified && !call && !alias && !phrase).then_some(name)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    async fn meta() -> Database {
+        let db = Database::new("postgres");
+        db.insert_table(
+            "public",
+            Table::new_with_ordered(
+                "a",
+                [("id", DataType::Uuid), ("name", DataType::Text(None))],
+            ),
+        )
+        .await;
+        db.insert_table(
+            "public",
+            Table::new_with_ordered("b", [("id", DataType::Uuid), ("a_id", DataType::Uuid)]),
+        )
+        .await;
+        db
+    }
+
+    #[rstest]
+    // Case 1: Shared column across a JOIN
+    #[case("SELECT id, name, a_id FROM a JOIN b ON a.id = b.a_id", vec!["id"])]
+    // Case 2: Qualified references are not ambiguous
+    #[case("SELECT a.id, b.id FROM a JOIN b ON a.id = b.a_id", vec![])]
+    // Case 3: Inside expressions, reported once
+    #[case("SELECT count(id), ID + 1 AS n FROM a, b", vec!["id"])]
+    // Case 4: Output aliases are not column references
+    #[case("SELECT name id, name AS id FROM a, b", vec![])]
+    // Case 5: The zone of `AT TIME ZONE` is a column reference, its words are not
+    #[case("SELECT name AT TIME ZONE id FROM a, b", vec!["id"])]
+    // Case 6: A single table is never ambiguous
+    #[case("SELECT id FROM a", vec![])]
+    #[tokio::test]
+    async fn reports_columns_shared_by_several_tables(
+        #[case] sql: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let result = ambiguous_columns(sql, Cursor::new(7, None), &meta().await).await;
+        assert_eq!(result, expected, "{sql}");
+    }
+}
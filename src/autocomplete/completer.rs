@@ -0,0 +1,184 @@
+//! Completion façade and result cache.
+//!
+//! A `Completer` bundles the metadata and `SearchOptions` so server code builds
+//! it once and then only passes `sql` and `cursor` per request.
+//!
+//! Editors often ask for the same completion repeatedly (re-opening a popup,
+//! re-sending an unchanged buffer), so results are remembered keyed by
+//! `(sql, cursor, metadata version)`; any metadata mutation bumps
+//! `Database::version` and thereby invalidates them.
+
+use crate::*;
+use moka::future::Cache;
+use std::sync::Arc;
+
+/// Default number of cached completion results.
+const DEFAULT_CACHE_CAPACITY: u64 = 64;
+
+/// Cache key: the buffer, the cursor and the metadata version it was computed for.
+type CacheKey = (String, Cursor, u64);
+
+/// Completion façade owning shared metadata, search options and a small
+/// result cache.
+///
+/// Cloning is cheap: clones share the metadata and the cache, so a clone can
+/// be handed to each per-request task.
+#[derive(Clone)]
+pub struct Completer {
+    metadata: Arc<Database>,
+    options: Arc<SearchOptions>,
+    cache: Cache<CacheKey, Suggestions>,
+}
+
+impl Completer {
+    /// Create a completer over `metadata` with the default cache capacity.
+    pub fn new(metadata: impl Into<Arc<Database>>) -> Self {
+        Self {
+            metadata: metadata.into(),
+            options: Arc::default(),
+            cache: Cache::new(DEFAULT_CACHE_CAPACITY),
+        }
+    }
+
+    /// Replace the search options. Previously cached results are dropped.
+    pub fn with_options(mut self, options: SearchOptions) -> Self {
+        self.options = Arc::new(options);
+        self.cache.invalidate_all();
+        self
+    }
+
+    /// Replace the cache with one holding at most `capacity` results
+    /// (`0` disables caching).
+    pub fn with_cache_capacity(mut self, capacity: u64) -> Self {
+        self.cache = Cache::new(capacity);
+        self
+    }
+
+    /// The metadata completions are computed against.
+    pub fn metadata(&self) -> &Arc<Database> {
+        &self.metadata
+    }
+
+    /// The options every completion is computed with.
+    pub fn options(&self) -> &SearchOptions {
+        &self.options
+    }
+
+    /// Complete `sql` at `cursor`, reusing a cached result when the buffer,
+    /// cursor and metadata version are unchanged. Errors are never cached.
+    pub async fn complete(&self, sql: &str, cursor: Cursor) -> Result<Suggestions> {
+        let key = (sql.to_string(), cursor, self.metadata.version());
+        if let Some(hit) = self.cache.get(&key).await {
+            return Ok(hit);
+        }
+        let suggestions =
+            Suggestion::search_with(sql, cursor, self.metadata.as_ref(), &self.options).await?;
+        self.cache.insert(key, suggestions.clone()).await;
+        Ok(suggestions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn completer() -> Completer {
+        let db = Database::new("postgres");
+        db.insert_table(
+            "public",
+            Table::new_with_ordered("a", [("id", DataType::Uuid)]),
+        )
+        .await;
+        Completer::new(db)
+    }
+
+    /// Add a column without going through `insert_*`, leaving the version as is.
+    async fn add_column_silently(db: &Database, name: &str) {
+        let schemas = db.schemas.read().await;
+        let tables = schemas["public"].tables.read().await;
+        let table = &tables["a"];
+        table
+            .columns
+            .write()
+            .await
+            .insert(name.into(), Column::new(name, DataType::Uuid));
+    }
+
+    fn names(suggestions: &Suggestions) -> Vec<String> {
+        suggestions.clone().into_labels()
+    }
+
+    #[tokio::test]
+    async fn completes_several_queries() {
+        let completer = completer().await;
+        let columns = completer
+            .complete("SELECT  FROM a", Cursor::new(7, None))
+            .await
+            .unwrap();
+        assert_eq!(names(&columns), ["id"]);
+        let tables = completer
+            .complete("SELECT * FROM ", Cursor::new(14, None))
+            .await
+            .unwrap();
+        assert_eq!(names(&tables), ["public", "a"]);
+    }
+
+    #[tokio::test]
+    async fn clones_share_metadata_and_options() {
+        let db = Database::new("postgres");
+        db.insert_table(
+            "dbo",
+            Table::new_with_ordered("#temp", [("id", DataType::Uuid)]),
+        )
+        .await;
+        let completer = Completer::new(db)
+            .with_options(SearchOptions::default().with_tokenizer(TokenizerOptions::sql_server()));
+        let clone = completer.clone();
+        assert!(Arc::ptr_eq(completer.metadata(), clone.metadata()));
+
+        let task = tokio::spawn(async move {
+            clone
+                .complete("SELECT  FROM #temp", Cursor::new(7, None))
+                .await
+        });
+        let columns = task.await.unwrap().unwrap();
+        assert_eq!(names(&columns), ["id"]);
+    }
+
+    #[tokio::test]
+    async fn repeated_completion_hits_cache() {
+        let completer = completer().await;
+        let cursor = Cursor::new(7, None);
+        let first = completer.complete("SELECT  FROM a", cursor).await.unwrap();
+        assert_eq!(names(&first), ["id"]);
+
+        // A change that does not bump the version is invisible: the result is cached.
+        add_column_silently(completer.metadata(), "hidden").await;
+        let second = completer.complete("SELECT  FROM a", cursor).await.unwrap();
+        assert_eq!(second, first);
+    }
+
+    #[tokio::test]
+    async fn metadata_change_invalidates_cache() {
+        let completer = completer().await;
+        let cursor = Cursor::new(7, None);
+        let before = completer.complete("SELECT  FROM a", cursor).await.unwrap();
+        assert_eq!(names(&before), ["id"]);
+
+        let version = completer.metadata().version();
+        completer
+            .metadata()
+            .insert_table(
+                "public",
+                Table::new_with_ordered(
+                    "a",
+                    [("id", DataType::Uuid), ("name", DataType::Text(None))],
+                ),
+            )
+            .await;
+        assert_ne!(completer.metadata().version(), version);
+
+        let after = completer.complete("SELECT  FROM a", cursor).await.unwrap();
+        assert_eq!(names(&after), ["id", "name"]);
+    }
+}
@@ -0,0 +1,108 @@
+//! Human-readable scope summaries for triaging autocomplete bugs.
+//!
+//! `explain_scope` reports what completion sees at a cursor: the chosen
+//! scope, its table references and any qualified prefix being completed.
+
+use std::fmt::Write;
+
+use crate::sql::{token::Token, token_kind::TokenKind, tokenizer::tokenize};
+use crate::*;
+
+/// Describe the scope resolved at `cursor`, one fact per line. For
+/// `SELECT o. FROM users u JOIN orders o ON ...` with the cursor after `o.`:
+///
+/// ```text
+/// scope: SELECT at byte 0 (depth 0)
+/// from: FROM at byte 10
+/// tables:
+///   - users AS u
+///   - orders AS o
+/// qualified prefix: o
+/// ```
+///
+/// Table lines are annotated with `derived`, `function`, `cross` and
+/// `ambiguous` where they apply. Without an enclosing scope only
+/// `scope: none` is reported.
+pub fn explain_scope(sql: &str, cursor: Cursor) -> String {
+    let tokens = tokenize(sql);
+    let cursor_pos = sql.floor_char_boundary(cursor.start());
+    let Some(scope) = Scope::at(&tokens, cursor_pos) else {
+        return "scope: none\n".to_string();
+    };
+
+    let mut out = String::new();
+    let select = &tokens[scope.select_idx];
+    let from = &tokens[scope.from_idx];
+    let _ = writeln!(
+        out,
+        "scope: {} at byte {} (depth {})",
+        keyword(select),
+        select.start,
+        scope.depth
+    );
+    let _ = writeln!(out, "from: {} at byte {}", keyword(from), from.start);
+    let _ = writeln!(out, "tables:");
+    for r in &scope.refs {
+        let mut line = match &r.alias {
+            Some(alias) if *alias != r.name || r.is_derived() => {
+                format!("  - {} AS {alias}", r.name)
+            }
+            _ => format!("  - {}", r.name),
+        };
+        let flags: Vec<&str> = [
+            (r.is_derived(), "derived"),
+            (r.function, "function"),
+            (r.cross, "cross"),
+            (r.ambiguous, "ambiguous"),
+        ]
+        .into_iter()
+        .filter_map(|(set, flag)| set.then_some(flag))
+        .collect();
+        if !flags.is_empty() {
+            let _ = write!(line, " ({})", flags.join(", "));
+        }
+        let _ = writeln!(out, "{line}");
+    }
+    let prefix = Suggestion::qualified_prefix(sql, &tokens, scope.select_idx, cursor_pos);
+    let _ = writeln!(
+        out,
+        "qualified prefix: {}",
+        prefix.as_deref().unwrap_or("none")
+    );
+    out
+}
+
+/// Upper-cased keyword of `token`, `?` for anything else.
+fn keyword(token: &Token) -> String {
+    match token.kind {
+        TokenKind::Keyword(k) => k.as_str().to_ascii_uppercase(),
+        _ => "?".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn explains_join_scope() {
+        let sql = "SELECT u. FROM users u JOIN orders o ON o.user_id = u.id";
+        let explained = explain_scope(sql, Cursor::new(9, None));
+        for phrase in [
+            "scope: SELECT at byte 0 (depth 0)",
+            "from: FROM at byte 10",
+            "  - users AS u\n",
+            "  - orders AS o\n",
+            "qualified prefix: u",
+        ] {
+            assert!(
+                explained.contains(phrase),
+                "missing {phrase:?} in:\n{explained}"
+            );
+        }
+        assert_eq!(
+            explain_scope("SELECT 1", Cursor::new(8, None)),
+            "scope: none\n"
+        );
+    }
+}
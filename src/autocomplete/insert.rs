@@ -0,0 +1,151 @@
+//! `INSERT ... VALUES` positional hints.
+//!
+//! Inside `INSERT INTO users (id, email) VALUES (1, |` the value being typed
+//! fills `email`; a `ValuesSlot` pins down the target table, the explicit
+//! column list (if any) and the position of the cursor within the current
+//! VALUES tuple so completion can show the expected column and type.
+
+use crate::sql::{depth::depth_delta, keyword::Keyword, token::Token, token_kind::TokenKind};
+use crate::*;
+
+/// The VALUES tuple position enclosing a cursor.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValuesSlot<'t> {
+    /// Schema qualifier of the target table (`INSERT INTO s.t`), if any.
+    pub schema: Option<&'t str>,
+    /// Target table name.
+    pub table: &'t str,
+    /// Explicit target column list; `None` means all columns in table order.
+    pub columns: Option<Vec<&'t str>>,
+    /// 0-based position of the cursor within the current VALUES tuple.
+    pub index: usize,
+}
+
+impl<'t> ValuesSlot<'t> {
+    /// Resolve the VALUES slot enclosing `cursor_pos`, if the cursor sits
+    /// inside a tuple of `INSERT INTO table [(columns)] VALUES (...), (...)`.
+    pub fn at(tokens: &'t [Token], cursor_pos: usize) -> Option<Self> {
+        let before = &tokens[..tokens.iter().take_while(|t| t.start < cursor_pos).count()];
+        let insert = before.iter().rposition(|t| t.is_keyword(Keyword::Insert))?;
+        let mut i = insert + 1;
+        if !before.get(i)?.is_keyword(Keyword::Into) {
+            return None;
+        }
+        i += 1;
+
+        // Target table: `table` or `schema.table`
+        let mut schema = None;
+        let mut table = before.get(i)?.ident()?;
+        i += 1;
+        if before
+            .get(i)
+            .is_some_and(|t| matches!(t.kind, TokenKind::Dot))
+        {
+            schema = Some(table);
+            table = before.get(i + 1)?.ident()?;
+            i += 2;
+        }
+
+        // Optional column list
+        let mut columns = None;
+        if before
+            .get(i)
+            .is_some_and(|t| matches!(t.kind, TokenKind::ParenOpen))
+        {
+            let close = i + before[i..]
+                .iter()
+                .position(|t| matches!(t.kind, TokenKind::ParenClose))?;
+            columns = Some(
+                before[i + 1..close]
+                    .iter()
+                    .filter_map(Token::ident)
+                    .collect(),
+            );
+            i = close + 1;
+        }
+        if !before.get(i)?.is_keyword(Keyword::Values) {
+            return None;
+        }
+
+        // Walk the VALUES tuples up to the cursor
+        let mut depth = 0;
+        let mut index = 0;
+        for t in &before[i + 1..] {
+            depth += depth_delta(t);
+            match (depth, &t.kind) {
+                (1, TokenKind::ParenOpen) => index = 0,
+                (1, TokenKind::Comma) => index += 1,
+                (0, TokenKind::Comma | TokenKind::ParenClose) => {}
+                (0, _) | (..0, _) => return None,
+                _ => {}
+            }
+        }
+        (depth > 0).then_some(Self {
+            schema,
+            table,
+            columns,
+            index,
+        })
+    }
+
+    /// The column (with its type) this slot fills, looked up in `meta`.
+    pub async fn target(&self, meta: &Database) -> Option<(String, DataType)> {
+        let schemas = meta.schemas.read().await;
+        for schema in meta.search_order(&schemas) {
+            if self.schema.is_some_and(|s| s != schema.name) {
+                continue;
+            }
+            let tables = schema.tables.read().await;
+            let Some(table) = tables.get(self.table) else {
+                continue;
+            };
+            let ordered = table.ordered_columns().await;
+            return match &self.columns {
+                Some(columns) => {
+                    let column = *columns.get(self.index)?;
+                    ordered.into_iter().find(|(name, _)| name == column)
+                }
+                None => ordered.into_iter().nth(self.index),
+            };
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::tokenizer::tokenize;
+
+    fn slot(sql: &str, cursor: usize) -> Option<(Option<String>, String, usize)> {
+        let tokens = tokenize(sql);
+        ValuesSlot::at(&tokens, cursor)
+            .map(|s| (s.schema.map(str::to_string), s.table.to_string(), s.index))
+    }
+
+    #[test]
+    fn counts_commas_in_the_current_tuple() {
+        let sql = "INSERT INTO users (id, email) VALUES (1, f(2, 3), ";
+        assert_eq!(slot(sql, 38), Some((None, "users".into(), 0)));
+        assert_eq!(slot(sql, 41), Some((None, "users".into(), 1)));
+        assert_eq!(slot(sql, sql.len()), Some((None, "users".into(), 2)));
+    }
+
+    #[test]
+    fn later_tuples_restart_at_zero() {
+        let sql = "INSERT INTO s.t VALUES (1, 2), (3, ";
+        assert_eq!(
+            slot(sql, sql.len()),
+            Some((Some("s".into()), "t".into(), 1))
+        );
+        assert_eq!(slot(sql, 32), Some((Some("s".into()), "t".into(), 0)));
+    }
+
+    #[test]
+    fn outside_a_tuple() {
+        let sql = "INSERT INTO t (a) VALUES (1) ";
+        assert_eq!(slot(sql, sql.len()), None);
+        assert_eq!(slot(sql, 15), None); // in the column list
+        assert_eq!(slot("SELECT (1, ", 11), None);
+    }
+}
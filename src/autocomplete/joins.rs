@@ -0,0 +1,163 @@
+//! Join condition extraction for lint tooling.
+//!
+//! Exposes the raw text of each `ON ...` predicate in the SELECT scope under
+//! a cursor, e.g. so a linter can flag joins that have no condition at all,
+//! and a heuristic check for accidental cartesian products.
+
+use crate::sql::{
+    depth::depth_delta, keyword::Keyword, token::Token, token_kind::TokenKind, tokenizer::tokenize,
+};
+use crate::*;
+
+/// The source text of every `ON` condition in the SELECT scope enclosing
+/// `cursor`, in FROM-clause order.
+///
+/// Each condition is sliced from `sql` between the first and last token of
+/// the predicate, so inner whitespace and comments are preserved verbatim.
+/// Joins without `ON` (`CROSS JOIN`, `NATURAL JOIN`, a missing condition)
+/// contribute nothing; no enclosing scope yields an empty list.
+pub fn join_conditions(sql: &str, cursor: Cursor) -> Vec<String> {
+    let tokens = tokenize(sql);
+    let cursor_pos = sql.floor_char_boundary(cursor.start());
+    let Some(scope) = Scope::at(&tokens, cursor_pos) else {
+        return vec![];
+    };
+    scope
+        .join_conditions(&tokens)
+        .into_iter()
+        .filter_map(|c| Some(sql[c.first()?.start..c.last()?.end].to_string()))
+        .collect()
+}
+
+/// True if the SELECT scope enclosing `cursor` likely builds an accidental
+/// cartesian product.
+///
+/// Heuristic: the FROM item plus every reference joined with a comma or
+/// `CROSS JOIN` form the unconstrained set. There is a risk when that set
+/// holds two or more references and the scope's `WHERE` clause does not
+/// qualify a column with each of them (`a.id = b.a_id` correlates `a` and
+/// `b`). Unqualified column references are not attributed to any table, and
+/// `ON` conditions of other joins do not count as correlation.
+pub fn has_cartesian_risk(sql: &str, cursor: Cursor) -> bool {
+    let tokens = tokenize(sql);
+    let cursor_pos = sql.floor_char_boundary(cursor.start());
+    let Some(scope) = Scope::at(&tokens, cursor_pos) else {
+        return false;
+    };
+    // Inside an `ON` condition `Scope::at` narrows the refs; use the full clause.
+    let Some(scope) = Scope::from_select(&tokens, scope.select_idx) else {
+        return false;
+    };
+    let unconstrained: Vec<&str> = scope
+        .refs
+        .iter()
+        .enumerate()
+        .filter(|(i, r)| *i == 0 || r.cross)
+        .map(|(_, r)| r.reference())
+        .collect();
+    if unconstrained.len() < 2 {
+        return false;
+    }
+    let qualifiers = where_qualifiers(&tokens, &scope);
+    !unconstrained.iter().all(|r| qualifiers.contains(r))
+}
+
+/// Identifiers used as `qualifier.` inside the scope's `WHERE` clause.
+fn where_qualifiers<'t>(tokens: &'t [Token], scope: &Scope) -> Vec<&'t str> {
+    let mut depth = scope.depth;
+    let mut in_where = false;
+    let mut qualifiers = Vec::new();
+    for (idx, t) in tokens.iter().enumerate().skip(scope.from_idx + 1) {
+        depth += depth_delta(t);
+        if depth < scope.depth {
+            break;
+        }
+        if depth == scope.depth {
+            match &t.kind {
+                TokenKind::Keyword(Keyword::Where) => in_where = true,
+                TokenKind::Keyword(k) if Keyword::TERMINATORS.contains(k) && in_where => break,
+                _ => {}
+            }
+        }
+        let qualifies = tokens
+            .get(idx + 1)
+            .is_some_and(|n| matches!(n.kind, TokenKind::Dot));
+        if in_where
+            && qualifies
+            && let Some(q) = t.ident()
+        {
+            qualifiers.push(q);
+        }
+    }
+    qualifiers
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    fn conditions(sql: &str, cursor: usize) -> Vec<String> {
+        join_conditions(sql, Cursor::new(cursor, None))
+    }
+
+    #[test]
+    fn two_joins_yield_two_conditions() {
+        let sql = "SELECT * FROM a JOIN b ON a.id = b.a_id LEFT JOIN c ON (c.b_id = b.id AND c.x > 1) WHERE true";
+        assert_eq!(
+            conditions(sql, 7),
+            ["a.id = b.a_id", "(c.b_id = b.id AND c.x > 1)"]
+        );
+    }
+
+    #[test]
+    fn joins_without_on_contribute_nothing() {
+        let sql = "SELECT * FROM a CROSS JOIN b JOIN c ON c.id = a.id, d";
+        assert_eq!(conditions(sql, 7), ["c.id = a.id"]);
+        assert!(conditions("SELECT * FROM a JOIN b ON ", 7).is_empty());
+        assert!(conditions("SELECT * FROM a, b", 7).is_empty());
+    }
+
+    #[test]
+    fn only_the_enclosing_scope_is_considered() {
+        let sql = "SELECT * FROM a JOIN (SELECT * FROM b JOIN c ON b.id = c.id) s ON s.id = a.id";
+        assert_eq!(conditions(sql, 7), ["s.id = a.id"]);
+        assert_eq!(conditions(sql, 30), ["b.id = c.id"]);
+    }
+
+    #[test]
+    fn no_scope_yields_nothing() {
+        assert!(conditions("", 0).is_empty());
+        assert!(conditions("SELECT 1", 8).is_empty());
+    }
+
+    #[rstest]
+    // Case 1: Comma join without WHERE
+    #[case("SELECT * FROM a, b", true)]
+    // Case 2: Comma join correlated in WHERE
+    #[case("SELECT * FROM a, b WHERE a.id=b.a_id", false)]
+    // Case 3: CROSS JOIN without correlation
+    #[case("SELECT * FROM a CROSS JOIN b WHERE a.x = 1", true)]
+    // Case 4: Aliases are what the WHERE clause qualifies with
+    #[case("SELECT * FROM a x, b y WHERE x.id = y.a_id", false)]
+    // Case 5: A third comma-joined table left uncorrelated
+    #[case("SELECT * FROM a, b, c WHERE a.id = b.a_id", true)]
+    // Case 6: Regular joins with conditions
+    #[case("SELECT * FROM a JOIN b ON a.id = b.a_id", false)]
+    // Case 7: Single table
+    #[case("SELECT * FROM a", false)]
+    // Case 8: Stray commas do not add references
+    #[case("SELECT * FROM , a, , WHERE a.x = 1", false)]
+    // Case 9: Qualifiers after GROUP BY do not correlate
+    #[case("SELECT * FROM a, b WHERE a.x = 1 GROUP BY b.id", true)]
+    fn cartesian_risk(#[case] sql: &str, #[case] expected: bool) {
+        assert_eq!(has_cartesian_risk(sql, Cursor::new(7, None)), expected);
+    }
+
+    #[test]
+    fn cartesian_risk_is_scoped() {
+        let sql = "SELECT * FROM a WHERE a.id IN (SELECT b.id FROM b, c)";
+        assert!(!has_cartesian_risk(sql, Cursor::new(7, None)));
+        assert!(has_cartesian_risk(sql, Cursor::new(38, None)));
+    }
+}
@@ -1,2 +1,10 @@
+crate::reexport!(ambiguous);
+crate::reexport!(completer);
+crate::reexport!(explain);
+crate::reexport!(insert);
+crate::reexport!(joins);
+crate::reexport!(resolve);
+crate::reexport!(scope);
+crate::reexport!(star);
 crate::reexport!(suggestion);
 mod suggestion_tests;
@@ -0,0 +1,84 @@
+//! Canonical table lookup for qualified prefixes.
+//!
+//! Lets editors annotate `u.` with "resolves to public.users" by following
+//! the alias map of the enclosing scope into metadata.
+
+use crate::sql::tokenizer::tokenize;
+use crate::*;
+
+/// The `(schema, table)` that the qualified prefix at `cursor` (`u.` in
+/// `SELECT u. FROM users u`) refers to.
+///
+/// The prefix is resolved through the aliases and table names of the
+/// enclosing scope; a schema written in the query (`FROM app.users u`) is
+/// kept, otherwise the table is looked up along `Database::search_path`.
+/// Returns `None` without a prefix, for prefixes the scope does not declare
+/// or declares ambiguously, for derived tables and table functions, and for
+/// tables missing from `meta`.
+pub async fn resolve_prefix(
+    sql: &str,
+    cursor: Cursor,
+    meta: &Database,
+) -> Option<(String, String)> {
+    let tokens = tokenize(sql);
+    let cursor_pos = sql.floor_char_boundary(cursor.start());
+    let scope = Scope::at(&tokens, cursor_pos)?;
+    let prefix = Suggestion::qualified_prefix(sql, &tokens, scope.select_idx, cursor_pos)?;
+    if scope.is_ambiguous(&prefix) {
+        return None;
+    }
+    let table = scope
+        .find(&prefix)
+        .filter(|r| !r.is_derived() && !r.function)?;
+    let schema = meta
+        .table_schema(table.schema.as_deref(), &table.name)
+        .await?;
+    Some((schema, table.name.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    async fn meta() -> Database {
+        let meta = Database::new("postgres");
+        meta.insert_table(
+            "public",
+            Table::new_with_ordered("users", [("id", DataType::Uuid)]),
+        )
+        .await;
+        meta.insert_table(
+            "app",
+            Table::new_with_ordered("orders", [("id", DataType::Uuid)]),
+        )
+        .await;
+        meta
+    }
+
+    #[rstest]
+    // Case 1: Alias
+    #[case("SELECT u. FROM users u", 9, Some(("public", "users")))]
+    // Case 2: Bare table name
+    #[case("SELECT users. FROM users", 13, Some(("public", "users")))]
+    // Case 3: Schema written in the query
+    #[case("SELECT o.id FROM users u JOIN app.orders o ON true", 9, Some(("app", "orders")))]
+    // Case 4: Unknown prefix
+    #[case("SELECT z. FROM users u", 9, None)]
+    // Case 5: Table in scope but not in metadata
+    #[case("SELECT g. FROM ghosts g", 9, None)]
+    // Case 6: Derived table
+    #[case("SELECT s. FROM (SELECT 1 AS one) s", 9, None)]
+    // Case 7: No prefix
+    #[case("SELECT  FROM users u", 7, None)]
+    #[tokio::test]
+    async fn resolves_prefix_to_schema_and_table(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Option<(&str, &str)>,
+    ) {
+        let resolved = resolve_prefix(sql, Cursor::new(cursor, None), &meta().await).await;
+        let expected = expected.map(|(schema, table)| (schema.to_string(), table.to_string()));
+        assert_eq!(resolved, expected, "{sql}");
+    }
+}
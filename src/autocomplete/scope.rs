@@ -0,0 +1,1372 @@
+//! SELECT scope resolution shared by the completion and refactoring helpers.
+//!
+//! A `Scope` answers "which tables can the cursor see?": it pins down the
+//! innermost `SELECT` enclosing the cursor, its matching `FROM` and the table
+//! references (with aliases) listed there. Everything is derived from the
+//! token stream only; metadata lookups happen in the callers.
+
+use crate::sql::{
+    depth::{depth_before, depth_delta},
+    keyword::Keyword,
+    statement::statement_separators,
+    token::Token,
+    token_kind::TokenKind,
+};
+
+/// A table referenced in a FROM clause together with its optional alias.
+///
+/// Derived tables (`(SELECT ...) AS sub`) use their alias as `name` and record
+/// the index of the inner `SELECT` token in `subquery`. References to a
+/// common table expression (`WITH x AS (SELECT ...) ... FROM x`) keep the
+/// CTE's name and record its `SELECT` the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableRef {
+    pub name: String,
+    /// The schema qualifying `name` in the query (`FROM public.users`).
+    /// Unqualified names resolve through `Database::search_path`.
+    pub schema: Option<String>,
+    pub alias: Option<String>,
+    pub subquery: Option<usize>,
+    /// True if joined as a cartesian product: listed after a comma or joined
+    /// with `CROSS JOIN`. The first FROM item is never marked.
+    pub cross: bool,
+    /// True if `name` is a table function called in FROM (`unnest(arr) u`).
+    pub function: bool,
+    /// True if another item of the same FROM clause declares the same alias
+    /// (`FROM a x, b x`), so qualifying with it is ambiguous. An alias
+    /// matching an unaliased table name is not: the alias shadows it.
+    pub ambiguous: bool,
+}
+
+impl TableRef {
+    /// A base table reference.
+    pub fn table(name: impl Into<String>, alias: Option<String>) -> Self {
+        Self {
+            name: name.into(),
+            schema: None,
+            alias,
+            subquery: None,
+            cross: false,
+            function: false,
+            ambiguous: false,
+        }
+    }
+
+    /// A table function call (`generate_series(1, 10) AS g`).
+    pub fn function(name: impl Into<String>, alias: Option<String>) -> Self {
+        Self {
+            function: true,
+            ..Self::table(name, alias)
+        }
+    }
+
+    /// A derived table: the subquery starting at `select_idx`, named `alias`.
+    pub fn derived(alias: impl Into<String>, select_idx: usize) -> Self {
+        let alias = alias.into();
+        Self {
+            name: alias.clone(),
+            schema: None,
+            alias: Some(alias),
+            subquery: Some(select_idx),
+            cross: false,
+            function: false,
+            ambiguous: false,
+        }
+    }
+
+    /// The name this table is referred to by inside the query (alias first).
+    pub fn reference(&self) -> &str {
+        self.alias.as_deref().unwrap_or(&self.name)
+    }
+
+    /// True if this reference is a derived table (parenthesized subquery).
+    pub fn is_derived(&self) -> bool {
+        self.subquery.is_some()
+    }
+}
+
+/// The SELECT (or UPDATE, or MERGE) scope enclosing a cursor position.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Scope {
+    /// Index of the scope's `SELECT` (or `UPDATE`, or `MERGE`) token.
+    pub select_idx: usize,
+    /// Parenthesis depth of the `SELECT` token.
+    pub depth: i32,
+    /// Index of the matching `FROM` token. An `UPDATE` without `FROM` uses
+    /// its `SET` token, a `MERGE` its `USING` token.
+    pub from_idx: usize,
+    /// Table references in FROM-clause order.
+    pub refs: Vec<TableRef>,
+}
+
+impl Scope {
+    /// Resolve the scope enclosing `cursor_pos`.
+    ///
+    /// Returns `None` when no `SELECT` encloses the cursor or when that
+    /// `SELECT` has no `FROM` yet.
+    ///
+    /// Inside a join condition (`FROM a JOIN b ON | JOIN c`) only the tables
+    /// joined so far (`a` and `b`) are in scope.
+    ///
+    /// An `UPDATE` later than the innermost enclosing `SELECT` opens an
+    /// UPDATE scope instead (see `from_update`), and a `MERGE` a MERGE scope
+    /// (see `from_merge`). The `UPDATE` of a `WHEN MATCHED` action belongs to
+    /// its `MERGE`.
+    pub fn at(tokens: &[Token], cursor_pos: usize) -> Option<Self> {
+        let select_idx = locate_select(tokens, cursor_pos);
+        if let Some(merge_idx) = locate_merge(tokens, cursor_pos)
+            && select_idx.is_none_or(|s| s < merge_idx)
+        {
+            return Self::from_merge(tokens, merge_idx);
+        }
+        if let Some(update_idx) = locate_update(tokens, cursor_pos)
+            && select_idx.is_none_or(|s| s < update_idx)
+        {
+            return Self::from_update(tokens, update_idx);
+        }
+        let mut scope = Self::from_select(tokens, select_idx?)?;
+        if let Some(on_idx) = locate_on(tokens, &scope, cursor_pos) {
+            scope.refs = extract_tables(&tokens[..on_idx], scope.from_idx, scope.depth);
+        }
+        Some(scope)
+    }
+
+    /// Resolve the scope opened by the `SELECT` token at `select_idx`.
+    pub fn from_select(tokens: &[Token], select_idx: usize) -> Option<Self> {
+        let depth = depth_before(tokens, select_idx);
+        let from_idx = locate_from(tokens, select_idx, depth)?;
+        let refs = extract_tables(tokens, from_idx, depth);
+        Some(Self {
+            select_idx,
+            depth,
+            from_idx,
+            refs,
+        })
+    }
+
+    /// Resolve the scope of `UPDATE target [alias] SET ... [FROM ...]` whose
+    /// `UPDATE` token is at `update_idx`.
+    ///
+    /// The target table comes first, followed by the references of the
+    /// optional Postgres `FROM` list. Returns `None` until `SET` is typed.
+    pub fn from_update(tokens: &[Token], update_idx: usize) -> Option<Self> {
+        let depth = depth_before(tokens, update_idx);
+        let mut i = update_idx + 1;
+        // `UPDATE ONLY t`
+        if tokens
+            .get(i)
+            .and_then(Token::ident)
+            .is_some_and(|w| w.eq_ignore_ascii_case("only"))
+            && tokens.get(i + 1).and_then(Token::ident).is_some()
+        {
+            i += 1;
+        }
+        let mut name = tokens.get(i)?.ident()?;
+        let mut schema = None;
+        if tokens
+            .get(i + 1)
+            .is_some_and(|t| matches!(t.kind, TokenKind::Dot))
+        {
+            schema = Some(name.to_string());
+            name = tokens.get(i + 2)?.ident()?; // `schema.table`
+            i += 2;
+        }
+        i += 1;
+        let alias = alias_at(tokens, i).map(|(alias, _)| alias.to_string());
+        let set_idx = i + tokens[i..]
+            .iter()
+            .position(|t| t.is_keyword(Keyword::Set))?;
+
+        let mut refs = vec![TableRef {
+            schema,
+            ..TableRef::table(name, alias)
+        }];
+        let mut from_idx = set_idx;
+        let separators = statement_separators(tokens);
+        let mut d = depth;
+        for (idx, t) in tokens.iter().enumerate().skip(set_idx + 1) {
+            d += depth_delta(t);
+            if d < depth || (d == depth && separators.contains(&idx)) {
+                break;
+            }
+            if d != depth {
+                continue;
+            }
+            if t.is_keyword(Keyword::From) {
+                from_idx = idx;
+                refs.extend(extract_tables(tokens, idx, depth));
+                break;
+            }
+            if matches!(&t.kind, TokenKind::Keyword(k) if Keyword::TERMINATORS.contains(k)) {
+                break;
+            }
+        }
+        Some(Self {
+            select_idx: update_idx,
+            depth,
+            from_idx,
+            refs,
+        })
+    }
+
+    /// Resolve the scope of `MERGE INTO target [alias] USING source [alias]
+    /// ON ... WHEN ...` whose `MERGE` token is at `merge_idx`.
+    ///
+    /// The target table comes first, followed by the source: a table or an
+    /// aliased subquery. Both are visible in the `ON` condition and the `WHEN`
+    /// clauses. Returns `None` until `USING` is typed.
+    pub fn from_merge(tokens: &[Token], merge_idx: usize) -> Option<Self> {
+        let depth = depth_before(tokens, merge_idx);
+        let mut i = merge_idx + 1;
+        if tokens.get(i).is_some_and(|t| t.is_keyword(Keyword::Into)) {
+            i += 1;
+        }
+        let mut name = tokens.get(i)?.ident()?;
+        let mut schema = None;
+        if tokens
+            .get(i + 1)
+            .is_some_and(|t| matches!(t.kind, TokenKind::Dot))
+        {
+            schema = Some(name.to_string());
+            name = tokens.get(i + 2)?.ident()?; // `schema.table`
+            i += 2;
+        }
+        i += 1;
+        let alias = alias_at(tokens, i).map(|(alias, _)| alias.to_string());
+        let using_idx = i + tokens[i..]
+            .iter()
+            .position(|t| t.is_keyword(Keyword::Using))?;
+
+        // The source ends at the `ON` of the merge condition.
+        let mut on_idx = tokens.len();
+        let mut d = depth;
+        for (idx, t) in tokens.iter().enumerate().skip(using_idx + 1) {
+            d += depth_delta(t);
+            if d == depth && t.is_keyword(Keyword::On) {
+                on_idx = idx;
+                break;
+            }
+        }
+        let mut refs = vec![TableRef {
+            schema,
+            ..TableRef::table(name, alias)
+        }];
+        refs.extend(extract_tables(&tokens[..on_idx], using_idx, depth));
+        Some(Self {
+            select_idx: merge_idx,
+            depth,
+            from_idx: using_idx,
+            refs,
+        })
+    }
+
+    /// Projection items between `SELECT` and `FROM`, split on top-level commas.
+    ///
+    /// A leading `DISTINCT`, `DISTINCT ON (...)` or `ALL` qualifies the whole
+    /// list and is not part of the first item.
+    pub fn projection<'t>(&self, tokens: &'t [Token]) -> Vec<&'t [Token]> {
+        let mut list = &tokens[self.select_idx + 1..self.from_idx];
+        let word = |t: &Token, w: &str| t.ident().is_some_and(|i| i.eq_ignore_ascii_case(w));
+        if let [first, rest @ ..] = list
+            && (word(first, "distinct") || word(first, "all"))
+        {
+            list = rest;
+            if let [on, group @ ..] = list
+                && word(first, "distinct")
+                && on.is_keyword(Keyword::On)
+            {
+                list = call_len(group).map_or(&[], |len| &group[len..]);
+            }
+        }
+        let mut items = Vec::new();
+        let mut depth = 0;
+        let mut start = 0;
+        for (i, t) in list.iter().enumerate() {
+            depth += depth_delta(t);
+            if depth == 0 && matches!(t.kind, TokenKind::Comma) {
+                items.push(&list[start..i]);
+                start = i + 1;
+            }
+        }
+        items.push(&list[start..]);
+        items.retain(|item| !item.is_empty());
+        items
+    }
+
+    /// Output aliases declared in the projection (`a.id AS k`, `count(*) n`,
+    /// `'x' label`), in projection order.
+    pub fn projection_aliases<'t>(&self, tokens: &'t [Token]) -> Vec<&'t str> {
+        self.projection(tokens)
+            .into_iter()
+            .filter_map(|item| match item {
+                // `ts AT TIME ZONE tz`: `tz` is the zone, not an alias
+                [rest @ .., _] if ends_at_time_zone(rest) => None,
+                [.., prev, last] => match prev.kind {
+                    TokenKind::Keyword(Keyword::As)
+                    | TokenKind::Ident(_)
+                    | TokenKind::ParenClose
+                    | TokenKind::String(_)
+                    | TokenKind::Number(_) => last.ident(),
+                    _ => None,
+                },
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Join conditions of the FROM clause, one token slice per `ON`, in order.
+    ///
+    /// A condition runs until the next `JOIN` (or join modifier), comma,
+    /// terminating keyword or the end of the scope; an `ON` with nothing after
+    /// it yields no slice.
+    pub fn join_conditions<'t>(&self, tokens: &'t [Token]) -> Vec<&'t [Token]> {
+        let mut conditions = Vec::new();
+        let mut depth = self.depth;
+        let mut start = None; // First token of the current condition
+        let mut end = tokens.len();
+        let separators = statement_separators(tokens);
+        for (idx, t) in tokens.iter().enumerate().skip(self.from_idx + 1) {
+            depth += depth_delta(t);
+            if depth < self.depth {
+                end = idx;
+                break;
+            }
+            if depth != self.depth {
+                continue;
+            }
+            match &t.kind {
+                TokenKind::Keyword(k) if Keyword::TERMINATORS.contains(k) => {
+                    end = idx;
+                    break;
+                }
+                TokenKind::Other(';') if separators.contains(&idx) => {
+                    end = idx;
+                    break;
+                }
+                TokenKind::Keyword(Keyword::On) => {
+                    if let Some(start) = start.replace(idx + 1) {
+                        conditions.push(&tokens[start..idx]);
+                    }
+                }
+                TokenKind::Keyword(k) if *k == Keyword::Join || k.is_join_modifier() => {
+                    if let Some(start) = start.take() {
+                        conditions.push(&tokens[start..idx]);
+                    }
+                }
+                TokenKind::Comma => {
+                    if let Some(start) = start.take() {
+                        conditions.push(&tokens[start..idx]);
+                    }
+                }
+                _ => {}
+            }
+        }
+        if let Some(start) = start {
+            conditions.push(&tokens[start..end]);
+        }
+        conditions.retain(|c| !c.is_empty());
+        conditions
+    }
+
+    /// True if `cursor_pos` lies in this scope's `GROUP BY` list.
+    pub fn in_group_by(&self, tokens: &[Token], cursor_pos: usize) -> bool {
+        let mut depth = self.depth;
+        let mut in_group_by = false;
+        for (idx, t) in tokens.iter().enumerate().skip(self.from_idx + 1) {
+            if t.start >= cursor_pos {
+                break;
+            }
+            depth += depth_delta(t);
+            if depth < self.depth {
+                return false;
+            }
+            if depth != self.depth {
+                continue;
+            }
+            // `BY` continues the clause its `GROUP` / `ORDER` opened.
+            if let TokenKind::Keyword(k) = &t.kind
+                && *k != Keyword::By
+            {
+                in_group_by = *k == Keyword::Group
+                    && tokens
+                        .get(idx + 1)
+                        .is_some_and(|by| by.is_keyword(Keyword::By) && by.end < cursor_pos);
+            }
+        }
+        in_group_by
+    }
+
+    /// Distinct base table names in FROM-clause order (derived tables excluded).
+    pub fn tables(&self) -> Vec<&str> {
+        let mut tables: Vec<&str> = Vec::new();
+        for r in self.refs.iter().filter(|r| !r.is_derived()) {
+            if !tables.contains(&r.name.as_str()) {
+                tables.push(&r.name);
+            }
+        }
+        tables
+    }
+
+    /// The table an alias points to. When an alias is declared twice the last
+    /// declaration wins (see `is_ambiguous`).
+    pub fn alias_target(&self, alias: &str) -> Option<&str> {
+        self.refs
+            .iter()
+            .rev()
+            .find(|r| r.alias.as_deref() == Some(alias))
+            .map(|r| r.name.as_str())
+    }
+
+    /// True if `qualifier` is an alias declared more than once (`FROM a x, b x`).
+    pub fn is_ambiguous(&self, qualifier: &str) -> bool {
+        self.refs
+            .iter()
+            .any(|r| r.ambiguous && r.reference() == qualifier)
+    }
+
+    /// Find the reference a qualifier (alias or table name) denotes.
+    ///
+    /// Aliases shadow same-named tables.
+    pub fn find(&self, qualifier: &str) -> Option<&TableRef> {
+        self.refs
+            .iter()
+            .rev()
+            .find(|r| r.alias.as_deref() == Some(qualifier))
+            .or_else(|| self.refs.iter().find(|r| r.name == qualifier))
+    }
+
+    /// Find the reference `qualifier` denotes in this scope or, for correlated
+    /// subqueries (`WHERE EXISTS (SELECT 1 FROM b WHERE b.x = a.|)`), in the
+    /// nearest enclosing scope that declares it.
+    pub fn find_correlated(&self, tokens: &[Token], qualifier: &str) -> Option<TableRef> {
+        if let Some(r) = self.find(qualifier) {
+            return Some(r.clone());
+        }
+        let mut outer = self.enclosing(tokens)?;
+        loop {
+            if let Some(r) = outer.find(qualifier) {
+                return Some(r.clone());
+            }
+            outer = outer.enclosing(tokens)?;
+        }
+    }
+
+    /// The scope of the `SELECT` whose parentheses enclose this one's, if any.
+    ///
+    /// A `UNION` sibling at the same depth is not an enclosing scope.
+    pub fn enclosing(&self, tokens: &[Token]) -> Option<Self> {
+        // Walk backwards tracking the depth just before each token; the first
+        // SELECT below every depth seen since our own SELECT encloses it.
+        let mut depth = self.depth;
+        let mut min = self.depth;
+        for idx in (0..self.select_idx).rev() {
+            depth -= depth_delta(&tokens[idx]);
+            min = min.min(depth);
+            if depth < self.depth && depth == min && tokens[idx].is_keyword(Keyword::Select) {
+                return Self::from_select(tokens, idx);
+            }
+        }
+        None
+    }
+
+    /// Resolve a qualifier (alias or table name) to the table it denotes.
+    ///
+    /// Aliases shadow same-named tables; anything else is assumed to already
+    /// be a table name.
+    pub fn resolve<'a>(&'a self, qualifier: &'a str) -> &'a str {
+        self.alias_target(qualifier).unwrap_or(qualifier)
+    }
+}
+
+/// Locate the index of the innermost `SELECT` token whose scope encloses
+/// `cursor_pos`.
+///
+/// Depth counting allows distinguishing nested subqueries: only tokens
+/// at the same depth as the matching `FROM` should be considered. A
+/// subquery's `SELECT` stops being a candidate once its parenthesis closes,
+/// so a cursor placed after `WHERE x IN (SELECT ...)` falls back to the
+/// outer query instead of the already-closed subquery. Parenthesized
+/// expression groups (`(a.x, a.y)`, `ROW(...)`) hold no `SELECT` and so
+/// never open a scope of their own.
+fn locate_select(tokens: &[Token], cursor_pos: usize) -> Option<usize> {
+    let mut depth = 0;
+    let mut open: Vec<(usize, i32)> = Vec::new();
+    for (idx, t) in tokens.iter().enumerate() {
+        if t.start >= cursor_pos {
+            break;
+        }
+        depth += depth_delta(t);
+        // Closing a group ends every SELECT opened inside it.
+        while open.last().is_some_and(|&(_, d)| d > depth) {
+            open.pop();
+        }
+        if t.is_keyword(Keyword::Select) {
+            open.push((idx, depth));
+        }
+    }
+    open.last().map(|&(idx, _)| idx)
+}
+
+/// Locate the index of the last `UPDATE` token before `cursor_pos`, unless a
+/// `;` (outside string literals) has ended its statement.
+fn locate_update(tokens: &[Token], cursor_pos: usize) -> Option<usize> {
+    let separators = statement_separators(tokens);
+    let mut update = None;
+    for (idx, t) in tokens
+        .iter()
+        .enumerate()
+        .take_while(|(_, t)| t.start < cursor_pos)
+    {
+        match &t.kind {
+            TokenKind::Keyword(Keyword::Update) => update = Some(idx),
+            TokenKind::Other(';') if separators.contains(&idx) => update = None,
+            _ => {}
+        }
+    }
+    update
+}
+
+/// Locate the index of the last `MERGE` token before `cursor_pos`, unless a
+/// `;` (outside string literals) has ended its statement.
+fn locate_merge(tokens: &[Token], cursor_pos: usize) -> Option<usize> {
+    let separators = statement_separators(tokens);
+    let mut merge = None;
+    for (idx, t) in tokens
+        .iter()
+        .enumerate()
+        .take_while(|(_, t)| t.start < cursor_pos)
+    {
+        match &t.kind {
+            TokenKind::Keyword(Keyword::Merge) => merge = Some(idx),
+            TokenKind::Other(';') if separators.contains(&idx) => merge = None,
+            _ => {}
+        }
+    }
+    merge
+}
+
+/// From a previously found `SELECT` token, scan forward to find the
+/// corresponding `FROM` token at the same parenthesis depth.
+///
+/// Returns the index of that `FROM` token if found.
+fn locate_from(tokens: &[Token], select_idx: usize, select_depth: i32) -> Option<usize> {
+    let mut depth = select_depth;
+    for (idx, t) in tokens.iter().enumerate().skip(select_idx + 1) {
+        depth += depth_delta(t);
+        if depth == select_depth && t.is_keyword(Keyword::From) {
+            return Some(idx);
+        }
+    }
+    None
+}
+
+/// Find the `ON` token whose join condition encloses `cursor_pos`, if any.
+///
+/// Scans the scope's FROM clause at its own depth; a later `JOIN`, a comma or
+/// a terminating keyword ends the condition.
+fn locate_on(tokens: &[Token], scope: &Scope, cursor_pos: usize) -> Option<usize> {
+    let mut depth = scope.depth;
+    let mut on = None;
+    for (idx, t) in tokens.iter().enumerate().skip(scope.from_idx + 1) {
+        if t.start >= cursor_pos {
+            break;
+        }
+        depth += depth_delta(t);
+        if depth < scope.depth {
+            return None;
+        }
+        if depth != scope.depth {
+            continue;
+        }
+        match &t.kind {
+            TokenKind::Keyword(Keyword::On) => on = Some(idx),
+            TokenKind::Keyword(Keyword::Join) | TokenKind::Comma => on = None,
+            TokenKind::Keyword(k) if Keyword::TERMINATORS.contains(k) => return None,
+            _ => {}
+        }
+    }
+    on
+}
+
+/// Extract table references and aliases beginning just after the `FROM` token.
+///
+/// Parsing rules (simplified):
+/// - Continue until depth decreases below `select_depth` or a terminating
+///   keyword (e.g. WHERE, GROUP, ORDER, etc.) at the same depth is found.
+/// - Handle comma separated tables and JOIN clauses, skipping the JOIN keyword
+///   and any join modifiers (`LEFT OUTER`, `NATURAL`, `CROSS`, `LATERAL`, ...).
+///   A modifier word where a table is expected (`FROM outer`) is read as the
+///   table name instead, except `LATERAL`.
+/// - Skip join conditions (`ON ...`) up to the next JOIN or comma.
+/// - Mark references following a comma or `CROSS JOIN` as `cross`.
+/// - Stray commas (`FROM , a`, `FROM a, , b`, `FROM a,`) only separate
+///   slots; they are never read as a table or alias.
+/// - Support aliases in the forms: `table AS alias` and `table alias`.
+/// - Skip the inheritance markers `ONLY` before a table (`FROM ONLY t`) and
+///   `*` after it (`FROM parent*`); neither becomes a name or alias.
+/// - A parenthesized subquery followed by an alias (`(SELECT ...) sub` or
+///   `(SELECT ...) AS sub`) is registered as a derived table.
+/// - A name followed by `(` is a table function call (`generate_series(1,
+///   10) AS g`); the alias follows its closing paren. An unclosed call
+///   ends the FROM clause.
+/// - Items declaring the same alias (`FROM a x, b x`) are all marked
+///   `ambiguous`.
+/// - An unqualified name declared by a preceding `WITH` clause is a CTE
+///   reference, resolved to the CTE's `SELECT` (see `cte_select`).
+/// - A parenthesized subquery without an alias (`FROM (SELECT id FROM a)`,
+///   common mid-edit) contributes nothing: its tokens sit at a deeper depth
+///   and are never mistaken for top-level tables.
+fn extract_tables(tokens: &[Token], from_idx: usize, select_depth: i32) -> Vec<TableRef> {
+    let mut refs = Vec::new();
+    let mut depth = select_depth;
+    let mut group_start = None; // Index of the `(` opening the current group
+    let mut in_condition = false; // Inside an `ON ...` join condition
+    let mut expecting_table = true; // Right after FROM, a comma or JOIN
+    let mut cross = false; // The next reference follows a comma or CROSS JOIN
+    let mut i = from_idx + 1; // Start after the FROM token
+
+    while let Some(t) = tokens.get(i) {
+        // 1. Handle parenthesis tracking to respect nesting depth
+        let delta = depth_delta(t);
+        if delta != 0 {
+            depth += delta;
+            if depth < select_depth {
+                break; // Exit if we've closed out of our SELECT scope
+            }
+            if delta > 0 && depth == select_depth + 1 {
+                group_start = Some(i);
+            }
+            i += 1;
+
+            // Closing a subquery group: register it under its alias, if any
+            let subquery = group_start
+                .filter(|_| delta < 0 && depth == select_depth)
+                .map(|g| g + 1)
+                .filter(|&s| tokens.get(s).is_some_and(|x| x.is_keyword(Keyword::Select)));
+            if let Some(select_idx) = subquery {
+                group_start = None;
+                if let Some((alias, len)) = alias_at(tokens, i) {
+                    refs.push(TableRef {
+                        cross: std::mem::take(&mut cross),
+                        ..TableRef::derived(alias, select_idx)
+                    });
+                    expecting_table = false;
+                    i += len;
+                }
+            }
+            continue;
+        }
+
+        // 2. Only process tokens at our target SELECT depth
+        if depth != select_depth {
+            i += 1;
+            continue;
+        }
+
+        // 3. Handle terminating keywords and JOIN clauses
+        if let Some(k) = t.keyword() {
+            if Keyword::TERMINATORS.contains(&k) {
+                break; // Stop at WHERE, GROUP BY, ORDER BY, etc.
+            }
+            if k == Keyword::Join {
+                in_condition = false;
+                expecting_table = true;
+                cross = i > 0 && tokens[i - 1].is_keyword(Keyword::Cross);
+                i += 1;
+                continue; // Skip JOIN keyword itself
+            }
+            let as_table = expecting_table && k != Keyword::Lateral;
+            if k.is_join_modifier() && !in_condition && !as_table {
+                i += 1;
+                continue; // Skip LEFT / OUTER / NATURAL / LATERAL ...
+            }
+            if k == Keyword::On {
+                in_condition = true;
+            }
+        }
+        if matches!(t.kind, TokenKind::Comma) {
+            in_condition = false;
+            expecting_table = true;
+            cross = !refs.is_empty(); // A stray leading comma joins nothing
+        }
+        if in_condition {
+            i += 1;
+            continue; // Join condition operands are not table references
+        }
+
+        // 4. Extract table names and handle aliasing patterns. Where a table
+        //    is expected, an unquoted join modifier word (`FROM inner`) is
+        //    leniently read as the table name.
+        // `FROM ONLY t`
+        if expecting_table
+            && t.ident().is_some_and(|w| w.eq_ignore_ascii_case("only"))
+            && tokens.get(i + 1).and_then(Token::ident).is_some()
+        {
+            i += 1;
+            continue;
+        }
+        let name = match &t.kind {
+            TokenKind::Ident(name) => Some(name.as_str()),
+            TokenKind::Keyword(k) if expecting_table && k.is_join_modifier() => Some(k.as_str()),
+            _ => None,
+        };
+        if let Some(name) = name {
+            let mut name = name.to_string();
+            expecting_table = false;
+
+            // `schema.table`: the name is the word after the dot
+            let mut schema = None;
+            let qualified = tokens
+                .get(i + 1)
+                .is_some_and(|t| matches!(t.kind, TokenKind::Dot));
+            if let Some(table) = tokens
+                .get(i + 2)
+                .and_then(Token::ident)
+                .filter(|_| qualified)
+            {
+                schema = Some(std::mem::replace(&mut name, table.to_string()));
+                i += 2;
+            }
+
+            // `parent*`: legacy syntax for including child tables
+            if tokens
+                .get(i + 1)
+                .is_some_and(|t| matches!(t.kind, TokenKind::Other('*')))
+            {
+                i += 1;
+            }
+
+            // 5. Check for "table AS alias" / "table alias" patterns, after
+            //    the argument list of a function call
+            let is_call = tokens
+                .get(i + 1)
+                .is_some_and(|t| matches!(t.kind, TokenKind::ParenOpen));
+            let args = match is_call {
+                true => match call_len(&tokens[i + 1..]) {
+                    Some(args) => args,
+                    None => {
+                        refs.push(TableRef {
+                            cross: std::mem::take(&mut cross),
+                            ..TableRef::function(name, None)
+                        });
+                        break;
+                    }
+                },
+                false => 0,
+            };
+            let alias = alias_at(tokens, i + 1 + args);
+            let len = 1 + args + alias.map_or(0, |(_, len)| len);
+            let alias = alias.map(|(alias, _)| alias.to_string());
+            let cte = schema
+                .is_none()
+                .then(|| cte_select(tokens, &name, i))
+                .flatten();
+            let table = match (is_call, cte) {
+                (true, _) => TableRef::function(name, alias),
+                (false, Some(select_idx)) => TableRef {
+                    subquery: Some(select_idx),
+                    ..TableRef::table(name, alias)
+                },
+                (false, None) => TableRef {
+                    schema,
+                    ..TableRef::table(name, alias)
+                },
+            };
+            refs.push(TableRef {
+                cross: std::mem::take(&mut cross),
+                ..table
+            });
+            i += len;
+            continue;
+        }
+
+        // 6. Skip commas between table references
+        i += 1;
+    }
+
+    // 7. Flag aliases declared more than once
+    for idx in 0..refs.len() {
+        let alias = refs[idx].alias.as_deref();
+        refs[idx].ambiguous = alias.is_some()
+            && refs
+                .iter()
+                .enumerate()
+                .any(|(other, r)| other != idx && r.alias.as_deref() == alias);
+    }
+    refs
+}
+
+/// A common table expression declared in a `WITH` clause.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Cte<'t> {
+    name: &'t str,
+    /// Index of the `SELECT` token opening the CTE's body.
+    select_idx: usize,
+    /// Index of the `)` closing the CTE's body.
+    end_idx: usize,
+}
+
+/// Every CTE declared in `tokens`, in source order:
+/// `WITH [RECURSIVE] name [(cols)] AS [[NOT] MATERIALIZED] (SELECT ...), ...`.
+///
+/// Bodies that are not a closed `SELECT` (mid-edit, `VALUES`) are skipped,
+/// and a malformed definition ends its `WITH` list.
+fn cte_definitions(tokens: &[Token]) -> Vec<Cte<'_>> {
+    let word = |i: usize, w: &str| {
+        tokens
+            .get(i)
+            .and_then(Token::ident)
+            .is_some_and(|t| t.eq_ignore_ascii_case(w))
+    };
+    let is = |i: usize, kind: TokenKind| tokens.get(i).is_some_and(|t| t.kind == kind);
+    let mut ctes = Vec::new();
+    for with_idx in (0..tokens.len()).filter(|&i| tokens[i].is_keyword(Keyword::With)) {
+        let mut i = with_idx + 1;
+        if word(i, "recursive") {
+            i += 1;
+        }
+        while let Some(name) = tokens.get(i).and_then(Token::ident) {
+            i += 1;
+            // Column list: `name (a, b) AS (...)`
+            if is(i, TokenKind::ParenOpen) {
+                let Some(len) = call_len(&tokens[i..]) else {
+                    break;
+                };
+                i += len;
+            }
+            if !tokens.get(i).is_some_and(|t| t.is_keyword(Keyword::As)) {
+                break;
+            }
+            i += 1;
+            if word(i, "not") {
+                i += 1;
+            }
+            if word(i, "materialized") {
+                i += 1;
+            }
+            if !is(i, TokenKind::ParenOpen) {
+                break;
+            }
+            let Some(len) = call_len(&tokens[i..]) else {
+                break;
+            };
+            let end_idx = i + len - 1;
+            if tokens[i + 1].is_keyword(Keyword::Select) {
+                ctes.push(Cte {
+                    name,
+                    select_idx: i + 1,
+                    end_idx,
+                });
+            }
+            i = end_idx + 1;
+            if !is(i, TokenKind::Comma) {
+                break;
+            }
+            i += 1;
+        }
+    }
+    ctes
+}
+
+/// The `SELECT` index of the CTE named `name` visible from `tokens[at]`: the
+/// latest one whose body closes before `at` within the same statement. A
+/// CTE's own body does not see it, so self-references (`WITH RECURSIVE`)
+/// stay unresolved. Later CTEs may reference earlier ones (`WITH x AS (...),
+/// y AS (SELECT ... FROM x)`); these chains resolve one `Scope` at a time.
+fn cte_select(tokens: &[Token], name: &str, at: usize) -> Option<usize> {
+    let separators = statement_separators(tokens);
+    cte_definitions(tokens)
+        .into_iter()
+        .rev()
+        .filter(|cte| cte.end_idx < at && !separators.iter().any(|&s| cte.end_idx < s && s < at))
+        .find(|cte| cte.name == name)
+        .map(|cte| cte.select_idx)
+}
+
+/// Number of tokens in the parenthesized group `tokens` starts with, up to
+/// and including its closing paren, or `None` if it is never closed.
+fn call_len(tokens: &[Token]) -> Option<usize> {
+    let mut depth = 0;
+    for (i, t) in tokens.iter().enumerate() {
+        depth += depth_delta(t);
+        if depth == 0 {
+            return Some(i + 1);
+        }
+    }
+    None
+}
+
+/// True if `tokens` end inside an `AT TIME ZONE` phrase (`ts AT`, `ts AT
+/// TIME`, `ts AT TIME ZONE`), where the zone operand is still expected.
+///
+/// The phrase words have no `Keyword` variant (`time` and `zone` are common
+/// column names), so they are matched as identifiers here.
+pub fn ends_at_time_zone(tokens: &[Token]) -> bool {
+    let word = |t: &Token, w: &str| t.ident().is_some_and(|i| i.eq_ignore_ascii_case(w));
+    match tokens {
+        [.., at, time, zone] if word(at, "at") && word(time, "time") && word(zone, "zone") => true,
+        [.., at, time] if word(at, "at") && word(time, "time") => true,
+        [.., at] => word(at, "at"),
+        [] => false,
+    }
+}
+
+/// Match an alias starting at `tokens[i]`: `AS alias` or a bare `alias`.
+///
+/// Returns the alias and the number of tokens it spans.
+fn alias_at(tokens: &[Token], i: usize) -> Option<(&str, usize)> {
+    let first = tokens.get(i)?;
+    if first.is_keyword(Keyword::As) {
+        return tokens.get(i + 1)?.ident().map(|alias| (alias, 2));
+    }
+    first.ident().map(|alias| (alias, 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::tokenizer::tokenize;
+    use rstest::rstest;
+
+    fn scope(sql: &str, cursor: usize) -> Option<Scope> {
+        Scope::at(&tokenize(sql), cursor)
+    }
+
+    #[test]
+    fn collects_refs_in_order_with_aliases() {
+        let s = scope("SELECT  FROM a AS x JOIN b y, c", 7).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, [("a", "x"), ("b", "y"), ("c", "c")]);
+        assert_eq!(s.resolve("y"), "b");
+        assert_eq!(s.resolve("c"), "c");
+    }
+
+    #[rstest]
+    #[case("SELECT  FROM accounts*", vec![("accounts", "accounts")])]
+    #[case("SELECT  FROM s.accounts* a, ONLY b AS y", vec![("accounts", "a"), ("b", "y")])]
+    #[case("SELECT  FROM ONLY s.a* JOIN only c ON true", vec![("a", "a"), ("c", "c")])]
+    fn skips_inheritance_markers(#[case] sql: &str, #[case] expected: Vec<(&str, &str)>) {
+        let s = scope(sql, 7).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, expected);
+    }
+
+    #[test]
+    fn resolves_cte_references_to_their_select() {
+        let sql =
+            "WITH x AS (SELECT id FROM a), y AS (SELECT id FROM x) SELECT  FROM y, x, public.x";
+        let tokens = tokenize(sql);
+        let ctes: Vec<_> = cte_definitions(&tokens)
+            .iter()
+            .map(|c| (c.name, c.select_idx))
+            .collect();
+        assert_eq!(ctes, [("x", 4), ("y", 13)]);
+        let s = Scope::at(&tokens, 62).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.subquery))
+            .collect();
+        assert_eq!(refs, [("y", Some(13)), ("x", Some(4)), ("x", None)]);
+        let inner = Scope::from_select(&tokens, 13).expect("inner scope");
+        assert_eq!(inner.refs[0].subquery, Some(4));
+    }
+
+    #[test]
+    fn marks_comma_and_cross_joined_refs() {
+        let sql = "SELECT  FROM a, b JOIN c ON true CROSS JOIN d, (SELECT 1 FROM x) e LEFT JOIN f ON true";
+        let s = scope(sql, 7).expect("scope");
+        let cross: Vec<_> = s.refs.iter().map(|r| (r.reference(), r.cross)).collect();
+        assert_eq!(
+            cross,
+            [
+                ("a", false),
+                ("b", true),
+                ("c", false),
+                ("d", true),
+                ("e", true),
+                ("f", false)
+            ]
+        );
+    }
+
+    #[test]
+    fn where_predicates_are_not_refs() {
+        let sql = "SELECT  FROM a x, b WHERE (x.s, x.e) OVERLAPS (b.s, b.e) AND x.s IS NOT NULL";
+        let s = scope(sql, 7).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, [("a", "x"), ("b", "b")]);
+    }
+
+    #[test]
+    fn enclosing_skips_closed_and_sibling_selects() {
+        let sql = "SELECT * FROM a x WHERE (SELECT 1 FROM c) = 1 AND EXISTS (SELECT 1 FROM b WHERE b.v = x.v)";
+        let tokens = tokenize(sql);
+        let inner = Scope::at(&tokens, 78).expect("inner scope");
+        assert_eq!(inner.tables(), ["b"]);
+        let outer = inner.enclosing(&tokens).expect("outer scope");
+        assert_eq!(outer.tables(), ["a"]);
+        assert!(outer.enclosing(&tokens).is_none());
+        assert_eq!(
+            inner.find_correlated(&tokens, "x").map(|r| r.name),
+            Some("a".into())
+        );
+        assert_eq!(
+            inner.find_correlated(&tokens, "b").map(|r| r.name),
+            Some("b".into())
+        );
+        assert_eq!(inner.find_correlated(&tokens, "c"), None);
+
+        let union = "SELECT 1 FROM a UNION SELECT 1 FROM b";
+        let tokens = tokenize(union);
+        assert!(
+            Scope::at(&tokens, union.len())
+                .expect("scope")
+                .enclosing(&tokens)
+                .is_none()
+        );
+    }
+
+    #[rstest]
+    // Case 1: Target only
+    #[case("UPDATE a SET x = 1", vec![("a", "a")])]
+    // Case 2: Aliased, schema-qualified target with a FROM list
+    #[case("UPDATE ONLY s.a AS t SET x = b.y FROM b, c z WHERE t.id = b.a_id", vec![("a", "t"), ("b", "b"), ("c", "z")])]
+    // Case 3: FROM after the cursor still counts
+    #[case("UPDATE a SET x =  FROM b JOIN c ON true", vec![("a", "a"), ("b", "b"), ("c", "c")])]
+    fn update_scope_refs(#[case] sql: &str, #[case] expected: Vec<(&str, &str)>) {
+        let s = scope(sql, 16).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, expected);
+    }
+
+    #[test]
+    fn update_scope_requires_set_and_yields_to_subqueries() {
+        assert_eq!(scope("UPDATE a ", 9), None);
+        let sql = "UPDATE a SET x = 1 WHERE id IN (SELECT a_id FROM b WHERE )";
+        let s = scope(sql, sql.len() - 1).expect("scope");
+        assert_eq!(s.tables(), ["b"]);
+        let s = scope(sql, sql.len()).expect("scope");
+        assert_eq!(s.tables(), ["a"]);
+        // A `;` ends the UPDATE statement.
+        let sql = "UPDATE a SET x = 1; DELETE FROM b WHERE ";
+        assert_eq!(scope(sql, sql.len()), None);
+    }
+
+    #[rstest]
+    // Case 1: Complete phrase
+    #[case("ts AT TIME ZONE", true)]
+    // Case 2: Partial phrases, any case
+    #[case("ts at", true)]
+    #[case("ts At Time", true)]
+    // Case 3: Phrase already given its zone
+    #[case("ts AT TIME ZONE tz", false)]
+    // Case 4: Unrelated words
+    #[case("ts TIME ZONE", false)]
+    #[case("", false)]
+    fn detects_at_time_zone_phrase(#[case] sql: &str, #[case] expected: bool) {
+        assert_eq!(ends_at_time_zone(&tokenize(sql)), expected, "{sql}");
+    }
+
+    #[test]
+    fn on_condition_resolves_aliases_of_joined_tables() {
+        let sql = "SELECT * FROM x AS a JOIN y AS b ON b. JOIN z AS c ON true";
+        let s = scope(sql, 38).expect("scope");
+        assert_eq!(s.resolve("b"), "y");
+        assert_eq!(s.resolve("a"), "x");
+        // `c` is joined after this condition and not yet visible.
+        assert_eq!(s.find("c"), None);
+    }
+
+    #[rstest]
+    // Case 1: Tables without aliases
+    #[case("MERGE INTO a USING b ON ", vec![("a", "a"), ("b", "b")])]
+    // Case 2: Aliased, schema-qualified target and aliased source
+    #[case("MERGE INTO s.a AS t USING b src ON t.id = src.id WHEN MATCHED THEN UPDATE SET x = 1", vec![("a", "t"), ("b", "src")])]
+    // Case 3: Aliased subquery as the source
+    #[case("MERGE INTO a t USING (SELECT id FROM b) AS s ON t.id = s.id", vec![("a", "t"), ("s", "s")])]
+    fn merge_scope_refs(#[case] sql: &str, #[case] expected: Vec<(&str, &str)>) {
+        let s = scope(sql, sql.len()).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, expected);
+    }
+
+    #[rstest]
+    // Case 1: Schema-qualified table with an alias
+    #[case("SELECT  FROM public.users u", vec![(Some("public"), "users", "u")])]
+    // Case 2: Qualified and bare references mixed
+    #[case(
+        "SELECT  FROM users, app.orders JOIN items i ON true",
+        vec![(None, "users", "users"), (Some("app"), "orders", "orders"), (None, "items", "i")]
+    )]
+    // Case 3: UPDATE target
+    #[case("UPDATE public.users SET ", vec![(Some("public"), "users", "users")])]
+    fn schema_qualified_refs(#[case] sql: &str, #[case] expected: Vec<(Option<&str>, &str, &str)>) {
+        let s = scope(sql, 7).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.schema.as_deref(), r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, expected);
+    }
+
+    #[test]
+    fn merge_scope_requires_using_and_yields_to_subqueries() {
+        assert_eq!(scope("MERGE INTO a ", 13), None);
+        let sql = "MERGE INTO a USING (SELECT id FROM b WHERE ) s ON true";
+        let s = scope(sql, 43).expect("scope");
+        assert_eq!(s.tables(), ["b"]);
+        let s = scope(sql, sql.len()).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.reference(), r.is_derived()))
+            .collect();
+        assert_eq!(refs, [("a", false), ("s", true)]);
+        // A `;` ends the MERGE statement.
+        let sql = "MERGE INTO a USING b ON true; UPDATE c SET x = ";
+        assert_eq!(scope(sql, sql.len()).expect("scope").tables(), ["c"]);
+    }
+
+    #[rstest]
+    // Case 1: Aliased call
+    #[case("SELECT  FROM generate_series(1, 10) AS g", vec![("generate_series", "g")])]
+    // Case 2: Bare alias, nested parens, followed by a table
+    #[case("SELECT  FROM unnest(f(a, b)) u, t", vec![("unnest", "u"), ("t", "t")])]
+    // Case 3: Unaliased call
+    #[case("SELECT  FROM unnest(arr) WHERE", vec![("unnest", "unnest")])]
+    // Case 4: Unclosed call mid-edit
+    #[case("SELECT  FROM t JOIN unnest(arr", vec![("t", "t"), ("unnest", "unnest")])]
+    fn function_calls_are_single_refs(#[case] sql: &str, #[case] expected: Vec<(&str, &str)>) {
+        let scope = scope(sql, 7).unwrap();
+        let refs: Vec<_> = scope
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, expected);
+        assert!(scope.refs.iter().all(|r| r.function == (r.name != "t")));
+    }
+
+    #[test]
+    fn expression_groups_do_not_open_scopes() {
+        let sql = "SELECT * FROM a WHERE (a.x, a.) IN (SELECT y FROM b)";
+        let scope = scope(sql, 30).unwrap();
+        assert_eq!(scope.select_idx, 0);
+        assert_eq!(scope.tables(), ["a"]);
+    }
+
+    #[rstest]
+    #[case("SELECT  FROM a x, b x", vec![true, true])]
+    #[case("SELECT  FROM a x JOIN b y ON true, c x", vec![true, false, true])]
+    #[case("SELECT  FROM a, b a", vec![false, false])]
+    #[case("SELECT  FROM a x, b y", vec![false, false])]
+    fn duplicate_references_are_ambiguous(#[case] sql: &str, #[case] expected: Vec<bool>) {
+        let scope = scope(sql, 7).unwrap();
+        let ambiguous: Vec<_> = scope.refs.iter().map(|r| r.ambiguous).collect();
+        assert_eq!(ambiguous, expected);
+        assert_eq!(scope.is_ambiguous("x"), expected[0]);
+    }
+
+    #[test]
+    fn tables_are_distinct() {
+        let s = scope("SELECT  FROM a x JOIN a y", 7).expect("scope");
+        assert_eq!(s.tables(), ["a"]);
+        assert_eq!(s.alias_target("x"), Some("a"));
+        assert_eq!(s.alias_target("y"), Some("a"));
+    }
+
+    #[test]
+    fn registers_derived_tables() {
+        let sql = "SELECT  FROM a, (SELECT id FROM b) sub JOIN (SELECT 1 FROM c) AS d ON true";
+        let s = scope(sql, 7).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.reference(), r.is_derived()))
+            .collect();
+        assert_eq!(refs, [("a", false), ("sub", true), ("d", true)]);
+        assert_eq!(s.tables(), ["a"]);
+    }
+
+    #[test]
+    fn join_conditions_do_not_end_the_from_clause() {
+        let s = scope(
+            "SELECT  FROM a JOIN b ON a.id = b.id JOIN c ON c.x = (1) WHERE",
+            7,
+        )
+        .expect("scope");
+        assert_eq!(s.tables(), ["a", "b", "c"]);
+    }
+
+    #[test]
+    fn on_clause_only_sees_tables_joined_so_far() {
+        let sql = "SELECT * FROM a JOIN b ON  JOIN c ON ";
+        let s = scope(sql, 26).expect("scope");
+        assert_eq!(s.tables(), ["a", "b"]);
+        let s = scope(sql, sql.len()).expect("scope");
+        assert_eq!(s.tables(), ["a", "b", "c"]);
+    }
+
+    #[rstest]
+    #[case("JOIN")]
+    #[case("INNER JOIN")]
+    #[case("LEFT JOIN")]
+    #[case("LEFT OUTER JOIN")]
+    #[case("RIGHT JOIN")]
+    #[case("RIGHT OUTER JOIN")]
+    #[case("FULL JOIN")]
+    #[case("FULL OUTER JOIN")]
+    #[case("CROSS JOIN")]
+    #[case("NATURAL JOIN")]
+    #[case("NATURAL INNER JOIN")]
+    #[case("NATURAL LEFT JOIN")]
+    #[case("NATURAL LEFT OUTER JOIN")]
+    #[case("NATURAL RIGHT JOIN")]
+    #[case("NATURAL RIGHT OUTER JOIN")]
+    #[case("NATURAL FULL JOIN")]
+    #[case("NATURAL FULL OUTER JOIN")]
+    #[case("JOIN LATERAL")]
+    #[case("CROSS JOIN LATERAL")]
+    #[case("LEFT JOIN LATERAL")]
+    #[case(", LATERAL")]
+    #[case("natural left outer join")]
+    fn join_modifiers_are_skipped(#[case] join: &str) {
+        let refs = |sql: &str| -> Vec<(String, String)> {
+            let s = scope(sql, 7).expect("scope");
+            s.refs
+                .iter()
+                .map(|r| (r.name.clone(), r.reference().to_string()))
+                .collect()
+        };
+        let pair = |name: &str, reference: &str| (name.to_string(), reference.to_string());
+
+        let bare = format!("SELECT  FROM a {join} b");
+        assert_eq!(refs(&bare), [pair("a", "a"), pair("b", "b")], "{bare}");
+
+        let aliased =
+            format!("SELECT  FROM a x {join} b y ON x.id = y.id LEFT OUTER JOIN c z ON true");
+        assert_eq!(
+            refs(&aliased),
+            [pair("a", "x"), pair("b", "y"), pair("c", "z")],
+            "{aliased}"
+        );
+    }
+
+    #[rstest]
+    // Case 1: Leading comma
+    #[case("SELECT  FROM , a", vec![("a", "a")])]
+    // Case 2: Trailing comma
+    #[case("SELECT  FROM a x,", vec![("a", "x")])]
+    // Case 3: Doubled comma
+    #[case("SELECT  FROM a, , b", vec![("a", "a"), ("b", "b")])]
+    // Case 4: Doubled comma without whitespace, with aliases
+    #[case("SELECT  FROM a x,,b AS y", vec![("a", "x"), ("b", "y")])]
+    // Case 5: Dangling AS before a comma
+    #[case("SELECT  FROM a AS, b", vec![("a", "a"), ("b", "b")])]
+    // Case 6: Only commas
+    #[case("SELECT  FROM , ,", vec![])]
+    // Case 7: Stray commas around joins
+    #[case("SELECT  FROM , a JOIN b ON true, , c WHERE", vec![("a", "a"), ("b", "b"), ("c", "c")])]
+    fn stray_commas_are_not_references(#[case] sql: &str, #[case] expected: Vec<(&str, &str)>) {
+        let s = scope(sql, 7).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, expected);
+        assert!(
+            s.refs.first().is_none_or(|r| !r.cross),
+            "first reference is never cross"
+        );
+    }
+
+    #[test]
+    fn projection_items_split_on_top_level_commas() {
+        let tokens = tokenize("SELECT a, f(b, c), d.e FROM t");
+        let s = Scope::at(&tokens, 7).expect("scope");
+        let lens: Vec<_> = s.projection(&tokens).iter().map(|i| i.len()).collect();
+        assert_eq!(lens, [1, 6, 3]);
+        let tokens = tokenize("SELECT DISTINCT ON (a) b, c FROM t");
+        let s = Scope::at(&tokens, 7).expect("scope");
+        let lens: Vec<_> = s.projection(&tokens).iter().map(|i| i.len()).collect();
+        assert_eq!(lens, [1, 1]);
+    }
+
+    #[rstest]
+    // Case 1: Aliased and plain items mixed
+    #[case("SELECT a.id AS k, count(*) n, b.x, y, p + q, 1 one FROM t", vec!["k", "n", "one"])]
+    // Case 2: DISTINCT / ALL qualify the list, they are not `expr alias`
+    #[case("SELECT DISTINCT name FROM t", vec![])]
+    #[case("SELECT ALL name, id AS k FROM t", vec!["k"])]
+    // Case 3: DISTINCT ON (...) is skipped as a whole
+    #[case("SELECT DISTINCT ON (x) y FROM t", vec![])]
+    #[case("SELECT distinct on (x, f(z)) y AS k, n m FROM t", vec!["k", "m"])]
+    fn projection_aliases_in_order(#[case] sql: &str, #[case] expected: Vec<&str>) {
+        let tokens = tokenize(sql);
+        let s = Scope::at(&tokens, 7).expect("scope");
+        assert_eq!(s.projection_aliases(&tokens), expected, "{sql}");
+    }
+
+    #[test]
+    fn collation_names_are_not_aliases() {
+        let tokens = tokenize(r#"SELECT name COLLATE "C", id AS k FROM t"#);
+        let s = Scope::at(&tokens, 7).expect("scope");
+        assert_eq!(s.projection_aliases(&tokens), ["k"]);
+    }
+
+    #[rstest]
+    #[case("SELECT * FROM a x GROUP ")]
+    #[case("SELECT * FROM a x GROUP BY id")]
+    #[case("SELECT * FROM a x ORDER ")]
+    #[case("SELECT * FROM a x ORDER BY id DESC")]
+    fn from_list_ends_at_group_and_order(#[case] sql: &str) {
+        let s = scope(sql, sql.len()).expect("scope");
+        let refs: Vec<_> = s
+            .refs
+            .iter()
+            .map(|r| (r.name.as_str(), r.reference()))
+            .collect();
+        assert_eq!(refs, [("a", "x")]);
+    }
+
+    #[test]
+    fn group_by_detection() {
+        let sql = "SELECT a FROM t GROUP BY a, (b) ORDER BY a";
+        let tokens = tokenize(sql);
+        let s = Scope::at(&tokens, 7).expect("scope");
+        assert!(!s.in_group_by(&tokens, 16)); // before GROUP
+        assert!(!s.in_group_by(&tokens, 24)); // typing BY
+        assert!(s.in_group_by(&tokens, 25));
+        assert!(s.in_group_by(&tokens, 30)); // inside (b)
+        assert!(!s.in_group_by(&tokens, sql.len())); // ORDER BY
+    }
+
+    #[test]
+    fn no_scope_without_from() {
+        assert!(scope("SELECT a", 7).is_none());
+        assert!(scope("FROM a", 5).is_none());
+    }
+}
@@ -0,0 +1,227 @@
+//! "Explode star" refactor support.
+//!
+//! Replaces a projection `*` (or `table.*`) with the explicit list of columns
+//! it stands for, reusing the same scope resolution as completion.
+
+use crate::sql::{
+    depth::depth_before, keyword::Keyword, statement::statement_separators, token::Token,
+    token_kind::TokenKind, tokenizer::tokenize,
+};
+use crate::*;
+
+/// Expand the projection star under the cursor into explicit column names.
+///
+/// Returns the byte span to replace and the columns to substitute, or `None`
+/// if the cursor is not on a projection `*`/`table.*` or nothing in scope is
+/// known to the metadata.
+///
+/// - `table.*` (or `alias.*`) yields that table's columns qualified the same
+///   way, e.g. `a.*` -> `a.id`, `a.name`.
+/// - A bare `*` with a single table in scope yields unqualified names; with
+///   several tables every column is qualified by its table reference so the
+///   substituted list stays unambiguous.
+///
+/// Stars that are not projection items (`COUNT(*)`, `a * b`) are ignored.
+///
+/// The `TABLE name` statement is shorthand for `SELECT * FROM name`: with
+/// the cursor on it, the span is the whole `TABLE name` and the columns are
+/// that table's, unqualified.
+pub async fn expand_star(
+    sql: &str,
+    cursor: Cursor,
+    meta: &Database,
+) -> Option<((usize, usize), Vec<String>)> {
+    let tokens = tokenize(sql);
+    let pos = cursor.start();
+
+    if let Some((span, table)) = table_statement(&tokens, pos) {
+        let names = column_names(meta, &tokens, &TableRef::table(table, None)).await;
+        return (!names.is_empty()).then_some((span, names));
+    }
+
+    for (idx, star) in tokens.iter().enumerate() {
+        if !matches!(star.kind, TokenKind::Other('*')) {
+            continue;
+        }
+        let qualifier = star_qualifier(&tokens, idx);
+        let start = qualifier.map_or(star.start, |q| q.start);
+        if pos < start || pos > star.end {
+            continue;
+        }
+
+        let scope = Scope::at(&tokens, start)?;
+        if !is_projection_item(&tokens, &scope, idx, qualifier.is_some()) {
+            return None;
+        }
+
+        let mut names = Vec::new();
+        match qualifier.and_then(Token::ident) {
+            Some(q) => {
+                let columns = match scope.find(q) {
+                    Some(r) => column_names(meta, &tokens, r).await,
+                    None => column_names(meta, &tokens, &TableRef::table(q, None)).await,
+                };
+                names.extend(columns.into_iter().map(|col| format!("{q}.{col}")));
+            }
+            None if scope.refs.len() == 1 => {
+                names = column_names(meta, &tokens, &scope.refs[0]).await;
+            }
+            None => {
+                for r in &scope.refs {
+                    for col in column_names(meta, &tokens, r).await {
+                        names.push(format!("{}.{col}", r.reference()));
+                    }
+                }
+            }
+        }
+        return (!names.is_empty()).then_some(((start, star.end), names));
+    }
+    None
+}
+
+/// The span and table name of a `TABLE [schema.]name` statement whose span
+/// contains `pos`. The statement must start the input, follow a `;` or open
+/// a parenthesized group (`... UNION (TABLE b)`).
+fn table_statement(tokens: &[Token], pos: usize) -> Option<((usize, usize), &str)> {
+    let separators = statement_separators(tokens);
+    tokens.iter().enumerate().find_map(|(idx, keyword)| {
+        if !keyword.is_keyword(Keyword::Table) {
+            return None;
+        }
+        let starts_statement = idx.checked_sub(1).is_none_or(|prev| {
+            separators.contains(&prev) || matches!(tokens[prev].kind, TokenKind::ParenOpen)
+        });
+        let name = match tokens.get(idx + 1..idx + 4) {
+            Some([_, dot, name])
+                if matches!(dot.kind, TokenKind::Dot) && name.ident().is_some() =>
+            {
+                name
+            }
+            _ => tokens.get(idx + 1).filter(|t| t.ident().is_some())?,
+        };
+        (starts_statement && (keyword.start..=name.end).contains(&pos))
+            .then(|| ((keyword.start, name.end), name.ident().unwrap_or_default()))
+    })
+}
+
+/// The `table` token of a `table.*` star, if present.
+fn star_qualifier(tokens: &[Token], star_idx: usize) -> Option<&Token> {
+    let dot = tokens.get(star_idx.checked_sub(1)?)?;
+    let qualifier = tokens.get(star_idx.checked_sub(2)?)?;
+    (matches!(dot.kind, TokenKind::Dot) && qualifier.ident().is_some()).then_some(qualifier)
+}
+
+/// True if the star at `star_idx` is a top-level item of the scope's projection.
+fn is_projection_item(tokens: &[Token], scope: &Scope, star_idx: usize, qualified: bool) -> bool {
+    if star_idx <= scope.select_idx || star_idx >= scope.from_idx {
+        return false;
+    }
+    let item_start = star_idx - if qualified { 3 } else { 1 };
+    depth_before(tokens, star_idx) == scope.depth
+        && matches!(
+            tokens[item_start].kind,
+            TokenKind::Keyword(Keyword::Select) | TokenKind::Comma
+        )
+}
+
+/// Column names visible through `table` in declaration order.
+pub(crate) async fn column_names(
+    meta: &Database,
+    tokens: &[Token],
+    table: &TableRef,
+) -> Vec<String> {
+    let mut columns = Vec::new();
+    Suggestion::gather_ref_columns(meta, tokens, table, &mut columns).await;
+    columns
+        .into_iter()
+        .filter_map(|s| match s {
+            Suggestion::Column(name, ..) => Some(name),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn meta() -> Database {
+        let db = Database::new("postgres");
+        db.insert_table(
+            "public",
+            Table::new_with_ordered(
+                "a",
+                [("id", DataType::Uuid), ("name", DataType::Text(None))],
+            ),
+        )
+        .await;
+        db.insert_table(
+            "public",
+            Table::new_with_ordered("b", [("bid", DataType::Uuid)]),
+        )
+        .await;
+        db
+    }
+
+    async fn expand(sql: &str, cursor: usize) -> Option<((usize, usize), Vec<String>)> {
+        expand_star(sql, Cursor::new(cursor, None), &meta().await).await
+    }
+
+    #[tokio::test]
+    async fn bare_star_single_table() {
+        let result = expand("SELECT * FROM a", 7).await;
+        assert_eq!(result, Some(((7, 8), vec!["id".into(), "name".into()])));
+    }
+
+    #[tokio::test]
+    async fn bare_star_multiple_tables_is_qualified() {
+        let result = expand("SELECT * FROM a x JOIN b ON x.id = b.bid", 8).await;
+        assert_eq!(
+            result,
+            Some(((7, 8), vec!["x.id".into(), "x.name".into(), "b.bid".into()]))
+        );
+    }
+
+    #[tokio::test]
+    async fn qualified_star_only_expands_that_table() {
+        let sql = "SELECT b.bid, a.* FROM a, b";
+        let result = expand(sql, 16).await;
+        assert_eq!(
+            result,
+            Some(((14, 17), vec!["a.id".into(), "a.name".into()]))
+        );
+    }
+
+    #[tokio::test]
+    async fn ignores_non_projection_stars() {
+        assert_eq!(expand("SELECT COUNT(*) FROM a", 13).await, None);
+        assert_eq!(expand("SELECT id * 2 FROM a", 10).await, None);
+        assert_eq!(expand("SELECT id FROM a", 7).await, None);
+    }
+
+    #[rstest]
+    // Case 1: Cursor on the keyword
+    #[case("TABLE a", 2, Some((0, 7)))]
+    // Case 2: Cursor at the end of the name, schema-qualified, after `;`
+    #[case("SELECT 1; TABLE public.a;", 24, Some((10, 24)))]
+    // Case 3: Parenthesized set operation operand
+    #[case("TABLE b UNION (TABLE a)", 20, Some((15, 22)))]
+    // Case 4: Not a statement start
+    #[case("CREATE TABLE a", 10, None)]
+    // Case 5: Cursor outside the statement
+    #[case("TABLE a ", 8, None)]
+    #[tokio::test]
+    async fn table_statement_expands_all_columns(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] span: Option<(usize, usize)>,
+    ) {
+        let expected = span.map(|span| (span, vec!["id".to_string(), "name".to_string()]));
+        assert_eq!(expand(sql, cursor).await, expected);
+    }
+
+    #[tokio::test]
+    async fn unknown_table_yields_none() {
+        assert_eq!(expand("SELECT * FROM missing", 7).await, None);
+    }
+}
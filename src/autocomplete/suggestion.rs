@@ -1,21 +1,386 @@
 use crate::*;
+use std::borrow::{Borrow, Cow};
+use std::collections::HashMap;
 
 /// An autocomplete suggestion. Variants represent different kinds of things that can be suggested while
-/// the user types a SQL query: raw keywords, fully qualified columns and tables.
+/// the user types a SQL query: raw keywords, fully qualified columns, tables, schemas, the
+/// allowed values of enum columns and references to projection outputs.
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, derive_more::Display)]
 pub enum Suggestion {
-    #[display("{_0}")]
-    Keyword(String),
+    /// A keyword completing the word being typed; `replace` is the byte span
+    /// of the typed prefix (e.g. `SEL`) the editor should overwrite.
+    #[display("{keyword}")]
+    Keyword {
+        keyword: String,
+        replace: (usize, usize),
+    },
     #[display("{_0}::{_1}")]
-    Column(String, DataType),
+    Column(String, DataType, ColumnFlags),
+    /// A column of a table whose name exists in several schemas on the
+    /// lookup path, labelled with its schema and table (see
+    /// `SearchOptions::disambiguate_schemas`).
+    #[display("{schema}.{table}.{column}::{data_type}")]
+    SchemaColumn {
+        schema: String,
+        table: String,
+        column: String,
+        data_type: DataType,
+        flags: ColumnFlags,
+    },
     #[display("{schema}.{name}")]
     Table { schema: String, name: String },
+    #[display("{_0}")]
+    Schema(String),
+    #[display("{_0}")]
+    EnumValue(String),
+    /// A registered key of a JSON column (`Column::json_keys`), completing
+    /// the string literal after `data ->> '`.
+    #[display("{_0}")]
+    JsonKey(String),
+    /// An output alias declared in the projection (`SELECT a.id AS k`).
+    #[display("{_0}")]
+    Alias(String),
+    /// A 1-based projection position (`GROUP BY 1`).
+    #[display("{_0}")]
+    Ordinal(usize),
+    /// The column (and its type) the value under the cursor fills, e.g. in
+    /// `INSERT INTO t (a, b) VALUES (1, |`. Informational, not insertable text.
+    #[display("-- {column} :: {}", data_type.to_sql())]
+    Hint { column: String, data_type: DataType },
 }
 pub type Suggestions = Vec<Suggestion>;
 
-use crate::sql::{keyword::Keyword, token_kind::TokenKind, tokenizer::tokenize};
+/// Extra facts about a column suggestion that UIs may render (e.g. strike
+/// through deprecated columns).
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ColumnFlags {
+    /// The column is marked deprecated in metadata (`Column::deprecated`).
+    pub deprecated: bool,
+    /// The column is part of its table's primary key (`Column::primary_key`).
+    pub primary_key: bool,
+}
+
+/// The variant of a `Suggestion`, without its payload.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SuggestionKind {
+    Keyword,
+    Column,
+    Table,
+    Schema,
+    EnumValue,
+    JsonKey,
+    Alias,
+    Ordinal,
+    Hint,
+}
+
+/// Convenience conversions for a list of suggestions.
+pub trait SuggestionsExt {
+    /// Keep only the suggestions of the given kind, preserving order.
+    fn filter_kind(self, kind: SuggestionKind) -> Self;
+    /// The bare text of every suggestion (see `Suggestion::label`).
+    fn into_labels(self) -> Vec<String>;
+    /// The `Display` rendering of every suggestion (e.g. `id::Uuid`, `public.users`).
+    fn into_display(self) -> Vec<String>;
+}
+
+impl SuggestionsExt for Suggestions {
+    fn filter_kind(mut self, kind: SuggestionKind) -> Self {
+        self.retain(|s| s.kind() == kind);
+        self
+    }
+
+    fn into_labels(self) -> Vec<String> {
+        self.iter().map(|s| s.label().to_string()).collect()
+    }
+
+    fn into_display(self) -> Vec<String> {
+        self.iter().map(ToString::to_string).collect()
+    }
+}
+
+use futures::{
+    Stream, StreamExt as _,
+    channel::mpsc::{self, UnboundedSender},
+    stream,
+};
+use std::time::Instant;
+use tracing::{Instrument as _, Span, field};
+
+/// Run `f`, recording its duration in microseconds as `field` on `span` when
+/// the span is enabled.
+fn timed<T>(span: &Span, field: &'static str, f: impl FnOnce() -> T) -> T {
+    if span.is_disabled() {
+        return f();
+    }
+    let start = Instant::now();
+    let out = f();
+    span.record(field, micros(start));
+    out
+}
+
+fn micros(start: Instant) -> u64 {
+    start.elapsed().as_micros().try_into().unwrap_or(u64::MAX)
+}
+
+use crate::sql::{
+    depth::depth_at,
+    keyword::Keyword,
+    quote::quote_ident,
+    token::Token,
+    token_kind::TokenKind,
+    tokenizer::{TokenizerOptions, tokenize_with},
+};
+
+/// Set operations offered after a complete SELECT (see
+/// `Suggestion::ends_complete_select`).
+const SET_OPERATIONS: [&str; 4] = ["UNION", "UNION ALL", "EXCEPT", "INTERSECT"];
+
+/// Keywords offered where a statement starts: in an empty buffer or before
+/// the first token.
+const STATEMENT_KEYWORDS: [&str; 4] = ["SELECT", "INSERT", "UPDATE", "WITH"];
+
+/// Keywords valid as a value in an `INSERT ... VALUES` tuple slot.
+const VALUE_LITERALS: [&str; 2] = ["DEFAULT", "NULL"];
+
+/// Words without a `Keyword` variant (so tokenized as identifiers) after which
+/// an operand is still expected (`WHERE a = 1 AND |`, `WHERE a IS |`,
+/// `WHERE (a, b) OVERLAPS |`).
+const EXPECTS_OPERAND: [&str; 17] = [
+    "and", "or", "not", "is", "like", "ilike", "similar", "to", "escape", "when", "then", "else",
+    "case", "distinct", "exists", "all", "overlaps",
+];
+
+/// Options controlling `Suggestion::search_with`.
+///
+/// The default value reproduces `Suggestion::search` exactly.
+#[derive(Debug, Clone, Default)]
+pub struct SearchOptions {
+    /// Options used to tokenize the buffer (dialect sigils, extra keywords).
+    pub tokenizer: TokenizerOptions,
+    /// Resolve qualifiers unknown to a subquery through its enclosing scopes,
+    /// as correlated subqueries (`EXISTS (SELECT 1 FROM b WHERE b.x = a.|)`)
+    /// may. Off by default: outer aliases do not leak into subqueries.
+    pub correlation: bool,
+    /// Make replace spans cover the whole token under the cursor (`na|me`
+    /// replaces all of `name`) instead of only the prefix before it. Applies
+    /// to `Keyword::replace` and the span returned by `search_with_replace`.
+    pub replace_whole_token: bool,
+    /// When the cursor is the right-hand operand of a comparison with a
+    /// column of known type (`WHERE created_at > |`), rank columns of a
+    /// comparable type (`DataType::is_comparable_with`) first. When the
+    /// operand is qualified (`ON a.id = |`), the other tables' columns
+    /// precede those of `a` itself.
+    pub type_context: bool,
+    /// Character that triggered the completion request (LSP
+    /// `CompletionContext::triggerCharacter`), `None` when invoked manually.
+    /// A `.` trigger forces the qualified interpretation: only the members
+    /// of the identifier before the dot are suggested, and nothing if the
+    /// dot does not follow an identifier (`1.`). Other triggers behave like
+    /// a manual invocation.
+    pub trigger: Option<char>,
+    /// Rank primary key columns (`ColumnFlags::primary_key`) before the other
+    /// columns, which are often what `WHERE` and `JOIN ... ON` compare. Type
+    /// context ranking, when enabled, still takes precedence.
+    pub primary_key_boost: bool,
+    /// When an unqualified table in scope (`FROM users`) exists in several
+    /// schemas, suggest its columns as `SchemaColumn`s (`public.users.id`,
+    /// `analytics.users.id`) instead of listing every schema's columns
+    /// unlabelled.
+    pub disambiguate_schemas: bool,
+}
+
+impl SearchOptions {
+    /// Replace the tokenizer options.
+    pub fn with_tokenizer(mut self, tokenizer: TokenizerOptions) -> Self {
+        self.tokenizer = tokenizer;
+        self
+    }
+
+    /// Enable or disable correlated qualifier resolution.
+    pub fn with_correlation(mut self, correlation: bool) -> Self {
+        self.correlation = correlation;
+        self
+    }
+
+    /// Enable or disable whole-token replace spans.
+    pub fn with_replace_whole_token(mut self, replace_whole_token: bool) -> Self {
+        self.replace_whole_token = replace_whole_token;
+        self
+    }
+
+    /// Enable or disable type-context ranking of columns.
+    pub fn with_type_context(mut self, type_context: bool) -> Self {
+        self.type_context = type_context;
+        self
+    }
+
+    /// Set the character that triggered the completion request.
+    pub fn with_trigger(mut self, trigger: Option<char>) -> Self {
+        self.trigger = trigger;
+        self
+    }
+
+    /// Enable or disable ranking primary key columns first.
+    pub fn with_primary_key_boost(mut self, primary_key_boost: bool) -> Self {
+        self.primary_key_boost = primary_key_boost;
+        self
+    }
+
+    /// Enable or disable schema-qualified columns for tables found in
+    /// several schemas.
+    pub fn with_disambiguate_schemas(mut self, disambiguate_schemas: bool) -> Self {
+        self.disambiguate_schemas = disambiguate_schemas;
+        self
+    }
+}
+
+/// Number of trailing `tokens` forming one word: adjacent identifiers, keywords,
+/// digit runs (`é1`) and the `Other` tokens of non-ASCII letters (see
+/// `Suggestion::qualified_prefix`).
+fn word_run(tokens: &[&Token]) -> usize {
+    let is_word = |t: &Token| match t.kind {
+        TokenKind::Ident(_)
+        | TokenKind::Keyword(_)
+        | TokenKind::ExtraKeyword(_)
+        | TokenKind::Number(_) => true,
+        TokenKind::Other(c) => !c.is_ascii(),
+        _ => false,
+    };
+    let mut run = 0;
+    for (i, t) in tokens.iter().enumerate().rev() {
+        let adjacent = tokens.get(i + 1).is_none_or(|next| next.start == t.end);
+        if !is_word(t) || !adjacent {
+            break;
+        }
+        run += 1;
+    }
+    run
+}
+
+/// Where a table reference is about to be typed (see `Suggestion::table_position`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum TablePosition {
+    /// `FROM |`, `JOIN |` or `FROM a, |`: a schema or a table may follow.
+    Unqualified,
+    /// `FROM schema.|`: only tables of the named schema may follow.
+    Schema(String),
+}
 
 impl Suggestion {
+    /// A column suggestion without flags.
+    pub fn column(name: impl Into<String>, data_type: DataType) -> Self {
+        Suggestion::Column(name.into(), data_type, ColumnFlags::default())
+    }
+
+    /// True for a column suggestion whose column is marked deprecated.
+    pub fn is_deprecated(&self) -> bool {
+        self.column_parts()
+            .is_some_and(|(_, _, flags)| flags.deprecated)
+    }
+
+    /// The name, type and flags of a `Column` or `SchemaColumn` suggestion.
+    fn column_parts(&self) -> Option<(&str, &DataType, ColumnFlags)> {
+        match self {
+            Suggestion::Column(name, data_type, flags)
+            | Suggestion::SchemaColumn {
+                column: name,
+                data_type,
+                flags,
+                ..
+            } => Some((name, data_type, *flags)),
+            _ => None,
+        }
+    }
+
+    /// The kind of this suggestion.
+    pub fn kind(&self) -> SuggestionKind {
+        match self {
+            Suggestion::Keyword { .. } => SuggestionKind::Keyword,
+            Suggestion::Column(..) | Suggestion::SchemaColumn { .. } => SuggestionKind::Column,
+            Suggestion::Table { .. } => SuggestionKind::Table,
+            Suggestion::Schema(_) => SuggestionKind::Schema,
+            Suggestion::EnumValue(_) => SuggestionKind::EnumValue,
+            Suggestion::JsonKey(_) => SuggestionKind::JsonKey,
+            Suggestion::Alias(_) => SuggestionKind::Alias,
+            Suggestion::Ordinal(_) => SuggestionKind::Ordinal,
+            Suggestion::Hint { .. } => SuggestionKind::Hint,
+        }
+    }
+
+    /// The text a user would insert for this suggestion: the keyword, column,
+    /// table, schema, enum value, JSON key or alias name (without type or schema
+    /// decoration), the `schema.table.column` path of a `SchemaColumn`, or
+    /// the ordinal's digits.
+    pub fn label(&self) -> Cow<'_, str> {
+        match self {
+            Suggestion::Keyword { keyword: s, .. }
+            | Suggestion::Column(s, ..)
+            | Suggestion::Schema(s)
+            | Suggestion::EnumValue(s)
+            | Suggestion::JsonKey(s)
+            | Suggestion::Alias(s)
+            | Suggestion::Hint { column: s, .. } => Cow::Borrowed(s),
+            Suggestion::Table { name, .. } => Cow::Borrowed(name),
+            Suggestion::SchemaColumn {
+                schema,
+                table,
+                column,
+                ..
+            } => Cow::Owned(format!("{schema}.{table}.{column}")),
+            Suggestion::Ordinal(n) => Cow::Owned(n.to_string()),
+        }
+    }
+
+    /// Secondary text an editor may show next to the label (LSP
+    /// `CompletionItem::detail`): a column's SQL type, badged `PK` for
+    /// primary key columns (`uuid PK`), a table's schema or a hint's type.
+    /// `None` for the other kinds.
+    pub fn detail(&self) -> Option<String> {
+        match self {
+            Suggestion::Hint { data_type, .. } => Some(data_type.to_sql()),
+            _ if let Some((_, data_type, flags)) = self.column_parts() => match flags.primary_key {
+                true => Some(format!("{} PK", data_type.to_sql())),
+                false => Some(data_type.to_sql()),
+            },
+            Suggestion::Table { schema, .. } => Some(schema.clone()),
+            _ => None,
+        }
+    }
+
+    /// The text to insert into the buffer for this suggestion.
+    ///
+    /// Column, table, schema and alias names are quoted when they collide
+    /// with a keyword or are not plain lower-case identifiers (`order` ->
+    /// `"order"`, `my col` -> `"my col"`), each segment of a `SchemaColumn`
+    /// path separately. Enum values and JSON keys are
+    /// inserted inside an already opened string literal, so embedded `'` are
+    /// doubled. A `Hint` inserts nothing.
+    pub fn insert_text(&self) -> Cow<'_, str> {
+        match self {
+            Suggestion::Column(s, ..)
+            | Suggestion::Schema(s)
+            | Suggestion::Alias(s)
+            | Suggestion::Table { name: s, .. } => quote_ident(s),
+            Suggestion::SchemaColumn {
+                schema,
+                table,
+                column,
+                ..
+            } => Cow::Owned(format!(
+                "{}.{}.{}",
+                quote_ident(schema),
+                quote_ident(table),
+                quote_ident(column)
+            )),
+            Suggestion::EnumValue(s) | Suggestion::JsonKey(s) if s.contains('\'') => {
+                Cow::Owned(s.replace('\'', "''"))
+            }
+            Suggestion::Hint { .. } => Cow::Borrowed(""),
+            _ => self.label(),
+        }
+    }
+
     /// Search the SQL buffer for possible column suggestions at the given cursor.
     ///
     /// Strategy:
@@ -25,209 +390,1142 @@ impl Suggestion {
     /// 4. Extract table names and their aliases from the range that follows.
     /// 5. If the cursor position represents a qualified prefix (`alias.`) only gather
     ///    columns for that single table; else gather columns for all tables in scope.
-    pub async fn search(sql: &str, cursor: Cursor, meta: Database) -> Result<Suggestions> {
-        let tokens = tokenize(sql);
-        let cursor_pos = cursor.start();
-        let (select_idx, select_depth) = match Self::locate_select(&tokens, cursor_pos) {
-            Some(v) => v,
-            None => return Ok(vec![]),
+    ///    A longer chain (`alias.address.geo.`) walks composite columns and
+    ///    suggests the fields (`Column::fields`) of its last segment.
+    ///    With `SearchOptions::correlation`, qualifiers unknown to a subquery
+    ///    are resolved through its enclosing scopes. An alias declared twice
+    ///    in one FROM (`FROM a x, b x`) is ambiguous and yields nothing.
+    ///
+    /// In a `MERGE` the `ON` condition and `WHEN` clauses see both the target
+    /// and the `USING` source.
+    ///
+    /// A FROM item naming a CTE of the statement's `WITH` clause exposes the
+    /// columns the CTE projects, also through chains (`WITH x AS (...), y AS
+    /// (SELECT ... FROM x) SELECT | FROM y`).
+    ///
+    /// In a `GROUP BY` list, projection aliases and ordinals follow the columns.
+    /// Inside an `INSERT ... VALUES` tuple a `Hint` names the target column
+    /// and type for the current position, followed by the `DEFAULT` and
+    /// `NULL` keywords.
+    ///
+    /// When the cursor instead sits where a table reference begins (after `FROM`,
+    /// `JOIN`, `UPDATE`, `TABLE` or a comma in the FROM list) schema and table
+    /// names are suggested, filtered by the partially typed identifier. Inside a string literal
+    /// compared to an enum column (`status = '`) the enum's values are suggested,
+    /// and after a JSON path operator (`data ->> '`) the column's registered
+    /// `json_keys`. Nothing else is suggested inside a string literal, and the
+    /// words of a literal (`'FROM me'`) are never read as SQL.
+    ///
+    /// With nothing typed before the cursor (an empty buffer, or the cursor
+    /// ahead of the first statement) the statement keywords `SELECT`,
+    /// `INSERT`, `UPDATE` and `WITH` are suggested.
+    ///
+    /// While a word is being typed, keywords starting with it (`SEL` →
+    /// `SELECT`) follow the other suggestions, each carrying the span of the
+    /// typed prefix to replace. After a complete SELECT (`SELECT * FROM a |`)
+    /// the set operations `UNION`, `UNION ALL`, `EXCEPT` and `INTERSECT` do.
+    /// Right after `GROUP` or `ORDER` only `BY` is suggested.
+    ///
+    /// Nothing is suggested for the collation name after `COLLATE`.
+    ///
+    /// Cursor offsets past the end of `sql` or inside a multi-byte character are
+    /// clamped to the preceding character boundary, so `search` never panics.
+    ///
+    /// `meta` may be passed owned, borrowed or behind an `Arc`.
+    pub async fn search(
+        sql: &str,
+        cursor: Cursor,
+        meta: impl Borrow<Database>,
+    ) -> Result<Suggestions> {
+        Self::search_with(sql, cursor, meta, &SearchOptions::default()).await
+    }
+
+    /// `search` with caller supplied `SearchOptions`.
+    ///
+    /// Runs inside a `search` debug span recording `tokenize_us`, `scope_us`,
+    /// `lock_wait_us` and the number of `suggestions`; timings are only taken
+    /// when that span is enabled.
+    pub async fn search_with(
+        sql: &str,
+        cursor: Cursor,
+        meta: impl Borrow<Database>,
+        options: &SearchOptions,
+    ) -> Result<Suggestions> {
+        let span = tracing::debug_span!(
+            "search",
+            sql_len = sql.len(),
+            cursor = cursor.start(),
+            tokenize_us = field::Empty,
+            scope_us = field::Empty,
+            lock_wait_us = field::Empty,
+            suggestions = field::Empty,
+        );
+        let result = Self::search_in(sql, cursor, meta.borrow(), options, &span, None)
+            .instrument(span.clone())
+            .await;
+        if let Ok(out) = &result {
+            span.record("suggestions", out.len());
+        }
+        result
+    }
+
+    /// `search_with`, plus the byte span every returned suggestion replaces:
+    /// the partial word before the cursor, or with
+    /// `SearchOptions::replace_whole_token` the whole token under it. It is
+    /// the span keywords carry in `replace`.
+    pub async fn search_with_replace(
+        sql: &str,
+        cursor: Cursor,
+        meta: impl Borrow<Database>,
+        options: &SearchOptions,
+    ) -> Result<(Suggestions, (usize, usize))> {
+        let tokens = tokenize_with(sql, &options.tokenizer);
+        let cursor_pos = sql.floor_char_boundary(cursor.start());
+        let (_, partial) = Self::split_partial(sql, &tokens, cursor_pos);
+        let replace = Self::replace_span(&tokens, partial, cursor_pos, options);
+        let suggestions = Self::search_with(sql, cursor, meta, options).await?;
+        Ok((suggestions, replace))
+    }
+
+    /// `search_with` yielding suggestions as they are gathered, so editors can
+    /// render the first items of a large result early.
+    ///
+    /// Unqualified columns are yielded one table at a time; everything else
+    /// (and every column when a partial word, `SearchOptions::type_context`
+    /// or `SearchOptions::primary_key_boost` reorders them) arrives once
+    /// complete. The collected stream equals `search_with`.
+    pub fn search_stream<'a>(
+        sql: &'a str,
+        cursor: Cursor,
+        meta: &'a Database,
+        options: &'a SearchOptions,
+    ) -> impl Stream<Item = Suggestion> + 'a {
+        let (tx, rx) = mpsc::unbounded();
+        let producer = async move {
+            let rest = Self::search_in(sql, cursor, meta, options, &Span::none(), Some(&tx)).await;
+            for suggestion in rest.unwrap_or_default() {
+                let _ = tx.unbounded_send(suggestion);
+            }
         };
-        let from_idx = match Self::locate_from(&tokens, select_idx, select_depth) {
-            Some(v) => v,
-            None => return Ok(vec![]),
+        // The producer yields no items; the channel closes once it is done.
+        stream::select(rx, stream::once(producer).filter_map(|()| async { None }))
+    }
+
+    /// One page of `search`: the `limit` suggestions starting at `offset`,
+    /// and whether more follow. Pages are cut after ranking, so walking
+    /// `offset` forward visits every suggestion of `search` exactly once.
+    pub async fn search_paged(
+        sql: &str,
+        cursor: Cursor,
+        meta: impl Borrow<Database>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<(Suggestions, bool)> {
+        let all = Self::search(sql, cursor, meta).await?;
+        let has_more = all.len() > offset.saturating_add(limit);
+        let page = all.into_iter().skip(offset).take(limit).collect();
+        Ok((page, has_more))
+    }
+
+    /// Only schema and table suggestions, for editors that already know the
+    /// cursor is at a table position (right after `FROM`, `JOIN`, a comma in
+    /// the FROM list or `FROM schema.`). Returns nothing anywhere else,
+    /// without resolving the SELECT scope or looking up columns.
+    pub async fn search_tables(
+        sql: &str,
+        cursor: Cursor,
+        meta: impl Borrow<Database>,
+    ) -> Result<Suggestions> {
+        let tokens = tokenize_with(sql, &TokenizerOptions::default());
+        let cursor_pos = sql.floor_char_boundary(cursor.start());
+        let (preceding, partial) = Self::split_partial(sql, &tokens, cursor_pos);
+        let mut out = Vec::new();
+        if let Some(position) = Self::table_position(preceding) {
+            Self::gather_tables(meta.borrow(), &position, partial.unwrap_or(""), &mut out).await;
+        }
+        Ok(out)
+    }
+
+    /// Body of `search_with`, recording phase timings on `span` (a no-op when
+    /// the span is disabled).
+    async fn search_in(
+        sql: &str,
+        cursor: Cursor,
+        meta: &Database,
+        options: &SearchOptions,
+        span: &Span,
+        stream: Option<&UnboundedSender<Suggestion>>,
+    ) -> Result<Suggestions> {
+        let tokens = timed(span, "tokenize_us", || {
+            tokenize_with(sql, &options.tokenizer)
+        });
+        let cursor_pos = sql.floor_char_boundary(cursor.start());
+        // Fresh buffer or nothing typed before the cursor: a statement starts here.
+        if cursor_pos == 0 || !tokens.iter().any(|t| t.start < cursor_pos) {
+            let replace = Self::replace_span(&tokens, None, cursor_pos, options);
+            return Ok(STATEMENT_KEYWORDS
+                .iter()
+                .map(|k| Suggestion::Keyword {
+                    keyword: k.to_string(),
+                    replace,
+                })
+                .collect());
+        }
+
+        if !span.is_disabled() {
+            // Time until a metadata read lock is available (e.g. behind a refresh).
+            let start = Instant::now();
+            drop(meta.schemas.read().await);
+            span.record("lock_wait_us", micros(start));
+        }
+
+        let (preceding, partial) = Self::split_partial(sql, &tokens, cursor_pos);
+        let replace = Self::replace_span(&tokens, partial, cursor_pos, options);
+        // A `.` trigger completes the members of the identifier before it.
+        let triggered = match options.trigger {
+            Some('.') => match (preceding, partial) {
+                ([.., q, dot], None) if matches!(dot.kind, TokenKind::Dot) => match q.ident() {
+                    Some(q) => Some(q.to_string()),
+                    None => return Ok(vec![]),
+                },
+                _ => return Ok(vec![]),
+            },
+            _ => None,
+        };
+
+        // `GROUP |` / `ORDER |`: only `BY` can follow
+        if let [.., last] = preceding
+            && matches!(
+                last.kind,
+                TokenKind::Keyword(Keyword::Group | Keyword::Order)
+            )
+        {
+            let lower = partial.unwrap_or("").to_ascii_lowercase();
+            return Ok(match Keyword::By.as_str().starts_with(&lower) {
+                true => vec![Suggestion::Keyword {
+                    keyword: "BY".to_string(),
+                    replace,
+                }],
+                false => vec![],
+            });
+        }
+
+        // Positional hint inside INSERT ... VALUES (, then the value literals
+        if let Some(slot) = ValuesSlot::at(&tokens, cursor_pos) {
+            let mut out = Vec::new();
+            if let Some((column, data_type)) = slot.target(meta).await {
+                out.push(Suggestion::Hint { column, data_type });
+                let lower = partial.unwrap_or("").to_ascii_lowercase();
+                out.extend(
+                    VALUE_LITERALS
+                        .iter()
+                        .filter(|k| k.to_ascii_lowercase().starts_with(&lower))
+                        .map(|k| Suggestion::Keyword {
+                            keyword: k.to_string(),
+                            replace,
+                        }),
+                );
+            }
+            return Ok(out);
+        }
+        // A (possibly quoted) collation name follows COLLATE; nothing in
+        // metadata can complete it.
+        let name_start = match preceding {
+            [rest @ .., quote] if matches!(quote.kind, TokenKind::Other('"')) => rest,
+            _ => preceding,
+        };
+        if name_start
+            .last()
+            .is_some_and(|t| t.is_keyword(Keyword::Collate))
+        {
+            return Ok(vec![]);
+        }
+        // Inside a string literal only enum values and JSON keys complete.
+        if partial.is_some() && tokens[preceding.len()].kind.string().is_some() {
+            let mut out = Vec::new();
+            let Some(scope) = Scope::at(&tokens, cursor_pos) else {
+                return Ok(out);
+            };
+            let (target, json) = match Self::json_path_column(preceding) {
+                Some(target) => (Some(target), true),
+                None => (Self::compared_column(preceding), false),
+            };
+            let Some((qualifier, column)) = target else {
+                return Ok(out);
+            };
+            let tables: Vec<TableRef> = match qualifier {
+                Some(q) => vec![
+                    scope
+                        .find(q)
+                        .cloned()
+                        .unwrap_or_else(|| TableRef::table(q, None)),
+                ],
+                None => scope
+                    .refs
+                    .iter()
+                    .filter(|r| !r.is_derived())
+                    .cloned()
+                    .collect(),
+            };
+            let partial = partial.unwrap_or("");
+            match json {
+                // JSON key after a path operator (e.g. data ->> ')
+                true => Self::gather_json_keys(meta, &tables, column, partial, &mut out).await,
+                // String literal compared to a column (e.g. status = ')
+                false => Self::gather_enum_values(meta, &tables, column, partial, &mut out).await,
+            }
+            return Ok(out);
+        }
+        let (position, scope) = timed(span, "scope_us", || match Self::table_position(preceding) {
+            Some(position) => (Some(position), None),
+            None => (None, Scope::at(&tokens, cursor_pos)),
+        });
+        if let Some(position) = position {
+            let mut out = Vec::new();
+            Self::gather_tables(meta, &position, partial.unwrap_or(""), &mut out).await;
+            return Ok(out);
+        }
+        let Some(scope) = scope else {
+            let mut out = Vec::new();
+            Self::gather_keywords(partial, replace, &mut out);
+            return Ok(out);
+        };
+
+        let expected = match options.type_context {
+            true => Self::expected_type(meta, &tokens, &scope, preceding).await,
+            false => None,
         };
-        let (tables, aliases) = Self::extract_tables(&tokens, from_idx, select_depth);
 
-        // Qualified prefix (e.g. users.)
-        if let Some(prefix) = Self::qualified_prefix(sql, tokens[select_idx].end, cursor_pos) {
+        // Qualified prefix (e.g. users.) or composite access chain (e.g. users.address.)
+        let chain = match triggered {
+            Some(q) => Self::qualified_chain(sql, &tokens, scope.select_idx, cursor_pos)
+                .filter(|chain| chain.last() == Some(&q))
+                .or(Some(vec![q])),
+            None => Self::qualified_chain(sql, &tokens, scope.select_idx, cursor_pos),
+        };
+        if let Some(mut chain) = chain {
             let mut out = Vec::new();
-            let base = aliases.get(&prefix).cloned().unwrap_or(prefix);
-            Self::gather_columns(&meta, &base, &mut out).await;
+            if let Some(fields) = Self::composite_fields(meta, &scope, &chain).await {
+                out.extend(fields.into_iter().map(|c| {
+                    let flags = ColumnFlags {
+                        deprecated: c.deprecated,
+                        primary_key: c.primary_key,
+                    };
+                    Suggestion::Column(c.name, c.data_type, flags)
+                }));
+                Self::rank_columns(
+                    &mut out,
+                    partial,
+                    expected.as_ref(),
+                    options.primary_key_boost,
+                );
+                return Ok(out);
+            }
+            // Not a composite chain: the last segment qualifies (`schema.table.`).
+            let prefix = chain.pop().unwrap_or_default();
+            // `FROM a x, b x`: `x.` could mean either table, suggest nothing.
+            if scope.is_ambiguous(&prefix) {
+                return Ok(out);
+            }
+            let found = match options.correlation {
+                true => scope.find_correlated(&tokens, &prefix),
+                false => scope.find(&prefix).cloned(),
+            };
+            match found {
+                Some(r) => Self::gather_ref_columns(meta, &tokens, &r, &mut out).await,
+                None => Self::gather_columns(meta, None, &prefix, &mut out).await,
+            }
+            Self::rank_columns(
+                &mut out,
+                partial,
+                expected.as_ref(),
+                options.primary_key_boost,
+            );
             return Ok(out);
         }
 
         // Unqualified: aggregate columns from all tables in scope.
         let mut out = Vec::new();
-        for tbl in tables {
-            Self::gather_columns(&meta, &tbl, &mut out).await;
+        // Ranking needs every column first; otherwise flush table by table.
+        let flush = stream
+            .filter(|_| partial.is_none() && expected.is_none() && !options.primary_key_boost);
+        // `ON a.id = |`: the other tables hold the likely match, gather `a` last.
+        let mut columns_scope = Cow::Borrowed(&scope);
+        if expected.is_some()
+            && let Some((Some(qualifier), _)) = Self::comparison_operand(preceding)
+            && let Some(pos) = scope.refs.iter().position(|r| r.reference() == qualifier)
+        {
+            let refs = &mut columns_scope.to_mut().refs;
+            let compared = refs.remove(pos);
+            refs.push(compared);
+        }
+        Self::gather_scope_columns(
+            meta,
+            &tokens,
+            &columns_scope,
+            options.disambiguate_schemas,
+            &mut out,
+            flush,
+        )
+        .await;
+        Self::rank_columns(
+            &mut out,
+            partial,
+            expected.as_ref(),
+            options.primary_key_boost,
+        );
+
+        // GROUP BY may also reference projection outputs by alias or position.
+        if scope.in_group_by(&tokens, cursor_pos) {
+            let aliases = scope.projection_aliases(&tokens);
+            out.extend(
+                aliases
+                    .into_iter()
+                    .map(|a| Suggestion::Alias(a.to_string())),
+            );
+            let items = scope.projection(&tokens).len();
+            out.extend((1..=items).map(Suggestion::Ordinal));
+        }
+        Self::gather_keywords(partial, replace, &mut out);
+        if partial.is_none() && Self::ends_complete_select(&tokens, &scope, preceding, cursor_pos) {
+            out.extend(SET_OPERATIONS.iter().map(|op| Suggestion::Keyword {
+                keyword: op.to_string(),
+                replace,
+            }));
         }
         Ok(out)
     }
 
-    /// Locate the index and parenthesis depth of the last `SELECT` token
-    /// that starts before `cursor_pos`.
+    /// Split the tokens before `cursor_pos` into the completed tokens and the
+    /// word prefix currently being typed.
     ///
-    /// Depth counting allows distinguishing nested subqueries: only tokens
-    /// at the same depth as the matching `FROM` should be considered.
-    fn locate_select(
-        tokens: &[crate::sql::token::Token],
+    /// - Cursor inside a token (`SELECT na|me`): the text before the cursor
+    ///   (`na`) is the partial.
+    /// - Cursor at the end of an identifier (`SELECT name|`): the whole
+    ///   just-ended identifier (`name`) is the partial.
+    /// - Cursor inside a string literal (`'ac|`, `'ac|me'`): the text after
+    ///   the opening quote is the partial.
+    /// - Cursor at the end of anything else (`FROM a,|`): there is no partial.
+    pub(crate) fn split_partial<'a>(
+        sql: &'a str,
+        tokens: &'a [Token],
         cursor_pos: usize,
-    ) -> Option<(usize, i32)> {
-        let mut depth = 0;
-        let mut last = None;
-        for (idx, t) in tokens.iter().enumerate() {
-            if t.start >= cursor_pos {
-                break;
+    ) -> (&'a [Token], Option<&'a str>) {
+        let before = tokens.iter().take_while(|t| t.start < cursor_pos).count();
+        match before.checked_sub(1).map(|i| &tokens[i]) {
+            Some(t) if t.kind.string().is_some() => {
+                match t.contains(cursor_pos) || t.is_unterminated_string() {
+                    true => (&tokens[..before - 1], Some(&sql[t.start + 1..cursor_pos])),
+                    false => (&tokens[..before], None),
+                }
             }
-            match t.kind {
-                TokenKind::ParenOpen => depth += 1,
-                TokenKind::ParenClose => depth -= 1,
+            Some(t) if t.contains(cursor_pos) || (t.ident().is_some() && t.ends_at(cursor_pos)) => {
+                (&tokens[..before - 1], Some(&sql[t.start..cursor_pos]))
+            }
+            _ => (&tokens[..before], None),
+        }
+    }
+
+    /// Determine whether the completed tokens end where a table reference
+    /// begins: directly after `FROM`/`JOIN`/`UPDATE`/`TABLE`, after a comma inside a FROM list,
+    /// or after a `schema.` qualifier in one of those positions.
+    fn table_position(preceding: &[Token]) -> Option<TablePosition> {
+        fn starts_reference(preceding: &[Token]) -> bool {
+            match preceding.last().map(|t| &t.kind) {
+                Some(TokenKind::Keyword(
+                    Keyword::From | Keyword::Join | Keyword::Update | Keyword::Table,
+                )) => true,
+                Some(TokenKind::Comma) => Suggestion::in_from_list(preceding),
+                _ => false,
+            }
+        }
+
+        if starts_reference(preceding) {
+            return Some(TablePosition::Unqualified);
+        }
+        match preceding {
+            [rest @ .., schema, dot] if matches!(dot.kind, TokenKind::Dot) => {
+                let schema = schema.ident()?;
+                starts_reference(rest).then(|| TablePosition::Schema(schema.to_string()))
+            }
+            _ => None,
+        }
+    }
+
+    /// Walk backwards at the current parenthesis depth and report whether the
+    /// nearest clause keyword is `FROM` or `JOIN`.
+    fn in_from_list(preceding: &[Token]) -> bool {
+        let mut depth = 0;
+        for t in preceding.iter().rev() {
+            match &t.kind {
+                TokenKind::ParenClose => depth += 1,
+                TokenKind::ParenOpen if depth == 0 => return false,
+                TokenKind::ParenOpen => depth -= 1,
+                TokenKind::Keyword(Keyword::From | Keyword::Join) if depth == 0 => return true,
+                TokenKind::Keyword(Keyword::As) => {}
+                TokenKind::Keyword(_) if depth == 0 => return false,
                 _ => {}
             }
-            if t.is_keyword(Keyword::Select) {
-                last = Some((idx, depth));
+        }
+        false
+    }
+
+    /// If the tokens before the string literal being typed end with the
+    /// right-hand side of a comparison (`col = '`, `t.col <> '`, `col != '`),
+    /// return the column's optional qualifier and name.
+    fn compared_column(preceding: &[Token]) -> Option<(Option<&str>, &str)> {
+        let operand = match preceding {
+            [operand @ .., op] if matches!(op.kind.operator(), Some("!=" | "<>")) => operand,
+            [operand @ .., op] if matches!(op.kind, TokenKind::Other('=')) => operand,
+            _ => return None,
+        };
+        Self::column_operand(operand)
+    }
+
+    /// The left operand of a comparison the completed tokens end in, ignoring
+    /// a trailing `qualifier.` being typed: `col > `, `t.col <= a.`, `col <> `.
+    /// Returns the operand's optional qualifier and column name.
+    fn comparison_operand(preceding: &[Token]) -> Option<(Option<&str>, &str)> {
+        let is_op = |t: &Token| {
+            matches!(t.kind, TokenKind::Other('<' | '>' | '=' | '!'))
+                || matches!(t.kind.operator(), Some("<=" | ">=" | "<>" | "!="))
+        };
+        let rest = match preceding {
+            [rest @ .., qualifier, dot]
+                if qualifier.ident().is_some() && matches!(dot.kind, TokenKind::Dot) =>
+            {
+                rest
             }
+            _ => preceding,
+        };
+        let ops = rest.iter().rev().take_while(|t| is_op(t)).count();
+        if !(1..=2).contains(&ops) {
+            return None;
         }
-        last
+        Self::column_operand(&rest[..rest.len() - ops])
     }
 
-    /// From a previously found `SELECT` token, scan forward to find the
-    /// corresponding `FROM` token at the same parenthesis depth.
+    /// The type of the column the cursor is being compared with (see
+    /// `comparison_operand`), resolved through `scope`.
+    async fn expected_type(
+        meta: &Database,
+        tokens: &[Token],
+        scope: &Scope,
+        preceding: &[Token],
+    ) -> Option<DataType> {
+        let (qualifier, column) = Self::comparison_operand(preceding)?;
+        let candidates: Vec<&TableRef> = match qualifier {
+            Some(q) => vec![scope.find(q)?],
+            None => scope.refs.iter().collect(),
+        };
+        for r in candidates {
+            let mut columns = Vec::new();
+            Self::gather_ref_columns(meta, tokens, r, &mut columns).await;
+            let found = columns.into_iter().find_map(|c| match c {
+                Suggestion::Column(name, data_type, _) if name == column => Some(data_type),
+                _ => None,
+            });
+            if found.is_some() {
+                return found;
+            }
+        }
+        None
+    }
+
+    /// Stable-sort columns comparable with `expected` ahead of the rest,
+    /// after moving primary key columns first when `primary_key_first`.
     ///
-    /// Returns the index of that `FROM` token if found.
-    fn locate_from(
-        tokens: &[crate::sql::token::Token],
+    /// With a `partial`, the columns starting with it case-insensitively come
+    /// first, and among them those starting with it exactly (`ID` for `ID`)
+    /// precede the others (`id`). Both sorts are applied before the primary
+    /// key and type context ones, which take precedence.
+    fn rank_columns(
+        out: &mut Suggestions,
+        partial: Option<&str>,
+        expected: Option<&DataType>,
+        primary_key_first: bool,
+    ) {
+        if let Some(partial) = partial.filter(|p| !p.is_empty()) {
+            let lower = partial.to_ascii_lowercase();
+            out.sort_by_key(|s| match s.column_parts() {
+                Some((name, ..)) => (
+                    !name.to_ascii_lowercase().starts_with(&lower),
+                    !name.starts_with(partial),
+                ),
+                None => (true, true),
+            });
+        }
+        if primary_key_first {
+            out.sort_by_key(|s| {
+                !s.column_parts()
+                    .is_some_and(|(_, _, flags)| flags.primary_key)
+            });
+        }
+        if let Some(expected) = expected {
+            out.sort_by_key(|s| {
+                !s.column_parts()
+                    .is_some_and(|(_, data_type, _)| data_type.is_comparable_with(expected))
+            });
+        }
+    }
+
+    /// If the tokens before the string literal being typed end with a JSON
+    /// path operator (`data -> '`, `t.data ->> '`, tokenized as either an
+    /// `Operator` or separate characters), return the column's optional
+    /// qualifier and name.
+    fn json_path_column(preceding: &[Token]) -> Option<(Option<&str>, &str)> {
+        let operand = match preceding {
+            [operand @ .., op] if matches!(op.kind.operator(), Some("->" | "->>")) => operand,
+            [operand @ .., a, b, c]
+                if matches!(
+                    (&a.kind, &b.kind, &c.kind),
+                    (
+                        TokenKind::Other('-'),
+                        TokenKind::Other('>'),
+                        TokenKind::Other('>')
+                    )
+                ) =>
+            {
+                operand
+            }
+            [operand @ .., a, b]
+                if matches!(
+                    (&a.kind, &b.kind),
+                    (TokenKind::Other('-'), TokenKind::Other('>'))
+                ) =>
+            {
+                operand
+            }
+            _ => return None,
+        };
+        Self::column_operand(operand)
+    }
+
+    /// The `column` or `qualifier.column` the tokens end with.
+    fn column_operand(tokens: &[Token]) -> Option<(Option<&str>, &str)> {
+        match tokens {
+            [.., qualifier, dot, column] if matches!(dot.kind, TokenKind::Dot) => {
+                Some((qualifier.ident(), column.ident()?))
+            }
+            [.., column] => Some((None, column.ident()?)),
+            [] => None,
+        }
+    }
+
+    /// Determine the table/alias qualifying the word at the cursor: the
+    /// identifier of an `ident .` pair directly before the cursor (or before
+    /// the partial word being typed), after the scope's `SELECT` token.
+    ///
+    /// Returns the identifier (without the trailing dot) if present; for a
+    /// dotted chain (`a.b.`) that is its last segment (see `qualified_chain`).
+    pub(crate) fn qualified_prefix(
+        sql: &str,
+        tokens: &[Token],
         select_idx: usize,
-        select_depth: i32,
-    ) -> Option<usize> {
-        let mut depth = select_depth;
-        for (idx, t) in tokens.iter().enumerate().skip(select_idx + 1) {
-            match t.kind {
-                TokenKind::ParenOpen => depth += 1,
-                TokenKind::ParenClose => depth -= 1,
-                _ => {}
+        cursor_pos: usize,
+    ) -> Option<String> {
+        Self::qualified_chain(sql, tokens, select_idx, cursor_pos)?.pop()
+    }
+
+    /// The dotted chain qualifying the word at the cursor, outermost segment
+    /// first: `["a", "b"]` for `a.b.|` or `a.b.c|`, `None` if the cursor does
+    /// not follow `ident .`.
+    ///
+    /// Entirely token-driven: only the tokens between `select_idx` and the
+    /// cursor are inspected, so an earlier `a.id` in the projection does not
+    /// qualify a later unqualified position. Whitespace and comments (which
+    /// produce no tokens) may sit between an identifier and its dot
+    /// (`a /* x */ .`), and identifiers may contain non-ASCII letters
+    /// (`é_table.`), which tokenize as adjacent `Other` tokens.
+    pub(crate) fn qualified_chain(
+        sql: &str,
+        tokens: &[Token],
+        select_idx: usize,
+        cursor_pos: usize,
+    ) -> Option<Vec<String>> {
+        let mut visible: Vec<&Token> = tokens
+            .iter()
+            .skip(select_idx + 1)
+            .take_while(|t| t.start < cursor_pos)
+            .collect();
+
+        // The partial word touching the cursor is not part of the qualifier.
+        if visible.last().is_some_and(|t| t.end >= cursor_pos) {
+            let partial = word_run(&visible);
+            visible.truncate(visible.len() - partial);
+        }
+        let mut chain = Vec::new();
+        let mut rest = visible.as_slice();
+        while let Some((dot, before)) = rest.split_last() {
+            if !matches!(dot.kind, TokenKind::Dot) {
+                break;
             }
-            if depth == select_depth && t.is_keyword(Keyword::From) {
-                return Some(idx);
+            let run = word_run(before);
+            let (Some(first), Some(last)) = (before[before.len() - run..].first(), before.last())
+            else {
+                break;
+            };
+            let text = &sql[sql.floor_char_boundary(first.start)..sql.ceil_char_boundary(last.end)];
+            let ident = text
+                .rsplit(|c: char| !(c.is_alphanumeric() || c == '_'))
+                .next()
+                .unwrap_or("");
+            if ident.is_empty() {
+                break;
             }
+            chain.push(ident.to_string());
+            rest = &before[..before.len() - run];
         }
-        None
+        chain.reverse();
+        (!chain.is_empty()).then_some(chain)
     }
 
-    /// Extract table names and aliases beginning just after the `FROM` token.
+    /// Gather schema and table suggestions for a table reference position.
     ///
-    /// Parsing rules (simplified):
-    /// - Continue until depth decreases below `select_depth` or a terminating
-    ///   keyword (e.g. WHERE, GROUP, ORDER, etc.) at the same depth is found.
-    /// - Handle comma separated tables and JOIN clauses, skipping the JOIN keyword.
-    /// - Support aliases in the forms: `table AS alias` and `table alias`.
-    fn extract_tables(
-        tokens: &[crate::sql::token::Token],
-        from_idx: usize,
-        select_depth: i32,
-    ) -> (Vec<String>, std::collections::HashMap<String, String>) {
-        use std::collections::HashMap;
+    /// Names are matched case-insensitively against `partial`. Schemas come
+    /// first (unqualified positions only), followed by tables; both are sorted
+    /// so the output does not depend on map iteration order.
+    async fn gather_tables(
+        meta: &Database,
+        position: &TablePosition,
+        partial: &str,
+        out: &mut Suggestions,
+    ) {
+        let partial = partial.to_ascii_lowercase();
+        let matches = |name: &str| name.to_ascii_lowercase().starts_with(&partial);
+        let schemas = meta.schemas.read().await;
+
+        let mut names: Vec<&String> = schemas
+            .keys()
+            .filter(|s| match position {
+                TablePosition::Unqualified => true,
+                TablePosition::Schema(name) => *s == name,
+            })
+            .collect();
+        names.sort();
+
+        if *position == TablePosition::Unqualified {
+            out.extend(
+                names
+                    .iter()
+                    .filter(|s| matches(s))
+                    .map(|s| Suggestion::Schema(s.to_string())),
+            );
+        }
+
         let mut tables = Vec::new();
-        let mut aliases = HashMap::new();
-        let mut depth = select_depth;
-        let mut i = from_idx + 1; // Start after the FROM token
-
-        while let Some(t) = tokens.get(i) {
-            // 1. Handle parenthesis tracking to respect nesting depth
-            match t.kind {
-                TokenKind::ParenOpen => {
-                    depth += 1;
-                    i += 1;
-                    continue;
+        for name in names {
+            let schema_tables = schemas[name].tables.read().await;
+            tables.extend(
+                schema_tables
+                    .keys()
+                    .filter(|t| matches(t))
+                    .map(|t| Suggestion::Table {
+                        schema: name.clone(),
+                        name: t.clone(),
+                    }),
+            );
+        }
+        tables.sort();
+        out.extend(tables);
+    }
+
+    /// The byte span a completion of `partial` replaces: from the start of
+    /// the partial to the cursor, or with `SearchOptions::replace_whole_token`
+    /// to the end of the token the cursor is within.
+    fn replace_span(
+        tokens: &[Token],
+        partial: Option<&str>,
+        cursor_pos: usize,
+        options: &SearchOptions,
+    ) -> (usize, usize) {
+        let start = cursor_pos - partial.map_or(0, str::len);
+        let end = match options.replace_whole_token {
+            true => tokens
+                .iter()
+                .find(|t| t.contains(cursor_pos))
+                .map_or(cursor_pos, |t| t.end),
+            false => cursor_pos,
+        };
+        (start, end)
+    }
+
+    /// True if the completed tokens end a SELECT that could stand on its own
+    /// (`SELECT * FROM a |`, `... WHERE x = y |`): the cursor is past the
+    /// scope's FROM, at the SELECT's depth, right after a closing paren or an
+    /// identifier that does not expect an operand (see `EXPECTS_OPERAND`) or
+    /// a number.
+    fn ends_complete_select(
+        tokens: &[Token],
+        scope: &Scope,
+        preceding: &[Token],
+        cursor_pos: usize,
+    ) -> bool {
+        let Some(last) = preceding.last() else {
+            return false;
+        };
+        tokens[scope.select_idx].is_keyword(Keyword::Select)
+            && preceding.len() > scope.from_idx + 1
+            && !ends_at_time_zone(preceding)
+            && depth_at(tokens, cursor_pos) == scope.depth
+            && match &last.kind {
+                TokenKind::Ident(word) => {
+                    !EXPECTS_OPERAND.contains(&word.to_ascii_lowercase().as_str())
                 }
-                TokenKind::ParenClose => {
-                    depth -= 1;
-                    if depth < select_depth {
-                        break; // Exit if we've closed out of our SELECT scope
-                    }
-                    i += 1;
+                TokenKind::ParenClose | TokenKind::Number(_) => true,
+                _ => false,
+            }
+    }
+
+    /// Gather the (upper-cased) keywords starting with `partial`,
+    /// case-insensitively, each replacing `replace`. Nothing is suggested
+    /// without a partial.
+    fn gather_keywords(partial: Option<&str>, replace: (usize, usize), out: &mut Suggestions) {
+        let Some(partial) = partial.filter(|p| !p.is_empty()) else {
+            return;
+        };
+        let lower = partial.to_ascii_lowercase();
+        out.extend(
+            Keyword::ALL
+                .iter()
+                .filter(|k| k.as_str().starts_with(&lower))
+                .map(|k| Suggestion::Keyword {
+                    keyword: k.as_str().to_ascii_uppercase(),
+                    replace,
+                }),
+        );
+    }
+
+    /// Gather the allowed values of the first enum-typed `column` found in
+    /// `tables` (each looked up like `gather_columns`), filtered
+    /// case-insensitively by `partial`.
+    async fn gather_enum_values(
+        meta: &Database,
+        tables: &[TableRef],
+        column: &str,
+        partial: &str,
+        out: &mut Suggestions,
+    ) {
+        let partial = partial.to_ascii_lowercase();
+        let schemas = meta.schemas.read().await;
+        for table in tables {
+            for schema in Self::candidate_schemas(meta, &schemas, table.schema.as_deref()) {
+                let schema_tables = schema.tables.read().await;
+                let Some(t) = schema_tables.get(&table.name) else {
                     continue;
+                };
+                if let Some(DataType::Enum(variants)) =
+                    t.columns.read().await.get(column).map(|c| &c.data_type)
+                {
+                    out.extend(
+                        variants
+                            .iter()
+                            .filter(|v| v.to_ascii_lowercase().starts_with(&partial))
+                            .map(|v| Suggestion::EnumValue(v.clone())),
+                    );
+                    return;
                 }
-                _ => {}
             }
+        }
+    }
 
-            // 2. Only process tokens at our target SELECT depth
-            if depth != select_depth {
-                i += 1;
+    /// Resolve a composite access chain (`a.b.c.`): `a` a base table in
+    /// `scope` (looked up only in its written schema when qualified), `b` one
+    /// of its columns and every later segment a field (`Column::fields`) of
+    /// the one before. Returns the fields of the last segment, or `None` if
+    /// the chain is shorter than two segments or any segment is unknown or not
+    /// composite.
+    async fn composite_fields(
+        meta: &Database,
+        scope: &Scope,
+        chain: &[String],
+    ) -> Option<Vec<Column>> {
+        let [head, column, path @ ..] = chain else {
+            return None;
+        };
+        if scope.is_ambiguous(head) {
+            return None;
+        }
+        let table = scope
+            .find(head)
+            .filter(|r| !r.is_derived() && !r.function)?;
+        let schemas = meta.schemas.read().await;
+        for schema in Self::candidate_schemas(meta, &schemas, table.schema.as_deref()) {
+            let schema_tables = schema.tables.read().await;
+            let Some(t) = schema_tables.get(&table.name) else {
                 continue;
+            };
+            let columns = t.columns.read().await;
+            let mut current = columns.get(column)?;
+            for field in path {
+                current = current.fields.iter().find(|f| f.name == *field)?;
             }
+            return (!current.fields.is_empty()).then(|| current.fields.clone());
+        }
+        None
+    }
 
-            // 3. Handle terminating keywords and JOIN clauses
-            if let TokenKind::Keyword(k) = &t.kind {
-                if Keyword::TERMINATORS.contains(k) {
-                    break; // Stop at WHERE, GROUP BY, ORDER BY, etc.
+    /// Gather the registered JSON keys (`Column::json_keys`) of the first
+    /// `column` found in `tables` (each looked up like `gather_columns`) that
+    /// has any, filtered case-insensitively
+    /// by `partial`.
+    async fn gather_json_keys(
+        meta: &Database,
+        tables: &[TableRef],
+        column: &str,
+        partial: &str,
+        out: &mut Suggestions,
+    ) {
+        let partial = partial.to_ascii_lowercase();
+        let schemas = meta.schemas.read().await;
+        for table in tables {
+            for schema in Self::candidate_schemas(meta, &schemas, table.schema.as_deref()) {
+                let schema_tables = schema.tables.read().await;
+                let Some(t) = schema_tables.get(&table.name) else {
+                    continue;
+                };
+                let columns = t.columns.read().await;
+                let Some(keys) = columns
+                    .get(column)
+                    .map(|c| &c.json_keys)
+                    .filter(|k| !k.is_empty())
+                else {
+                    continue;
+                };
+                out.extend(
+                    keys.iter()
+                        .filter(|k| k.to_ascii_lowercase().starts_with(&partial))
+                        .map(|k| Suggestion::JsonKey(k.clone())),
+                );
+                return;
+            }
+        }
+    }
+
+    /// Gather the columns of every reference in `scope`, in FROM-clause order.
+    ///
+    /// A base table referenced several times (self-joins, or `users` next to
+    /// `public.users` with `public` on the search path) is listed once.
+    ///
+    /// With `disambiguate`, an unqualified base table found in several
+    /// schemas yields `SchemaColumn`s (see `gather_schema_columns`).
+    ///
+    /// With `flush`, each reference's columns are moved from `out` into it as
+    /// soon as they are gathered (see `search_stream`).
+    async fn gather_scope_columns(
+        meta: &Database,
+        tokens: &[Token],
+        scope: &Scope,
+        disambiguate: bool,
+        out: &mut Suggestions,
+        flush: Option<&UnboundedSender<Suggestion>>,
+    ) {
+        let mut seen: Vec<(Option<&str>, &str)> = Vec::new();
+        for r in &scope.refs {
+            if !r.is_derived() {
+                // A schema on the search path is implicit: `public.users` is `users`.
+                let schema = r
+                    .schema
+                    .as_deref()
+                    .filter(|s| !meta.search_path.iter().any(|p| p == s));
+                let key = (schema, r.name.as_str());
+                if seen.contains(&key) {
+                    continue;
                 }
-                if *k == Keyword::Join {
-                    i += 1;
-                    continue; // Skip JOIN keyword itself
+                seen.push(key);
+            }
+            match disambiguate && r.schema.is_none() && !r.is_derived() && !r.function {
+                true => Self::gather_schema_columns(meta, &r.name, out).await,
+                false => Self::gather_ref_columns(meta, tokens, r, out).await,
+            }
+            if let Some(tx) = flush {
+                for suggestion in out.drain(..) {
+                    let _ = tx.unbounded_send(suggestion);
                 }
             }
+        }
+    }
 
-            // 4. Extract table names and handle aliasing patterns
-            if let Some(name) = t.ident() {
-                let name = name.to_string();
-                if !tables.contains(&name) {
-                    tables.push(name.clone());
+    /// Gather the columns visible through a single FROM reference: a base
+    /// table's columns from metadata, a registered table function's output
+    /// columns, or a derived table's projected columns.
+    pub(crate) fn gather_ref_columns<'a>(
+        meta: &'a Database,
+        tokens: &'a [Token],
+        table: &'a TableRef,
+        out: &'a mut Suggestions,
+    ) -> std::pin::Pin<Box<dyn Future<Output = ()> + Send + 'a>> {
+        Box::pin(async move {
+            match table.subquery {
+                Some(select_idx) => {
+                    Self::gather_derived_columns(meta, tokens, select_idx, out).await
                 }
-
-                // 5. Check for "table AS alias" pattern
-                if let Some(alias_tok) = tokens
-                    .get(i + 2)
-                    .filter(|_| tokens.get(i + 1).is_some_and(|x| x.is_keyword(Keyword::As)))
-                    .and_then(|x| x.ident())
-                {
-                    aliases.insert(alias_tok.to_string(), name.clone());
-                    i += 3; // Skip table, AS, alias
-                    continue;
+                None if table.function => {
+                    let columns = meta.function_columns(&table.name).await.unwrap_or_default();
+                    out.extend(columns.into_iter().map(|c| {
+                        let flags = ColumnFlags {
+                            deprecated: c.deprecated,
+                            primary_key: c.primary_key,
+                        };
+                        Suggestion::Column(c.name, c.data_type, flags)
+                    }));
                 }
+                None => Self::gather_columns(meta, table.schema.as_deref(), &table.name, out).await,
+            }
+        })
+    }
 
-                // 6. Check for "table alias" pattern (no AS keyword)
-                if let Some(alias_tok) = tokens
-                    .get(i + 1)
-                    .filter(|x| x.ident().is_some() && !matches!(x.kind, TokenKind::Keyword(_)))
-                    .and_then(|x| x.ident())
+    /// Gather the columns projected by the subquery starting at `select_idx`.
+    ///
+    /// Plain column references (`col` or `t.col`) are resolved against the
+    /// subquery's own FROM clause to recover their data types. A `*` expands
+    /// to the columns of every inner FROM item in order, `t.*` to those of
+    /// `t`. An aliased column (`id AS ident`, `id ident`) is suggested under
+    /// its alias with the base column's type. Items that cannot be resolved
+    /// (expressions) are skipped.
+    async fn gather_derived_columns(
+        meta: &Database,
+        tokens: &[Token],
+        select_idx: usize,
+        out: &mut Suggestions,
+    ) {
+        let Some(inner) = Scope::from_select(tokens, select_idx) else {
+            return;
+        };
+        for item in inner.projection(tokens) {
+            let star = match item {
+                [star] if matches!(star.kind, TokenKind::Other('*')) => {
+                    Some(inner.refs.iter().collect())
+                }
+                [qualifier, dot, star]
+                    if matches!(dot.kind, TokenKind::Dot)
+                        && matches!(star.kind, TokenKind::Other('*')) =>
                 {
-                    aliases.insert(alias_tok.to_string(), name.clone());
-                    i += 2; // Skip table, alias
-                    continue;
+                    qualifier
+                        .ident()
+                        .and_then(|q| inner.find(q))
+                        .map(|r| vec![r])
+                }
+                _ => None,
+            };
+            if let Some(refs) = star {
+                for r in refs {
+                    Self::gather_ref_columns(meta, tokens, r, out).await;
                 }
+                continue;
             }
-
-            // 7. Skip commas between table references
-            if matches!(t.kind, TokenKind::Comma) {
-                i += 1;
+            // `col AS alias` / `col alias`: resolve `col`, suggest `alias`
+            let (item, alias) = match item {
+                [rest @ .., kw, alias] if kw.is_keyword(Keyword::As) => (rest, alias.ident()),
+                [rest @ .., alias] if rest.last().is_some_and(|t| t.ident().is_some()) => {
+                    (rest, alias.ident())
+                }
+                _ => (item, None),
+            };
+            let (candidates, column) = match item {
+                [column] => (inner.refs.iter().collect::<Vec<_>>(), column),
+                [qualifier, dot, column] if matches!(dot.kind, TokenKind::Dot) => {
+                    match qualifier.ident().and_then(|q| inner.find(q)) {
+                        Some(r) => (vec![r], column),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            };
+            let Some(column) = column.ident() else {
                 continue;
+            };
+            for r in candidates {
+                let mut columns = Vec::new();
+                Self::gather_ref_columns(meta, tokens, r, &mut columns).await;
+                let found = columns.into_iter().find_map(|c| match c {
+                    Suggestion::Column(name, data_type, flags) if name == column => {
+                        let name = alias.map_or(name, str::to_string);
+                        Some(Suggestion::Column(name, data_type, flags))
+                    }
+                    _ => None,
+                });
+                if let Some(found) = found {
+                    out.push(found);
+                    break;
+                }
             }
-            i += 1;
         }
-        (tables, aliases)
     }
 
-    /// Determine a qualified table/alias prefix if the cursor is currently
-    /// positioned after something like `alias.` within the SELECT projection.
-    ///
-    /// Returns the identifier (without the trailing dot) if present.
-    fn qualified_prefix(sql: &str, select_end: usize, cursor_pos: usize) -> Option<String> {
-        if cursor_pos <= select_end {
-            return None;
+    /// Gather the columns of `table` like `gather_columns`, but as
+    /// `SchemaColumn`s labelled with their schema when more than one schema
+    /// declares the table.
+    async fn gather_schema_columns(meta: &Database, table: &str, out: &mut Suggestions) {
+        let mut found = Vec::new();
+        {
+            let schemas = meta.schemas.read().await;
+            for schema in meta.search_order(&schemas) {
+                if schema.tables.read().await.contains_key(table) {
+                    found.push(schema.name.clone());
+                }
+            }
+        }
+        if found.len() < 2 {
+            return Self::gather_columns(meta, None, table, out).await;
+        }
+        for schema in found {
+            let mut columns = Vec::new();
+            Self::gather_columns(meta, Some(&schema), table, &mut columns).await;
+            out.extend(columns.into_iter().filter_map(|c| match c {
+                Suggestion::Column(column, data_type, flags) => Some(Suggestion::SchemaColumn {
+                    schema: schema.clone(),
+                    table: table.to_string(),
+                    column,
+                    data_type,
+                    flags,
+                }),
+                _ => None,
+            }));
+        }
+    }
+
+    /// The schemas a table reference is looked up in: only `schema` when the
+    /// query qualifies the table, otherwise every schema in
+    /// `Database::search_order`.
+    fn candidate_schemas<'s>(
+        meta: &Database,
+        schemas: &'s HashMap<String, Schema>,
+        schema: Option<&str>,
+    ) -> Vec<&'s Schema> {
+        match schema {
+            Some(name) => schemas.get(name).into_iter().collect(),
+            None => meta.search_order(schemas),
         }
-        let region = &sql[select_end..cursor_pos];
-        region.rfind('.').and_then(|dot| {
-            let before = region[..dot].trim_end();
-            let ident = before
-                .rsplit(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
-                .next()
-                .unwrap_or("");
-            (!ident.is_empty()).then(|| ident.to_string())
-        })
     }
 
-    /// Gather column suggestions for a single table name across all schemas.
+    /// Gather column suggestions for a single table name across all schemas,
+    /// visited in `Database::search_order`, or only in `schema` when the
+    /// query qualifies the table (`public.users`).
     ///
-    /// Columns are appended directly to `out` preserving order as supplied
-    /// by `Table::ordered_columns`.
-    async fn gather_columns(meta: &Database, table: &str, out: &mut Suggestions) {
+    /// Columns are appended directly to `out` in the table's column order,
+    /// flagged deprecated where the metadata says so.
+    pub(crate) async fn gather_columns(
+        meta: &Database,
+        schema: Option<&str>,
+        table: &str,
+        out: &mut Suggestions,
+    ) {
         let schemas = meta.schemas.read().await;
-        for schema in schemas.values() {
+        for schema in Self::candidate_schemas(meta, &schemas, schema) {
             let tables = schema.tables.read().await;
             if let Some(t) = tables.get(table) {
-                for (col, dt) in t.ordered_columns().await {
-                    out.push(Suggestion::Column(col, dt));
+                let columns = t.columns.read().await;
+                for c in t.column_order.iter().filter_map(|n| columns.get(n)) {
+                    let flags = ColumnFlags {
+                        deprecated: c.deprecated,
+                        primary_key: c.primary_key,
+                    };
+                    out.push(Suggestion::Column(
+                        c.name.clone(),
+                        c.data_type.clone(),
+                        flags,
+                    ));
                 }
             }
         }
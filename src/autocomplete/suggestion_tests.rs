@@ -4,7 +4,7 @@ use rstest::rstest;
 
 /// Build a lightweight in-memory `Database` with the provided tables (all in "public" schema).
 async fn database(database: &str, tables: &[(&str, Vec<(&str, DataType)>)]) -> Database {
-    let mut meta = Database::new(database);
+    let meta = Database::new(database);
     for (table_name, columns) in tables {
         meta.insert_table(
             "public",
@@ -28,7 +28,7 @@ async fn database_multi_schema(
     other_schema: &str,
     other_tables: &[(&str, Vec<(&str, DataType)>)],
 ) -> Database {
-    let mut meta = Database::new(database);
+    let meta = Database::new(database);
     for (table_name, columns) in public_tables {
         meta.insert_table(
             "public",
@@ -135,7 +135,7 @@ mod column_testing {
         let expected_columns: Vec<_> = tables
             .into_iter()
             .flat_map(|(_, columns)| columns)
-            .map(|(name, data_type)| Suggestion::Column(name.to_string(), data_type))
+            .map(|(name, data_type)| Suggestion::column(name.to_string(), data_type))
             .collect();
 
         assert_eq!(result, expected_columns);
@@ -200,7 +200,7 @@ mod column_testing {
 
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(name, data_type)| Suggestion::Column(name.to_string(), data_type))
+            .map(|(name, data_type)| Suggestion::column(name.to_string(), data_type))
             .collect();
 
         assert_eq!(
@@ -303,6 +303,17 @@ mod column_testing {
             ],
             vec![("id", DataType::Uuid)]
         )]
+    // Case 12: Same query, cursor in the whitespace after the dot
+    #[case(
+            "SELECT (SELECT inner.  FROM inner JOIN another ON inner.id = another.inner_id) FROM outer",
+            (22, None),
+            vec![
+                ("inner", vec![("id", DataType::Uuid)]),
+                ("another", vec![("inner_id", DataType::Uuid), ("val", DataType::Text(None))]),
+                ("outer", vec![("oid", DataType::Uuid)])
+            ],
+            vec![("id", DataType::Uuid)]
+        )]
     #[tokio::test]
     async fn should_recommend_qualified_columns(
         #[case] sql: &str,
@@ -321,7 +332,7 @@ mod column_testing {
         // Should
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(name, data_type)| Suggestion::Column(name.to_string(), data_type))
+            .map(|(name, data_type)| Suggestion::column(name.to_string(), data_type))
             .collect();
 
         assert_eq!(
@@ -330,17 +341,375 @@ mod column_testing {
         );
     }
 
-    // Derived subquery with star: current behavior -> no derived columns captured (star not expanded)
+    // A dot earlier in the projection must not qualify a later bare item.
+    #[tokio::test]
+    async fn earlier_qualifier_does_not_leak_into_next_item() {
+        let meta = database(
+            "postgres",
+            &[
+                ("a", vec![("id", DataType::Uuid)]),
+                ("b", vec![("bid", DataType::Uuid)]),
+            ],
+        )
+        .await;
+
+        let result = Suggestion::search("SELECT a.id,  FROM a, b", Cursor::new(13, None), meta)
+            .await
+            .expect("suggestion shouldnt error");
+
+        assert_eq!(
+            result,
+            vec![
+                Suggestion::column("id".to_string(), DataType::Uuid),
+                Suggestion::column("bid".to_string(), DataType::Uuid),
+            ]
+        );
+    }
+
+    #[rstest]
+    // Case 1: Upper-case prefix prefers the upper-case column
+    #[case("SELECT ID FROM a, b", 9, vec!["ID", "id", "Id_x", "name"])]
+    // Case 2: Lower-case prefix keeps the lower-case column first
+    #[case("SELECT id FROM a, b", 9, vec!["id", "ID", "Id_x", "name"])]
+    // Case 3: Mixed-case prefix
+    #[case("SELECT Id FROM a, b", 9, vec!["Id_x", "id", "ID", "name"])]
+    // Case 4: Qualified
+    #[case("SELECT a.I FROM a", 10, vec!["ID", "id", "name"])]
+    #[tokio::test]
+    async fn should_rank_exact_case_matches_first(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![
+                        ("name", DataType::Text(None)),
+                        ("id", DataType::Uuid),
+                        ("ID", DataType::Uuid),
+                    ],
+                ),
+                ("b", vec![("Id_x", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            expected,
+            "{sql}"
+        );
+    }
+
+    // Only the `;` after the string literal separates the statements.
+    #[tokio::test]
+    async fn semicolon_in_string_literal_is_not_a_statement_boundary() {
+        let tables = [
+            ("a", vec![("id", DataType::Uuid)]),
+            ("b", vec![("bid", DataType::Uuid)]),
+        ];
+        let meta = database("postgres", &tables).await;
+        let sql = "SELECT ';' FROM a; SELECT  FROM b";
+        let result = Suggestion::search(sql, Cursor::new(26, None), &meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), ["bid"]);
+        let sql = "SELECT ';' ; SELECT  FROM b";
+        let result = Suggestion::search(sql, Cursor::new(20, None), &meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), ["bid"]);
+    }
+
+    // WHERE-clause subqueries: `IN (SELECT ...)` opens its own SELECT scope which
+    // ends again once the parenthesis closes.
+    #[rstest]
+    // Case 1: Cursor inside `IN (SELECT  FROM sub)` resolves to the subquery scope
+    #[case(
+        "SELECT * FROM a WHERE a.id IN (SELECT  FROM sub)",
+        (38, None),
+        vec![
+            ("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))]),
+            ("sub", vec![("sid", DataType::Uuid), ("sval", DataType::Text(None))])
+        ],
+        vec![("sid", DataType::Uuid), ("sval", DataType::Text(None))]
+    )]
+    // Case 2: Cursor after the closed subquery falls back to the outer scope
+    #[case(
+        "SELECT * FROM a WHERE a.id IN (SELECT sid FROM sub) AND ",
+        (56, None),
+        vec![
+            ("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))]),
+            ("sub", vec![("sid", DataType::Uuid), ("sval", DataType::Text(None))])
+        ],
+        vec![("id", DataType::Uuid), ("name", DataType::Text(None))]
+    )]
+    // Case 3: BETWEEN before the subquery doesn't disturb qualified resolution inside it
+    #[case(
+        "SELECT * FROM a WHERE a.n BETWEEN 1 AND 2 AND a.id IN (SELECT s.  FROM sub s)",
+        (64, None),
+        vec![
+            ("a", vec![("id", DataType::Uuid), ("n", DataType::Integer(None))]),
+            ("sub", vec![("sid", DataType::Uuid), ("sval", DataType::Text(None))])
+        ],
+        vec![("sid", DataType::Uuid), ("sval", DataType::Text(None))]
+    )]
+    #[tokio::test]
+    async fn should_recommend_columns_where_in_subquery(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] tables: Vec<(&str, Vec<(&str, DataType)>)>,
+        #[case] expected: Vec<(&str, DataType)>,
+    ) {
+        let meta = database("postgres", &tables).await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("where in subquery");
+        let expected_columns: Vec<_> = expected
+            .into_iter()
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
+            .collect();
+        assert_eq!(
+            result, expected_columns,
+            "WHERE IN subquery scope should be isolated from the outer query"
+        );
+    }
+
+    // Multibyte identifiers before the cursor must not split a character.
+    #[rstest]
+    // Case 1: Multibyte qualifier
+    #[case("SELECT é_table. FROM é_table", 16)]
+    // Case 2: Multibyte text earlier in the projection
+    #[case("SELECT 'ünïcödé', é_table. FROM é_table", 31)]
+    #[tokio::test]
+    async fn should_resolve_multibyte_qualified_prefix(#[case] sql: &str, #[case] cursor: usize) {
+        assert_eq!(&sql[cursor - 1..cursor], ".");
+        let meta = database("postgres", &[("é_table", vec![("id", DataType::Uuid)])]).await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .expect("multibyte qualifier");
+        assert_eq!(result.into_labels(), ["id"]);
+    }
+
+    // `AT TIME ZONE` words are neither columns, aliases nor the end of a query.
+    #[rstest]
+    // Case 1: Qualified column after the expression
+    #[case("SELECT ts AT TIME ZONE 'UTC', a. FROM a", 32, vec!["id", "ts"])]
+    // Case 2: Unqualified column after the expression
+    #[case("SELECT ts AT TIME ZONE 'UTC',  FROM a", 30, vec!["id", "ts"])]
+    // Case 3: Qualified column in WHERE after the expression in the projection
+    #[case("SELECT ts AT TIME ZONE 'UTC' FROM a WHERE a.", 44, vec!["id", "ts"])]
+    // Case 4: A zone column is not an output alias
+    #[case("SELECT ts AT TIME ZONE tz FROM a GROUP BY ", 42, vec!["id", "ts", "1"])]
+    // Case 5: An alias after the zone literal
+    #[case("SELECT ts AT TIME ZONE 'UTC' local FROM a GROUP BY ", 51, vec!["id", "ts", "local", "1"])]
+    // Case 6: The zone operand is still expected, not a set operation
+    #[case("SELECT * FROM a WHERE ts AT TIME ZONE ", 38, vec!["id", "ts"])]
+    #[tokio::test]
+    async fn should_complete_around_at_time_zone(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[(
+                "a",
+                vec![("id", DataType::Uuid), ("ts", DataType::Timestamp(None))],
+            )],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .expect("at time zone");
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+
+    // Array constructor brackets do not hide the qualifier or the partial word.
+    #[rstest]
+    // Case 1: Qualifier right after `ARRAY[`
+    #[case("SELECT ARRAY[a. FROM a", 15, vec!["id", "name"])]
+    // Case 2: Second element of a closed constructor
+    #[case("SELECT ARRAY[a.id, a.] FROM a", 21, vec!["id", "name"])]
+    // Case 3: Unqualified partial after `[`, matching column first
+    #[case("SELECT ARRAY[na] FROM a", 15, vec!["name", "id"])]
+    // Case 4: Subscript after a constructor
+    #[case("SELECT ARRAY[a.id][1], a. FROM a", 25, vec!["id", "name"])]
+    #[tokio::test]
+    async fn should_complete_inside_array_constructor(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[(
+                "a",
+                vec![("id", DataType::Uuid), ("name", DataType::Text(None))],
+            )],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .expect("array constructor");
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            expected,
+            "{sql}"
+        );
+    }
+
+    // Whitespace and comments between the qualifier and its dot are skipped.
+    #[rstest]
+    // Case 1: Block comment before the dot
+    #[case("SELECT a /* x */ . FROM a, b", 18)]
+    // Case 2: Line comment and newline before the dot
+    #[case("SELECT a -- x\n. FROM a, b", 15)]
+    // Case 3: A dot inside a comment after the qualified prefix is ignored
+    #[case("SELECT a. /* b. */ FROM a, b", 18)]
+    #[tokio::test]
+    async fn should_skip_comments_before_qualifier_dot(#[case] sql: &str, #[case] cursor: usize) {
+        let meta = database(
+            "postgres",
+            &[
+                ("a", vec![("id", DataType::Uuid)]),
+                ("b", vec![("bid", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .expect("commented qualifier");
+        assert_eq!(result.into_labels(), ["id"], "{sql}");
+    }
+
+    // A qualifier declared twice in one FROM (`FROM a x, b x`) is ambiguous:
+    // nothing is suggested for it, while other qualifiers still resolve.
+    #[rstest]
+    // Case 1: Duplicate alias
+    #[case("SELECT x. FROM a x, b x", 9, vec![])]
+    // Case 2: Duplicate alias shadowing a real table name
+    #[case("SELECT x. FROM a x JOIN b x ON true", 9, vec![])]
+    // Case 3: A distinct alias in the same FROM
+    #[case("SELECT y. FROM a x, b x, a y", 9, vec!["ax"])]
+    #[tokio::test]
+    async fn should_not_resolve_ambiguous_aliases(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                ("a", vec![("ax", DataType::Integer(None))]),
+                ("b", vec![("bx", DataType::Integer(None))]),
+                ("x", vec![("xx", DataType::Integer(None))]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .expect("ambiguous alias");
+        assert_eq!(result.into_labels(), expected);
+    }
+
+    // Parenthesized tuples and ROW(...) in WHERE are expression groups, not
+    // subqueries: the cursor inside them stays in the enclosing SELECT scope.
+    #[rstest]
+    // Case 1: Qualified inside a tuple
+    #[case("SELECT * FROM a, b WHERE (a.x, a.) IN ((1, 2))", 33)]
+    // Case 2: Qualified inside ROW(...)
+    #[case("SELECT * FROM a, b WHERE ROW(a.x, a.) = ROW(1, 2)", 36)]
+    // Case 3: Tuple compared against a subquery that follows it
+    #[case("SELECT * FROM a, b WHERE (a.x, a.) IN (SELECT bx, by FROM b)", 33)]
+    // Case 4: An earlier item qualified by another table does not leak
+    #[case("SELECT * FROM a, b WHERE (b.bx, a.) IN ((1, 2))", 34)]
+    #[tokio::test]
+    async fn should_recommend_columns_inside_where_tuple(#[case] sql: &str, #[case] cursor: usize) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![
+                        ("x", DataType::Integer(None)),
+                        ("y", DataType::Integer(None)),
+                    ],
+                ),
+                (
+                    "b",
+                    vec![
+                        ("bx", DataType::Integer(None)),
+                        ("by", DataType::Integer(None)),
+                    ],
+                ),
+            ],
+        )
+        .await;
+        assert!(
+            sql[..cursor].ends_with("a."),
+            "cursor must follow `a.`: {sql}"
+        );
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .expect("where tuple");
+        assert_eq!(result.into_labels(), ["x", "y"]);
+    }
+
+    // Inheritance markers: `parent*` and `ONLY parent` resolve to `parent`
+    #[rstest]
+    // Case 1: Legacy include-children star
+    #[case("SELECT  FROM accounts*", 7)]
+    // Case 2: Star before an alias, qualified through it
+    #[case("SELECT x. FROM accounts* x", 9)]
+    // Case 3: ONLY before the table
+    #[case("SELECT  FROM ONLY accounts", 7)]
+    #[tokio::test]
+    async fn should_recommend_inheritance_marked_tables(#[case] sql: &str, #[case] cursor: usize) {
+        let meta = database(
+            "postgres",
+            &[(
+                "accounts",
+                vec![("id", DataType::Uuid), ("owner", DataType::Text(None))],
+            )],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .expect("inheritance markers");
+        assert_eq!(result.into_labels(), ["id", "owner"], "{sql}");
+    }
+
+    // Derived subquery with star: expanded into the inner FROM items' columns
     #[rstest]
-    // Case 1: Derived subquery star expansion unsupported -> expect empty suggestions
+    // Case 1: Derived subquery star expands in table order
     #[case(
         "SELECT  FROM (SELECT * FROM a) sub",
         (7, None),
         vec![("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))])],
-        vec![]
+        vec![("id", DataType::Uuid), ("name", DataType::Text(None))]
+    )]
+    // Case 2: Star over a join expands every inner table, in FROM order
+    #[case(
+        "SELECT  FROM (SELECT * FROM a JOIN b ON true) sub",
+        (7, None),
+        vec![("a", vec![("id", DataType::Uuid)]), ("b", vec![("bid", DataType::Uuid)])],
+        vec![("id", DataType::Uuid), ("bid", DataType::Uuid)]
+    )]
+    // Case 3: Qualified star next to a plain column
+    #[case(
+        "SELECT  FROM (SELECT y.*, x.id FROM a x, b y) sub",
+        (7, None),
+        vec![("a", vec![("id", DataType::Uuid)]), ("b", vec![("bid", DataType::Uuid)])],
+        vec![("bid", DataType::Uuid), ("id", DataType::Uuid)]
     )]
     #[tokio::test]
-    async fn should_document_gap_derived_star(
+    async fn should_expand_derived_star(
         #[case] sql: &str,
         #[case] (start, end): (usize, Option<usize>),
         #[case] tables: Vec<(&str, Vec<(&str, DataType)>)>,
@@ -352,25 +721,57 @@ mod column_testing {
             .expect("derived star");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
-        assert_eq!(
-            result, expected_columns,
-            "gap: star (*) in derived subquery not expanded into alias column list"
-        );
+        assert_eq!(result, expected_columns, "{sql}");
     }
 
-    // Derived subquery with column aliases: after rollback, derived alias columns unsupported -> expect empty.
+    // Derived subquery with column aliases: suggested under the alias with the base type
     #[rstest]
-    // Case 1: Derived subquery column aliases unsupported -> expect empty suggestions for qualified prefix
+    // Case 1: Every projected column aliased with AS
     #[case(
         "SELECT sub.  FROM (SELECT id AS ident, name AS nm FROM a) sub",
         (12, None),
         vec![("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))])],
-        vec![]
+        vec![("ident", DataType::Uuid), ("nm", DataType::Text(None))]
+    )]
+    // Case 2: Aliased and unaliased columns mixed, in projection order
+    #[case(
+        "SELECT sub.  FROM (SELECT id AS ident, name FROM a) sub",
+        (11, None),
+        vec![("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))])],
+        vec![("ident", DataType::Uuid), ("name", DataType::Text(None))]
+    )]
+    // Case 3: Qualified base columns with and without AS
+    #[case(
+        "SELECT  FROM (SELECT x.name, x.id ident, y.bid AS b FROM a x, b y) sub",
+        (7, None),
+        vec![
+            ("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))]),
+            ("b", vec![("bid", DataType::Integer(None))]),
+        ],
+        vec![
+            ("name", DataType::Text(None)),
+            ("ident", DataType::Uuid),
+            ("b", DataType::Integer(None)),
+        ]
+    )]
+    // Case 4: Aliased expressions have no base column and are skipped
+    #[case(
+        "SELECT sub.  FROM (SELECT count(*) AS n, id AS ident FROM a) sub",
+        (11, None),
+        vec![("a", vec![("id", DataType::Uuid)])],
+        vec![("ident", DataType::Uuid)]
+    )]
+    // Case 5: Case 1's query, cursor right after the dot
+    #[case(
+        "SELECT sub.  FROM (SELECT id AS ident, name AS nm FROM a) sub",
+        (11, None),
+        vec![("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))])],
+        vec![("ident", DataType::Uuid), ("nm", DataType::Text(None))]
     )]
     #[tokio::test]
-    async fn should_document_gap_derived_column_aliases(
+    async fn should_resolve_derived_column_aliases(
         #[case] sql: &str,
         #[case] (start, end): (usize, Option<usize>),
         #[case] tables: Vec<(&str, Vec<(&str, DataType)>)>,
@@ -379,27 +780,48 @@ mod column_testing {
         let meta = database("postgres", &tables).await;
         let result = Suggestion::search(sql, Cursor::new(start, end), meta)
             .await
-            .expect("derived column alias qualified");
+            .expect("derived column alias");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
-        assert_eq!(
-            result, expected_columns,
-            "rollback: derived column alias expansion unsupported; expecting empty suggestions"
-        );
+        assert_eq!(result, expected_columns, "{sql}");
     }
 
-    // CTE chain: y references x, neither exposed in suggestions (only base table 'a')
+    // CTE chain: y references x, both resolved to their projected columns
     #[rstest]
-    // Case 1: CTE chain not exposed, only base table columns suggested
+    // Case 1: Base table next to a CTE chain
     #[case(
         "WITH x AS (SELECT id FROM a), y AS (SELECT id FROM x) SELECT  FROM a", (61, None),
         vec![("a", vec![("id", DataType::Uuid)])],
         vec![("id", DataType::Uuid)]
     )]
+    // Case 2: Final SELECT over the chained CTE
+    #[case(
+        "WITH x AS (SELECT id, name FROM a), y AS (SELECT name FROM x) SELECT  FROM y", (69, None),
+        vec![("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))])],
+        vec![("name", DataType::Text(None))]
+    )]
+    // Case 3: Qualified through an alias of the chained CTE
+    #[case(
+        "WITH x AS (SELECT id, name FROM a), y AS (SELECT x.id, name FROM x) SELECT z. FROM y z", (77, None),
+        vec![("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))])],
+        vec![("id", DataType::Uuid), ("name", DataType::Text(None))]
+    )]
+    // Case 4: RECURSIVE, column list and MATERIALIZED are skipped
+    #[case(
+        "WITH RECURSIVE x (k) AS MATERIALIZED (SELECT id FROM a) SELECT  FROM x", (63, None),
+        vec![("a", vec![("id", DataType::Uuid)])],
+        vec![("id", DataType::Uuid)]
+    )]
+    // Case 5: A CTE of an earlier statement is not visible
+    #[case(
+        "WITH x AS (SELECT id FROM a) SELECT 1; SELECT  FROM x", (46, None),
+        vec![("a", vec![("id", DataType::Uuid)])],
+        vec![]
+    )]
     #[tokio::test]
-    async fn should_document_gap_cte_chain(
+    async fn should_resolve_cte_chain(
         #[case] sql: &str,
         #[case] (start, end): (usize, Option<usize>),
         #[case] tables: Vec<(&str, Vec<(&str, DataType)>)>,
@@ -411,12 +833,9 @@ mod column_testing {
             .expect("cte chain");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
-        assert_eq!(
-            result, expected_columns,
-            "gap: CTE chain columns not exposed; only underlying base tables available"
-        );
+        assert_eq!(result, expected_columns, "{sql}");
     }
 
     // Parenthesized join group alias: (a JOIN b ...) ab -> current behavior: alias 'ab' not resolved, a/b not captured at top depth
@@ -440,7 +859,7 @@ mod column_testing {
             .expect("parenthesized join group alias");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
         assert_eq!(
             result, expected_columns,
@@ -470,7 +889,7 @@ mod column_testing {
             .expect("intersect first");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
         assert_eq!(
             result, expected_columns,
@@ -502,7 +921,7 @@ mod column_testing {
             .expect("intersect second");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
         assert_eq!(
             result, expected_columns,
@@ -510,16 +929,16 @@ mod column_testing {
         );
     }
 
-    // Qualified derived star alias: (SELECT * FROM a) sub -> qualified 'sub.' returns no columns (star not expanded)
+    // Qualified derived star alias: (SELECT * FROM a) sub -> 'sub.' returns a's columns
     #[rstest]
-    // Case 1: Qualified derived star prefix unsupported -> expect empty suggestions
+    // Case 1: Qualified derived star prefix expands the inner table
     #[case(
         "SELECT sub.  FROM (SELECT * FROM a) sub", (12, None),
         vec![("a", vec![("id", DataType::Uuid), ("name", DataType::Text(None))])],
-        vec![] // expected empty
+        vec![("id", DataType::Uuid), ("name", DataType::Text(None))]
     )]
     #[tokio::test]
-    async fn should_document_gap_qualified_derived_star(
+    async fn should_expand_qualified_derived_star(
         #[case] sql: &str,
         #[case] (start, end): (usize, Option<usize>),
         #[case] tables: Vec<(&str, Vec<(&str, DataType)>)>,
@@ -531,12 +950,9 @@ mod column_testing {
             .expect("qualified derived star");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
-        assert_eq!(
-            result, expected_columns,
-            "gap: qualified derived star should expand underlying columns but currently yields none"
-        );
+        assert_eq!(result, expected_columns, "{sql}");
     }
 
     // Multi-schema duplicate table name aggregation (unqualified)
@@ -576,11 +992,11 @@ mod column_testing {
         // Build expected columns in actual output order: public schema first, then analytics schema.
         let mut expected_columns: Vec<Suggestion> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
         expected_columns.extend([
-            Suggestion::Column("user_id".into(), DataType::Uuid),
-            Suggestion::Column("created_at".into(), DataType::Text(None)),
+            Suggestion::column("user_id", DataType::Uuid),
+            Suggestion::column("created_at", DataType::Text(None)),
         ]);
         assert_eq!(
             result, expected_columns,
@@ -588,6 +1004,172 @@ mod column_testing {
         );
     }
 
+    #[tokio::test]
+    async fn should_order_multi_schema_columns_deterministically() {
+        let mut runs = Vec::new();
+        // Each run builds fresh maps, so each gets a different hash seed.
+        for _ in 0..32 {
+            let meta = database_multi_schema(
+                "postgres",
+                &[("users", vec![("id", DataType::Uuid)])],
+                "analytics",
+                &[("users", vec![("user_id", DataType::Uuid)])],
+            )
+            .await;
+            meta.insert_table(
+                "archive",
+                Table::new_with_ordered("users", [("old_id", DataType::Uuid)]),
+            )
+            .await;
+            meta.insert_table(
+                "zeta",
+                Table::new_with_ordered("users", [("z", DataType::Uuid)]),
+            )
+            .await;
+            let result = Suggestion::search("SELECT  FROM users", Cursor::new(7, None), meta)
+                .await
+                .unwrap();
+            runs.push(result.into_labels());
+        }
+        // `public` (the search path) first, then the other schemas by name.
+        assert_eq!(runs[0], ["id", "user_id", "old_id", "z"]);
+        assert!(runs.iter().all(|r| *r == runs[0]));
+    }
+
+    #[rstest]
+    // Case 1: Unqualified duplicate table, columns labelled with their schema
+    #[case("SELECT  FROM users", vec!["public.users.id", "public.users.email", "analytics.users.id"])]
+    // Case 2: Schema-qualified table is unambiguous
+    #[case("SELECT  FROM analytics.users", vec!["id"])]
+    // Case 3: Table in a single schema stays unlabelled
+    #[case("SELECT  FROM orders", vec!["total"])]
+    #[tokio::test]
+    async fn should_disambiguate_multi_schema_duplicate(
+        #[case] sql: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database_multi_schema(
+            "postgres",
+            &[
+                (
+                    "users",
+                    vec![("id", DataType::Uuid), ("email", DataType::Text(None))],
+                ),
+                ("orders", vec![("total", DataType::Integer(None))]),
+            ],
+            "analytics",
+            &[("users", vec![("id", DataType::Uuid)])],
+        )
+        .await;
+        let options = SearchOptions::default().with_disambiguate_schemas(true);
+        let result = Suggestion::search_with(sql, Cursor::new(7, None), meta, &options)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+
+    #[test]
+    fn should_render_schema_columns() {
+        let column = Suggestion::SchemaColumn {
+            schema: "analytics".into(),
+            table: "order".into(),
+            column: "id".into(),
+            data_type: DataType::Uuid,
+            flags: ColumnFlags {
+                primary_key: true,
+                ..ColumnFlags::default()
+            },
+        };
+        assert_eq!(column.to_string(), "analytics.order.id::Uuid");
+        assert_eq!(column.insert_text(), "analytics.\"order\".id");
+        assert_eq!(column.detail().as_deref(), Some("uuid PK"));
+        assert_eq!(column.kind(), SuggestionKind::Column);
+    }
+
+    // Introspection may register schemas without tables; they must not
+    // disturb lookups or listings, even first on the search path.
+    #[tokio::test]
+    async fn should_ignore_empty_schemas() {
+        let meta = database("postgres", &[("users", vec![("id", DataType::Uuid)])]).await;
+        meta.insert_schema(Schema::new("empty")).await;
+        let meta = meta.with_search_path(["empty", "public"]);
+
+        let result = Suggestion::search("SELECT  FROM users", Cursor::new(7, None), &meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), ["id"]);
+        let result = Suggestion::search("SELECT  FROM empty.users", Cursor::new(7, None), &meta)
+            .await
+            .unwrap();
+        assert!(result.is_empty(), "{result:?}");
+
+        let result = Suggestion::search("SELECT * FROM ", Cursor::new(14, None), &meta)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            [
+                Suggestion::Schema("empty".into()),
+                Suggestion::Schema("public".into()),
+                Suggestion::Table {
+                    schema: "public".into(),
+                    name: "users".into()
+                },
+            ]
+        );
+        let result = Suggestion::search("SELECT * FROM empty.", Cursor::new(20, None), &meta)
+            .await
+            .unwrap();
+        assert!(result.is_empty(), "{result:?}");
+    }
+
+    // `public` is on the default search path, so `public.users` and a bare
+    // `users` name the same table.
+    #[tokio::test]
+    async fn should_treat_public_schema_as_implicit() {
+        let columns = vec![("id", DataType::Uuid), ("email", DataType::Text(None))];
+        let meta = database("postgres", &[("users", columns)]).await;
+        let expected = ["id", "email"];
+
+        for sql in [
+            "SELECT  FROM users",
+            "SELECT  FROM public.users",
+            "SELECT  FROM users, public.users",
+            "SELECT  FROM public.users p JOIN users u ON true",
+        ] {
+            let result = Suggestion::search(sql, Cursor::new(7, None), &meta)
+                .await
+                .unwrap();
+            assert_eq!(result.into_labels(), expected, "{sql}");
+        }
+        let result =
+            Suggestion::search("SELECT p. FROM public.users p", Cursor::new(9, None), &meta)
+                .await
+                .unwrap();
+        assert_eq!(result.into_labels(), expected);
+    }
+
+    // A qualified reference only sees its own schema's table.
+    #[tokio::test]
+    async fn should_resolve_schema_qualified_table_in_its_schema() {
+        let meta = database_multi_schema(
+            "postgres",
+            &[("users", vec![("id", DataType::Uuid)])],
+            "analytics",
+            &[("users", vec![("user_id", DataType::Uuid)])],
+        )
+        .await;
+        let result =
+            Suggestion::search("SELECT  FROM analytics.users", Cursor::new(7, None), &meta)
+                .await
+                .unwrap();
+        assert_eq!(result.into_labels(), ["user_id"]);
+        let result = Suggestion::search("SELECT  FROM public.users", Cursor::new(7, None), &meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), ["id"]);
+    }
+
     // Alias shadowing: table named 'fake' and alias 'fake' for 'real' -> qualified fake. should resolve to alias target (real) columns first
     #[rstest]
     // Case 1: Alias shadowing a real table name resolves to aliased underlying table
@@ -615,7 +1197,7 @@ mod column_testing {
 
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
         assert_eq!(
             result, expected_columns,
@@ -662,7 +1244,7 @@ mod column_testing {
             .expect("suggestion shouldnt error");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(name, data_type)| Suggestion::Column(name.to_string(), data_type))
+            .map(|(name, data_type)| Suggestion::column(name.to_string(), data_type))
             .collect();
         assert_eq!(
             result, expected_columns,
@@ -674,7 +1256,7 @@ mod column_testing {
     // Case 1: Qualified UNION second SELECT scope suggestions for table b
     #[case(
         "SELECT aid FROM a UNION SELECT b.  FROM b",
-        (29, None),
+        (33, None),
         vec![
             ("a", vec![("aid", DataType::Uuid)]),
             ("b", vec![("bid", DataType::Uuid), ("bname", DataType::Text(None))])
@@ -698,7 +1280,7 @@ mod column_testing {
         // Then: expect only columns from table b
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
         assert_eq!(
             result, expected_columns,
@@ -774,6 +1356,15 @@ mod column_testing {
         ],
         vec![("id", DataType::Uuid), ("email", DataType::Text(None))]
     )]
+    // Case 7: A number's dot never qualifies: `1.` is not a table reference
+    #[case(
+        "SELECT 1.  FROM users u",
+        (9, None),
+        vec![
+            ("users", vec![("id", DataType::Uuid), ("email", DataType::Text(None))])
+        ],
+        vec![("id", DataType::Uuid), ("email", DataType::Text(None))]
+    )]
     #[tokio::test]
     async fn edge_cases_additional(
         #[case] sql: &str,
@@ -788,7 +1379,7 @@ mod column_testing {
 
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
 
         assert_eq!(result, expected_columns, "edge case mismatch");
@@ -842,11 +1433,1920 @@ mod column_testing {
             .expect("postgres grammar edge cases");
         let expected_columns: Vec<_> = expected
             .into_iter()
-            .map(|(n, dt)| Suggestion::Column(n.to_string(), dt))
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
             .collect();
         assert_eq!(
             result, expected_columns,
             "postgres grammar edge case mismatch"
         );
     }
+
+    // Table reference positions suggest schema names (distinct `Suggestion::Schema`)
+    // followed by table names, both filtered by the partially typed identifier.
+    #[rstest]
+    // Case 1: Directly after FROM -> every schema, then every table
+    #[case(
+        "SELECT * FROM ",
+        (14, None),
+        vec![
+            Suggestion::Schema("analytics".into()),
+            Suggestion::Schema("public".into()),
+            Suggestion::Table { schema: "analytics".into(), name: "events".into() },
+            Suggestion::Table { schema: "public".into(), name: "accounts".into() },
+            Suggestion::Table { schema: "public".into(), name: "users".into() },
+        ]
+    )]
+    // Case 2: Partial prefix filters schemas and tables case-insensitively
+    #[case(
+        "SELECT * FROM A",
+        (15, None),
+        vec![
+            Suggestion::Schema("analytics".into()),
+            Suggestion::Table { schema: "public".into(), name: "accounts".into() },
+        ]
+    )]
+    // Case 3: Schema qualifier restricts to that schema's tables (no schemas)
+    #[case(
+        "SELECT * FROM analytics.",
+        (24, None),
+        vec![Suggestion::Table { schema: "analytics".into(), name: "events".into() }]
+    )]
+    // Case 4: After JOIN
+    #[case(
+        "SELECT * FROM users u JOIN pu",
+        (29, None),
+        vec![Suggestion::Schema("public".into())]
+    )]
+    // Case 5: After a comma inside the FROM list
+    #[case(
+        "SELECT * FROM users, ev",
+        (23, None),
+        vec![Suggestion::Table { schema: "analytics".into(), name: "events".into() }]
+    )]
+    // Case 6: A comma in the projection is not a table position
+    #[case(
+        "SELECT id,  FROM users",
+        (11, None),
+        vec![Suggestion::column("id", DataType::Uuid)]
+    )]
+    #[tokio::test]
+    async fn should_recommend_schemas_and_tables(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<Suggestion>,
+    ) {
+        let meta = database_multi_schema(
+            "postgres",
+            &[
+                ("users", vec![("id", DataType::Uuid)]),
+                ("accounts", vec![("id", DataType::Uuid)]),
+            ],
+            "analytics",
+            &[("events", vec![("id", DataType::Uuid)])],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("table position");
+        assert_eq!(
+            result, expected,
+            "table positions should suggest schemas then tables"
+        );
+    }
+
+    // FROM with an unaliased subquery (invalid but common mid-edit): the outer
+    // projection gets no suggestions and inner tokens never become phantom tables.
+    #[rstest]
+    // Case 1: Outer projection yields nothing even though `a` and `id` exist as tables
+    #[case("SELECT  FROM (SELECT id FROM a)", (7, None), vec![])]
+    // Case 2: Trailing WHERE after the unaliased subquery
+    #[case("SELECT  FROM (SELECT id FROM a) WHERE 1 = 1", (7, None), vec![])]
+    // Case 3: Cursor inside the unaliased subquery still resolves its own scope
+    #[case(
+        "SELECT  FROM (SELECT  FROM a)",
+        (21, None),
+        vec![("id", DataType::Uuid), ("name", DataType::Text(None))]
+    )]
+    #[tokio::test]
+    async fn should_ignore_unaliased_from_subquery(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<(&str, DataType)>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("id", DataType::Uuid), ("name", DataType::Text(None))],
+                ),
+                ("id", vec![("phantom", DataType::Uuid)]),
+                ("select", vec![("phantom", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("unaliased subquery");
+        let expected_columns: Vec<_> = expected
+            .into_iter()
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
+            .collect();
+        assert_eq!(
+            result, expected_columns,
+            "unaliased FROM subquery must not produce phantom tables"
+        );
+    }
+
+    // Enum columns compared to an opened string literal suggest their variants.
+    #[rstest]
+    // Case 1: Unqualified enum column after `= '`
+    #[case(
+        "SELECT * FROM orders WHERE status = '",
+        (37, None),
+        vec![
+            Suggestion::EnumValue("pending".into()),
+            Suggestion::EnumValue("shipped".into()),
+            Suggestion::EnumValue("cancelled".into()),
+        ]
+    )]
+    // Case 2: Qualified through an alias, filtered by the typed prefix
+    #[case(
+        "SELECT * FROM orders o WHERE o.status = 'sh",
+        (43, None),
+        vec![Suggestion::EnumValue("shipped".into())]
+    )]
+    // Case 3: `<>` comparison
+    #[case(
+        "SELECT * FROM orders WHERE status <> '",
+        (38, None),
+        vec![
+            Suggestion::EnumValue("pending".into()),
+            Suggestion::EnumValue("shipped".into()),
+            Suggestion::EnumValue("cancelled".into()),
+        ]
+    )]
+    // Case 4: Non-enum column inside a literal yields nothing (no columns either)
+    #[case("SELECT * FROM orders WHERE note = '", (35, None), vec![])]
+    // Case 5: After a closed literal, regular column completion resumes
+    #[case(
+        "SELECT * FROM orders WHERE status = 'pending' AND ",
+        (50, None),
+        vec![
+            Suggestion::column("status", DataType::Enum(vec!["pending".into(), "shipped".into(), "cancelled".into()])),
+            Suggestion::column("note", DataType::Text(None)),
+        ]
+    )]
+    // Case 6: Cursor inside a closed literal completes the text before it
+    #[case("SELECT * FROM orders WHERE status = 'shx'", (39, None), vec![Suggestion::EnumValue("shipped".into())])]
+    #[tokio::test]
+    async fn should_recommend_enum_values(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<Suggestion>,
+    ) {
+        let status = DataType::Enum(vec!["pending".into(), "shipped".into(), "cancelled".into()]);
+        let meta = database(
+            "postgres",
+            &[(
+                "orders",
+                vec![("status", status), ("note", DataType::Text(None))],
+            )],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("enum values");
+        assert_eq!(result, expected, "enum value suggestions mismatch");
+    }
+
+    // Enum values come from the table in the schema written in the query
+    #[rstest]
+    // Case 1: Schema-qualified table, aliased column
+    #[case("SELECT * FROM app.users u WHERE u.status = '", vec!["app_a"])]
+    // Case 2: Schema-qualified table, unqualified column
+    #[case("SELECT * FROM app.users WHERE status = '", vec!["app_a"])]
+    // Case 3: Unqualified table resolves through the search path
+    #[case("SELECT * FROM users u WHERE u.status = '", vec!["pub_a"])]
+    #[tokio::test]
+    async fn should_recommend_enum_values_from_written_schema(
+        #[case] sql: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database_multi_schema(
+            "postgres",
+            &[(
+                "users",
+                vec![("status", DataType::Enum(vec!["pub_a".into()]))],
+            )],
+            "app",
+            &[(
+                "users",
+                vec![("status", DataType::Enum(vec!["app_a".into()]))],
+            )],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(sql.len(), None), meta)
+            .await
+            .expect("schema enum values");
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+
+    // Comma-joined derived tables: the base table and the derived table's projected
+    // columns are offered, while the subquery's own tables stay out of the outer scope.
+    #[rstest]
+    // Case 1: Unqualified completion lists `a` then `sub`'s projected columns in order
+    #[case(
+        "SELECT  FROM a, (SELECT id FROM b) sub",
+        (7, None),
+        vec![("aid", DataType::Uuid), ("aval", DataType::Text(None)), ("id", DataType::Uuid)]
+    )]
+    // Case 2: Qualified `sub.` resolves to the derived projection (types from `b`)
+    #[case(
+        "SELECT sub.  FROM a, (SELECT b.id, bval FROM b) AS sub",
+        (12, None),
+        vec![("id", DataType::Uuid), ("bval", DataType::Integer(None))]
+    )]
+    // Case 3: Derived table listed first, base table after it
+    #[case(
+        "SELECT  FROM (SELECT bval FROM b) sub, a",
+        (7, None),
+        vec![("bval", DataType::Integer(None)), ("aid", DataType::Uuid), ("aval", DataType::Text(None))]
+    )]
+    #[tokio::test]
+    async fn should_recommend_comma_joined_derived_table_columns(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<(&str, DataType)>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("aid", DataType::Uuid), ("aval", DataType::Text(None))],
+                ),
+                (
+                    "b",
+                    vec![("id", DataType::Uuid), ("bval", DataType::Integer(None))],
+                ),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("comma joined derived table");
+        let expected_columns: Vec<_> = expected
+            .into_iter()
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
+            .collect();
+        assert_eq!(
+            result, expected_columns,
+            "derived table columns should follow FROM order without leaking inner tables"
+        );
+    }
+
+    // JOIN-attached derived tables (`JOIN (SELECT ...) AS sub ON ...`): `sub.`
+    // resolves to the subquery's projection in the projection and in the ON
+    // clause, while the subquery's own tables never reach the outer scope.
+    #[rstest]
+    // Case 1: Qualified `sub.` in the projection
+    #[case(
+        "SELECT sub.  FROM a JOIN (SELECT id, bval FROM b) AS sub ON a.aid = sub.id",
+        (12, None),
+        vec![("id", DataType::Uuid), ("bval", DataType::Integer(None))]
+    )]
+    // Case 2: Unqualified projection sees `a` and `sub`, not `b`'s other columns
+    #[case(
+        "SELECT  FROM a JOIN (SELECT id FROM b) AS sub ON a.aid = sub.id",
+        (7, None),
+        vec![("aid", DataType::Uuid), ("aval", DataType::Text(None)), ("id", DataType::Uuid)]
+    )]
+    // Case 3: Qualified `sub.` inside the ON clause
+    #[case(
+        "SELECT * FROM a JOIN (SELECT id FROM b) AS sub ON sub.",
+        (54, None),
+        vec![("id", DataType::Uuid)]
+    )]
+    // Case 4: Qualified `a.` inside the ON clause
+    #[case(
+        "SELECT * FROM a JOIN (SELECT id FROM b) AS sub ON a.",
+        (52, None),
+        vec![("aid", DataType::Uuid), ("aval", DataType::Text(None))]
+    )]
+    // Case 5: Unqualified ON clause sees both sides of the join
+    #[case(
+        "SELECT * FROM a JOIN (SELECT id FROM b) sub ON ",
+        (47, None),
+        vec![("aid", DataType::Uuid), ("aval", DataType::Text(None)), ("id", DataType::Uuid)]
+    )]
+    #[tokio::test]
+    async fn should_recommend_join_attached_derived_table_columns(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<(&str, DataType)>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("aid", DataType::Uuid), ("aval", DataType::Text(None))],
+                ),
+                (
+                    "b",
+                    vec![("id", DataType::Uuid), ("bval", DataType::Integer(None))],
+                ),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("join attached derived table");
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
+            .collect();
+        assert_eq!(
+            result, expected,
+            "join attached derived table columns mismatch"
+        );
+    }
+
+    // Join conditions only see the tables joined so far: the accumulated left
+    // side plus the table joined by that specific JOIN.
+    #[rstest]
+    // Case 1: First ON of a three-table join excludes `c`
+    #[case(
+        "SELECT * FROM a JOIN b ON  JOIN c ON ",
+        (26, None),
+        vec![("aid", DataType::Uuid), ("bid", DataType::Uuid)]
+    )]
+    // Case 2: Second ON sees all three tables
+    #[case(
+        "SELECT * FROM a JOIN b ON aid = bid JOIN c ON ",
+        (46, None),
+        vec![("aid", DataType::Uuid), ("bid", DataType::Uuid), ("cid", DataType::Uuid)]
+    )]
+    // Case 3: The projection sees tables joined after an ON condition
+    #[case(
+        "SELECT  FROM a JOIN b ON a.aid = b.bid JOIN c ON c.cid = b.bid",
+        (7, None),
+        vec![("aid", DataType::Uuid), ("bid", DataType::Uuid), ("cid", DataType::Uuid)]
+    )]
+    // Case 4: A qualifier for a later join is not in scope yet and falls back to
+    // a plain table lookup
+    #[case(
+        "SELECT * FROM a JOIN b ON c. JOIN c ON ",
+        (28, None),
+        vec![("cid", DataType::Uuid)]
+    )]
+    // Case 5: An alias declared by the join resolves to its table, even when it
+    // shadows another table's name
+    #[case(
+        "SELECT * FROM b AS a JOIN c AS b ON b.",
+        (38, None),
+        vec![("cid", DataType::Uuid)]
+    )]
+    #[tokio::test]
+    async fn should_scope_on_clause_to_joined_tables(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<(&str, DataType)>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                ("a", vec![("aid", DataType::Uuid)]),
+                ("b", vec![("bid", DataType::Uuid)]),
+                ("c", vec![("cid", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("on clause");
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
+            .collect();
+        assert_eq!(result, expected, "ON clause should only see joined tables");
+    }
+
+    // INSERT ... VALUES: a hint naming the column/type for the current position,
+    // followed by the DEFAULT and NULL literals.
+    #[rstest]
+    // Case 1: Second value slot of an explicit column list
+    #[case(
+        "INSERT INTO users (id, email) VALUES (gen_random_uuid(), ",
+        (57, None),
+        vec![
+            Suggestion::Hint { column: "email".into(), data_type: DataType::Text(None) },
+            Suggestion::Keyword { keyword: "DEFAULT".into(), replace: (57, 57) },
+            Suggestion::Keyword { keyword: "NULL".into(), replace: (57, 57) },
+        ]
+    )]
+    // Case 2: Column list order differs from table order
+    #[case(
+        "INSERT INTO users (email, id) VALUES ('a', ",
+        (43, None),
+        vec![
+            Suggestion::Hint { column: "id".into(), data_type: DataType::Uuid },
+            Suggestion::Keyword { keyword: "DEFAULT".into(), replace: (43, 43) },
+            Suggestion::Keyword { keyword: "NULL".into(), replace: (43, 43) },
+        ]
+    )]
+    // Case 3: No column list falls back to table column order
+    #[case(
+        "INSERT INTO public.users VALUES (",
+        (33, None),
+        vec![
+            Suggestion::Hint { column: "id".into(), data_type: DataType::Uuid },
+            Suggestion::Keyword { keyword: "DEFAULT".into(), replace: (33, 33) },
+            Suggestion::Keyword { keyword: "NULL".into(), replace: (33, 33) },
+        ]
+    )]
+    // Case 4: More values than columns yields nothing
+    #[case("INSERT INTO users (id) VALUES (1, ", (34, None), vec![])]
+    // Case 5: A typed prefix filters the literals
+    #[case(
+        "INSERT INTO users (id) VALUES (nu",
+        (33, None),
+        vec![
+            Suggestion::Hint { column: "id".into(), data_type: DataType::Uuid },
+            Suggestion::Keyword { keyword: "NULL".into(), replace: (31, 33) },
+        ]
+    )]
+    #[tokio::test]
+    async fn should_hint_insert_values_position(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<Suggestion>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[(
+                "users",
+                vec![("id", DataType::Uuid), ("email", DataType::Text(None))],
+            )],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("insert values");
+        assert_eq!(result, expected, "VALUES hint mismatch");
+    }
+
+    // Degenerate buffers editors send for a fresh document: a statement
+    // starts at the cursor.
+    #[rstest]
+    // Case 1: Empty SQL
+    #[case("", 0)]
+    // Case 2: Empty SQL with a cursor past the end
+    #[case("", 3)]
+    // Case 3: Whitespace-only SQL
+    #[case("  \n\t ", 3)]
+    // Case 4: Cursor at position 0 of a complete query
+    #[case("SELECT  FROM a", 0)]
+    // Case 5: Cursor in leading whitespace
+    #[case("   SELECT  FROM a", 2)]
+    #[tokio::test]
+    async fn should_offer_statement_keywords_for_empty_input(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+    ) {
+        let meta = database("postgres", &[("a", vec![("id", DataType::Uuid)])]).await;
+        let result = Suggestion::search(sql, Cursor::at(cursor), meta)
+            .await
+            .expect("empty input");
+        let at = sql.floor_char_boundary(cursor);
+        let expected: Vec<_> = ["SELECT", "INSERT", "UPDATE", "WITH"]
+            .into_iter()
+            .map(|k| Suggestion::Keyword {
+                keyword: k.into(),
+                replace: (at, at),
+            })
+            .collect();
+        assert_eq!(result, expected);
+    }
+
+    // GROUP BY: columns in scope, then projection aliases, then ordinals.
+    #[rstest]
+    // Case 1: Aliased projection offers `k` and both ordinals
+    #[case(
+        "SELECT id AS k, count(*) FROM a GROUP BY ",
+        (41, None),
+        vec![
+            Suggestion::column("id", DataType::Uuid),
+            Suggestion::column("name", DataType::Text(None)),
+            Suggestion::Alias("k".into()),
+            Suggestion::Ordinal(1),
+            Suggestion::Ordinal(2),
+        ]
+    )]
+    // Case 2: After a first grouping item
+    #[case(
+        "SELECT id, name n FROM a GROUP BY id, ",
+        (38, None),
+        vec![
+            Suggestion::column("id", DataType::Uuid),
+            Suggestion::column("name", DataType::Text(None)),
+            Suggestion::Alias("n".into()),
+            Suggestion::Ordinal(1),
+            Suggestion::Ordinal(2),
+        ]
+    )]
+    // Case 3: ORDER BY after GROUP BY leaves the GROUP BY list
+    #[case(
+        "SELECT id AS k FROM a GROUP BY 1 ORDER BY ",
+        (42, None),
+        vec![
+            Suggestion::column("id", DataType::Uuid),
+            Suggestion::column("name", DataType::Text(None)),
+        ]
+    )]
+    // Case 4: DISTINCT is not mistaken for an expression aliased `name`
+    #[case(
+        "SELECT DISTINCT name FROM a GROUP BY ",
+        (37, None),
+        vec![
+            Suggestion::column("id", DataType::Uuid),
+            Suggestion::column("name", DataType::Text(None)),
+            Suggestion::Ordinal(1),
+        ]
+    )]
+    // Case 5: DISTINCT ON (...) before an aliased item
+    #[case(
+        "SELECT DISTINCT ON (id) name n FROM a GROUP BY ",
+        (47, None),
+        vec![
+            Suggestion::column("id", DataType::Uuid),
+            Suggestion::column("name", DataType::Text(None)),
+            Suggestion::Alias("n".into()),
+            Suggestion::Ordinal(1),
+        ]
+    )]
+    // Case 6: HAVING ends the GROUP BY list
+    #[case(
+        "SELECT id AS k FROM a GROUP BY id HAVING ",
+        (41, None),
+        vec![
+            Suggestion::column("id", DataType::Uuid),
+            Suggestion::column("name", DataType::Text(None)),
+        ]
+    )]
+    #[tokio::test]
+    async fn should_recommend_group_by_aliases_and_ordinals(
+        #[case] sql: &str,
+        #[case] (start, end): (usize, Option<usize>),
+        #[case] expected: Vec<Suggestion>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[(
+                "a",
+                vec![("id", DataType::Uuid), ("name", DataType::Text(None))],
+            )],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(start, end), meta)
+            .await
+            .expect("group by");
+        assert_eq!(result, expected, "GROUP BY suggestions mismatch");
+    }
+
+    #[rstest]
+    // Case 1: Cursor inside an identifier completes the text before the cursor
+    #[case("SELECT name FROM users", 9, Some("na"))]
+    // Case 2: Cursor right after an identifier completes the whole identifier
+    #[case("SELECT name FROM users", 11, Some("name"))]
+    // Case 3: Cursor after the following space has no partial
+    #[case("SELECT name FROM users", 12, None)]
+    // Case 4: Cursor right after a non-identifier token has no partial
+    #[case("SELECT * FROM users,", 20, None)]
+    // Case 5: Cursor inside a keyword still treats it as the word being typed
+    #[case("SELECT * FROM users", 11, Some("FR"))]
+    fn should_split_partial_at_token_end(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Option<&str>,
+    ) {
+        let tokens = crate::sql::tokenize(sql);
+        let (_, partial) = Suggestion::split_partial(sql, &tokens, cursor);
+        assert_eq!(partial, expected);
+    }
+    #[rstest]
+    // Case 1: Subquery alias inside EXISTS
+    #[case("SELECT * FROM a x WHERE EXISTS (SELECT 1 FROM sub s WHERE s.", false, vec![("v", DataType::Uuid), ("w", DataType::Text(None))])]
+    // Case 2: Outer alias inside EXISTS without correlation does not leak
+    #[case("SELECT * FROM a x WHERE EXISTS (SELECT 1 FROM sub s WHERE s.v = x.", false, vec![])]
+    // Case 3: Correlated outer alias inside EXISTS
+    #[case("SELECT * FROM a x WHERE EXISTS (SELECT 1 FROM sub s WHERE s.v = x.", true, vec![("id", DataType::Uuid)])]
+    // Case 4: Correlated outer alias two subqueries deep
+    #[case("SELECT * FROM a x WHERE x.id IN (SELECT v FROM sub WHERE EXISTS (SELECT 1 FROM sub t WHERE t.v = x.", true, vec![("id", DataType::Uuid)])]
+    // Case 5: Local aliases shadow outer ones
+    #[case("SELECT * FROM a s WHERE EXISTS (SELECT 1 FROM sub s WHERE s.", true, vec![("v", DataType::Uuid), ("w", DataType::Text(None))])]
+    #[tokio::test]
+    async fn should_resolve_qualifiers_inside_exists_subqueries(
+        #[case] sql: &str,
+        #[case] correlation: bool,
+        #[case] expected: Vec<(&str, DataType)>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                ("a", vec![("id", DataType::Uuid)]),
+                (
+                    "sub",
+                    vec![("v", DataType::Uuid), ("w", DataType::Text(None))],
+                ),
+            ],
+        )
+        .await;
+        let options = SearchOptions::default().with_correlation(correlation);
+        let result = Suggestion::search_with(sql, Cursor::new(sql.len(), None), meta, &options)
+            .await
+            .unwrap();
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(n, dt)| Suggestion::column(n.to_string(), dt))
+            .collect();
+        assert_eq!(result, expected, "{sql}");
+    }
+}
+
+mod labels {
+    use super::*;
+
+    #[test]
+    fn should_convert_to_labels_and_display() {
+        let suggestions: Suggestions = vec![
+            Suggestion::Keyword {
+                keyword: "SELECT".into(),
+                replace: (0, 3),
+            },
+            Suggestion::column("id", DataType::Uuid),
+            Suggestion::Table {
+                schema: "public".into(),
+                name: "users".into(),
+            },
+            Suggestion::Schema("analytics".into()),
+            Suggestion::EnumValue("active".into()),
+            Suggestion::Alias("k".into()),
+            Suggestion::Ordinal(2),
+            Suggestion::Hint {
+                column: "email".into(),
+                data_type: DataType::Uuid,
+            },
+            Suggestion::Hint {
+                column: "bio".into(),
+                data_type: DataType::Text(None),
+            },
+            Suggestion::Hint {
+                column: "name".into(),
+                data_type: DataType::VarChar(Some(64)),
+            },
+        ];
+        assert_eq!(
+            suggestions.clone().into_labels(),
+            [
+                "SELECT",
+                "id",
+                "users",
+                "analytics",
+                "active",
+                "k",
+                "2",
+                "email",
+                "bio",
+                "name"
+            ]
+        );
+        assert_eq!(
+            suggestions.into_display(),
+            [
+                "SELECT",
+                "id::Uuid",
+                "public.users",
+                "analytics",
+                "active",
+                "k",
+                "2",
+                "-- email :: uuid",
+                "-- bio :: text",
+                "-- name :: character varying(64)"
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn should_quote_insert_text_when_needed() {
+        let meta = database(
+            "postgres",
+            &[(
+                "t",
+                vec![
+                    ("id", DataType::Uuid),
+                    ("order", DataType::Int(None)),
+                    ("my col", DataType::Text(None)),
+                ],
+            )],
+        )
+        .await;
+        let result = Suggestion::search("SELECT  FROM t", Cursor::new(7, None), meta)
+            .await
+            .unwrap();
+        let texts: Vec<_> = result
+            .iter()
+            .map(|s| s.insert_text().into_owned())
+            .collect();
+        assert_eq!(texts, ["id", "\"order\"", "\"my col\""]);
+        assert_eq!(result.into_labels(), ["id", "order", "my col"]);
+
+        assert_eq!(
+            Suggestion::Table {
+                schema: "public".into(),
+                name: "select".into()
+            }
+            .insert_text(),
+            "\"select\""
+        );
+        assert_eq!(Suggestion::EnumValue("it's".into()).insert_text(), "it''s");
+        assert_eq!(
+            Suggestion::Keyword {
+                keyword: "ORDER".into(),
+                replace: (0, 2)
+            }
+            .insert_text(),
+            "ORDER"
+        );
+        assert_eq!(Suggestion::Ordinal(2).insert_text(), "2");
+    }
+
+    #[tokio::test]
+    async fn should_flag_deprecated_columns() {
+        let meta = Database::new("postgres");
+        let comment = "DEPRECATED: use email";
+        let legacy = Column::new("login", DataType::Text(None))
+            .with_deprecated(Column::is_deprecated_comment(comment))
+            .with_comment(comment);
+        meta.insert_table(
+            "public",
+            Table::new_from_columns(
+                "users",
+                [Column::new("email", DataType::Text(None)), legacy],
+            ),
+        )
+        .await;
+
+        let result = Suggestion::search("SELECT  FROM users", Cursor::new(7, None), meta)
+            .await
+            .unwrap();
+        let flags: Vec<_> = result
+            .iter()
+            .map(|s| (s.label().to_string(), s.is_deprecated()))
+            .collect();
+        assert_eq!(
+            flags,
+            [("email".to_string(), false), ("login".to_string(), true)]
+        );
+        assert_eq!(
+            result[1],
+            Suggestion::Column(
+                "login".into(),
+                DataType::Text(None),
+                ColumnFlags {
+                    deprecated: true,
+                    ..ColumnFlags::default()
+                }
+            )
+        );
+    }
+
+    #[tokio::test]
+    async fn should_filter_mixed_results_by_kind() {
+        let meta = database(
+            "postgres",
+            &[(
+                "a",
+                vec![("id", DataType::Uuid), ("grp", DataType::Text(None))],
+            )],
+        )
+        .await;
+        let sql = "SELECT grp AS g, count(*) FROM a GROUP BY i";
+        let result = Suggestion::search(sql, Cursor::new(sql.len(), None), meta)
+            .await
+            .unwrap();
+        let kinds: Vec<_> = result.iter().map(Suggestion::kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SuggestionKind::Column,
+                SuggestionKind::Column,
+                SuggestionKind::Alias,
+                SuggestionKind::Ordinal,
+                SuggestionKind::Ordinal,
+                SuggestionKind::Keyword,
+                SuggestionKind::Keyword,
+                SuggestionKind::Keyword,
+                SuggestionKind::Keyword,
+                SuggestionKind::Keyword,
+            ]
+        );
+
+        let columns = result.clone().filter_kind(SuggestionKind::Column);
+        assert_eq!(
+            columns,
+            [
+                Suggestion::column("id", DataType::Uuid),
+                Suggestion::column("grp", DataType::Text(None)),
+            ]
+        );
+        assert!(result.filter_kind(SuggestionKind::Table).is_empty());
+    }
+}
+
+// Keywords completing the typed word carry the span of that word.
+mod keywords {
+    use super::*;
+
+    fn keyword(keyword: &str, replace: (usize, usize)) -> Suggestion {
+        Suggestion::Keyword {
+            keyword: keyword.into(),
+            replace,
+        }
+    }
+
+    #[tokio::test]
+    async fn should_complete_keyword_at_statement_start() {
+        let meta = database("postgres", &[]).await;
+        let result = Suggestion::search("SEL", Cursor::new(3, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result, [keyword("SELECT", (0, 3))]);
+    }
+
+    #[tokio::test]
+    async fn should_append_keywords_after_columns() {
+        let meta = database("postgres", &[("a", vec![("id", DataType::Uuid)])]).await;
+        let sql = "SELECT id, fro FROM a";
+        let result = Suggestion::search(sql, Cursor::new(14, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            [
+                Suggestion::column("id", DataType::Uuid),
+                keyword("FROM", (11, 14))
+            ]
+        );
+    }
+
+    #[rstest]
+    // Case 1: After GROUP
+    #[case("SELECT * FROM a GROUP ", 22, vec![keyword("BY", (22, 22))])]
+    // Case 2: After ORDER
+    #[case("SELECT * FROM a ORDER ", 22, vec![keyword("BY", (22, 22))])]
+    // Case 3: Typing BY, case-insensitively
+    #[case("SELECT * FROM a order b", 23, vec![keyword("BY", (22, 23))])]
+    // Case 4: A word that cannot start BY
+    #[case("SELECT * FROM a GROUP x", 23, vec![])]
+    // Case 5: Inside a window definition
+    #[case("SELECT rank() OVER (ORDER  FROM a", 26, vec![keyword("BY", (26, 26))])]
+    #[tokio::test]
+    async fn should_suggest_by_after_group_and_order(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<Suggestion>,
+    ) {
+        let meta = database("postgres", &[("a", vec![("id", DataType::Uuid)])]).await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result, expected);
+    }
+
+    #[rstest]
+    // Case 1: After the FROM list
+    #[case("SELECT * FROM a ", 16, true)]
+    // Case 2: After a WHERE condition
+    #[case("SELECT * FROM a WHERE id = 1 ", 29, true)]
+    // Case 3: After a closed subquery in a nested select's outer query
+    #[case("SELECT * FROM a WHERE id IN (SELECT id FROM b) ", 47, true)]
+    // Case 4: Inside a subquery, at its own depth
+    #[case("SELECT * FROM a WHERE id IN (SELECT id FROM b )", 46, true)]
+    // Case 5: Operand still expected
+    #[case("SELECT * FROM a WHERE ", 22, false)]
+    #[case("SELECT * FROM a WHERE id = ", 27, false)]
+    #[case("SELECT * FROM a WHERE id = 1 AND ", 33, false)]
+    #[case("SELECT * FROM a GROUP BY ", 25, false)]
+    // Case 6: Before FROM
+    #[case("SELECT id  FROM a", 10, false)]
+    // Case 7: While typing a word
+    #[case("SELECT * FROM a WHERE id = x", 28, false)]
+    #[tokio::test]
+    async fn should_offer_set_operations_after_complete_select(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] offered: bool,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                ("a", vec![("id", DataType::Uuid)]),
+                ("b", vec![("id", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        let keywords = result.filter_kind(SuggestionKind::Keyword);
+        let expected: Vec<_> = match offered {
+            true => ["UNION", "UNION ALL", "EXCEPT", "INTERSECT"]
+                .into_iter()
+                .map(|k| keyword(k, (cursor, cursor)))
+                .collect(),
+            false => vec![],
+        };
+        assert_eq!(keywords, expected);
+    }
+
+    #[rstest]
+    // Case 1: Cursor mid-word replaces the whole token
+    #[case("SELECT name FROM t", 9, true, (7, 11))]
+    // Case 2: Default only replaces the text before the cursor
+    #[case("SELECT name FROM t", 9, false, (7, 9))]
+    // Case 3: Cursor at the end of the word is unaffected
+    #[case("SELECT na FROM t", 9, true, (7, 9))]
+    #[tokio::test]
+    async fn should_replace_whole_token_when_asked(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] whole: bool,
+        #[case] replace: (usize, usize),
+    ) {
+        let meta = database("postgres", &[("t", vec![("name", DataType::Text(None))])]).await;
+        let options = SearchOptions::default().with_replace_whole_token(whole);
+        let (result, span) =
+            Suggestion::search_with_replace(sql, Cursor::new(cursor, None), meta, &options)
+                .await
+                .unwrap();
+        // The column `name` and the keyword replace the same span
+        assert_eq!(span, replace);
+        assert_eq!(
+            result.clone().filter_kind(SuggestionKind::Column),
+            [Suggestion::column("name", DataType::Text(None))]
+        );
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Keyword),
+            [keyword("NATURAL", replace)]
+        );
+    }
+
+    #[rstest]
+    // Case 1: Prefix matching several keywords, in declaration order
+    #[case("SELECT * FROM a WHERE x INT", 27, vec![("INTERSECT", (24, 27)), ("INTO", (24, 27))])]
+    // Case 2: Cursor inside the word only replaces the text before it
+    #[case("SELEXT", 3, vec![("SELECT", (0, 3))])]
+    // Case 3: No partial, no keywords
+    #[case("SELECT * FROM a WHERE ", 22, vec![])]
+    #[tokio::test]
+    async fn should_filter_keywords_by_partial(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<(&str, (usize, usize))>,
+    ) {
+        let meta = database("postgres", &[]).await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        let keywords: Vec<_> = result
+            .into_iter()
+            .filter(|s| matches!(s, Suggestion::Keyword { .. }))
+            .collect();
+        let expected: Vec<_> = expected.into_iter().map(|(k, r)| keyword(k, r)).collect();
+        assert_eq!(keywords, expected);
+    }
+}
+
+// The collation name after COLLATE is never completed from metadata.
+mod collate {
+    use super::*;
+
+    #[rstest]
+    // Case 1: ORDER BY qualified column followed by COLLATE
+    #[case("SELECT a.name FROM a ORDER BY a.name COLLATE ", vec![])]
+    // Case 2: Collation name being typed (quoted)
+    #[case(r#"SELECT name FROM a ORDER BY name COLLATE "en"#, vec![])]
+    // Case 3: Collation name being typed (bare)
+    #[case("SELECT name FROM a WHERE name = 'x' COLLATE C", vec![])]
+    // Case 4: Completion resumes after the collation name
+    #[case(r#"SELECT name FROM a WHERE name = 'x' COLLATE "C" AND "#, vec!["name"])]
+    #[tokio::test]
+    async fn should_not_suggest_after_collate(#[case] sql: &str, #[case] expected: Vec<&str>) {
+        let meta = database("postgres", &[("a", vec![("name", DataType::Text(None))])]).await;
+        let result = Suggestion::search(sql, Cursor::new(sql.len(), None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+}
+
+// UPDATE statements: the target table plus the Postgres `UPDATE ... FROM` list.
+mod update {
+    use super::*;
+
+    #[rstest]
+    // Case 1: Qualified reference to a FROM table, FROM after the cursor
+    #[case("UPDATE a SET x = b. FROM b", 19, vec!["bid", "by"])]
+    // Case 2: Qualified reference in WHERE
+    #[case("UPDATE a SET x = y FROM b WHERE a_id = b.", 41, vec!["bid", "by"])]
+    // Case 3: Unqualified SET sees the target and FROM tables
+    #[case("UPDATE a SET  FROM b", 13, vec!["id", "x", "bid", "by"])]
+    // Case 4: Target alias
+    #[case("UPDATE a t SET x = t.", 21, vec!["id", "x"])]
+    // Case 5: Table position after UPDATE
+    #[case("UPDATE ", 7, vec!["public", "a", "b"])]
+    // Case 6: A `;` inside a string literal does not end the statement
+    #[case("UPDATE a SET x = ';', y =  FROM b", 26, vec!["id", "x", "bid", "by"])]
+    // Case 7: A `;` ends the UPDATE, later statements do not see its target
+    #[case("UPDATE a SET x = 1; DELETE FROM b WHERE ", 40, vec![])]
+    #[tokio::test]
+    async fn should_complete_update_statements(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("id", DataType::Uuid), ("x", DataType::Text(None))],
+                ),
+                (
+                    "b",
+                    vec![("bid", DataType::Uuid), ("by", DataType::Text(None))],
+                ),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+}
+
+mod merge {
+    use super::*;
+
+    #[rstest]
+    // Case 1: Qualified target in the ON condition
+    #[case("MERGE INTO a t USING b s ON t.", vec!["id", "x"])]
+    // Case 2: Qualified source in the ON condition
+    #[case("MERGE INTO a t USING b s ON t.id = s.", vec!["bid", "by"])]
+    // Case 3: Qualified target in a WHEN condition
+    #[case("MERGE INTO a t USING b s ON t.id = s.bid WHEN MATCHED AND t.", vec!["id", "x"])]
+    // Case 4: Qualified source in a WHEN MATCHED action
+    #[case("MERGE INTO a t USING b s ON t.id = s.bid WHEN MATCHED THEN UPDATE SET x = s.", vec!["bid", "by"])]
+    // Case 5: Qualified source given by an aliased subquery
+    #[case("MERGE INTO a t USING (SELECT bid FROM b) AS s ON t.id = s.", vec!["bid"])]
+    // Case 6: Unqualified ON sees the target and the source
+    #[case("MERGE INTO a USING b ON ", vec!["id", "x", "bid", "by"])]
+    // Case 7: A `;` inside a string literal does not end the statement
+    #[case("MERGE INTO a t USING b s ON t.id = s.bid WHEN MATCHED AND s.by = ';' THEN UPDATE SET x = s.", vec!["bid", "by"])]
+    #[tokio::test]
+    async fn should_complete_merge_statements(#[case] sql: &str, #[case] expected: Vec<&str>) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("id", DataType::Uuid), ("x", DataType::Text(None))],
+                ),
+                (
+                    "b",
+                    vec![("bid", DataType::Uuid), ("by", DataType::Text(None))],
+                ),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(sql.len(), None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+}
+
+mod type_context {
+    use super::*;
+
+    async fn meta() -> Database {
+        database(
+            "postgres",
+            &[(
+                "a",
+                vec![
+                    ("name", DataType::Text(None)),
+                    ("created_at", DataType::Timestamptz(None)),
+                    ("note", DataType::Text(None)),
+                    ("born_on", DataType::Date),
+                ],
+            )],
+        )
+        .await
+    }
+
+    #[rstest]
+    // Case 1: Qualified, temporal columns first, otherwise in table order
+    #[case("SELECT * FROM a WHERE created_at > a.", 38, true, vec!["created_at", "born_on", "name", "note"])]
+    // Case 2: Opt-in only
+    #[case("SELECT * FROM a WHERE created_at > a.", 38, false, vec!["name", "created_at", "note", "born_on"])]
+    // Case 3: Unqualified with a two-character operator and qualified operand
+    #[case("SELECT * FROM a WHERE a.name <> ", 32, true, vec!["name", "note", "created_at", "born_on"])]
+    // Case 4: Not a comparison, table order
+    #[case("SELECT * FROM a WHERE created_at AND ", 37, true, vec!["name", "created_at", "note", "born_on"])]
+    #[tokio::test]
+    async fn should_rank_comparable_columns_first(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] type_context: bool,
+        #[case] expected: Vec<&str>,
+    ) {
+        let options = SearchOptions::default().with_type_context(type_context);
+        let result =
+            Suggestion::search_with(sql, Cursor::new(cursor, None), meta().await, &options)
+                .await
+                .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            expected
+        );
+    }
+
+    #[rstest]
+    // Case 1: Unqualified, the other table's Uuid column first, then the compared table
+    #[case("SELECT * FROM a JOIN b ON a.id = ", vec!["a_id", "id", "label", "name"])]
+    // Case 2: Qualified with the other table
+    #[case("SELECT * FROM a JOIN b ON a.id = b.", vec!["a_id", "label"])]
+    // Case 3: Operand from the joined table, in reverse
+    #[case("SELECT * FROM a JOIN b ON b.a_id = ", vec!["id", "a_id", "name", "label"])]
+    #[tokio::test]
+    async fn should_rank_join_columns_matching_the_compared_type(
+        #[case] sql: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("id", DataType::Uuid), ("name", DataType::Text(None))],
+                ),
+                (
+                    "b",
+                    vec![("label", DataType::Text(None)), ("a_id", DataType::Uuid)],
+                ),
+            ],
+        )
+        .await;
+        let options = SearchOptions::default().with_type_context(true);
+        let result = Suggestion::search_with(sql, Cursor::new(sql.len(), None), meta, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            expected,
+            "{sql}"
+        );
+    }
+
+    #[tokio::test]
+    async fn should_rank_after_operator_tokens() {
+        let options = SearchOptions::default()
+            .with_type_context(true)
+            .with_tokenizer(TokenizerOptions::default().with_operators(true));
+        let sql = "SELECT * FROM a WHERE created_at >= a.";
+        let result = Suggestion::search_with(sql, Cursor::new(38, None), meta().await, &options)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            ["created_at", "born_on", "name", "note"]
+        );
+    }
+}
+
+mod primary_key {
+    use super::*;
+
+    async fn meta() -> Database {
+        let meta = Database::new("postgres");
+        meta.insert_table(
+            "public",
+            Table::new_from_columns(
+                "orders",
+                [
+                    Column::new("id_legacy", DataType::Integer(None)),
+                    Column::new("id", DataType::Uuid).with_primary_key(true),
+                    Column::new("total", DataType::Numeric(10, 2)),
+                ],
+            ),
+        )
+        .await;
+        meta
+    }
+
+    #[rstest]
+    // Case 1: Boosted, PK first among columns sharing the prefix
+    #[case("SELECT * FROM orders WHERE id", true, vec!["id", "id_legacy", "total"])]
+    // Case 2: Not boosted, declaration order
+    #[case("SELECT * FROM orders WHERE id", false, vec!["id_legacy", "id", "total"])]
+    // Case 3: Boosted qualified columns
+    #[case("SELECT * FROM orders o JOIN orders p ON o.", true, vec!["id", "id_legacy", "total"])]
+    #[tokio::test]
+    async fn should_rank_primary_key_columns_first(
+        #[case] sql: &str,
+        #[case] boost: bool,
+        #[case] expected: Vec<&str>,
+    ) {
+        let options = SearchOptions::default().with_primary_key_boost(boost);
+        let result =
+            Suggestion::search_with(sql, Cursor::new(sql.len(), None), meta().await, &options)
+                .await
+                .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            expected
+        );
+    }
+
+    #[tokio::test]
+    async fn should_badge_primary_key_columns_in_detail() {
+        let result = Suggestion::search("SELECT  FROM orders", Cursor::new(7, None), meta().await)
+            .await
+            .unwrap();
+        let details: Vec<_> = result.iter().map(Suggestion::detail).collect();
+        assert_eq!(
+            details,
+            [
+                Some("integer".into()),
+                Some("uuid PK".into()),
+                Some("numeric(10,2)".into())
+            ]
+        );
+        let hint = Suggestion::Hint {
+            column: "note".into(),
+            data_type: DataType::Text(None),
+        };
+        assert_eq!(hint.detail().as_deref(), Some("text"));
+        assert_eq!(Suggestion::Schema("public".into()).detail(), None);
+    }
+}
+
+mod trigger {
+    use super::*;
+
+    #[rstest]
+    // Case 1: `.` trigger after a qualifier
+    #[case("SELECT x. FROM a x, b", 9, Some('.'), vec!["id", "name"])]
+    // Case 2: Manual invocation at the same position
+    #[case("SELECT x. FROM a x, b", 9, None, vec!["id", "name"])]
+    // Case 3: Stale `.` trigger without a dot before the cursor
+    #[case("SELECT  FROM a x, b", 7, Some('.'), vec![])]
+    // Case 4: Manual invocation at the same position sees every table
+    #[case("SELECT  FROM a x, b", 7, None, vec!["id", "name", "bid"])]
+    // Case 5: Other triggers behave like a manual invocation
+    #[case("SELECT  FROM a x, b", 7, Some(' '), vec!["id", "name", "bid"])]
+    // Case 6: A dot not following an identifier
+    #[case("SELECT 1. FROM a", 9, Some('.'), vec![])]
+    // Case 7: Schema qualifier in a table position
+    #[case("SELECT * FROM public.", 21, Some('.'), vec!["a", "b"])]
+    #[tokio::test]
+    async fn should_respect_trigger_character(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] trigger: Option<char>,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("id", DataType::Uuid), ("name", DataType::Text(None))],
+                ),
+                ("b", vec![("bid", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let options = SearchOptions::default().with_trigger(trigger);
+        let result = Suggestion::search_with(sql, Cursor::new(cursor, None), meta, &options)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected, "{sql} {trigger:?}");
+    }
+}
+
+mod json_keys {
+    use super::*;
+
+    async fn meta() -> Database {
+        let meta = Database::new("postgres");
+        meta.insert_table(
+            "public",
+            Table::new_from_columns(
+                "events",
+                [
+                    Column::new("id", DataType::Uuid),
+                    Column::new("data", DataType::Json).with_json_keys(["user", "kind", "it's"]),
+                    Column::new("raw", DataType::Json),
+                ],
+            ),
+        )
+        .await;
+        meta
+    }
+
+    #[rstest]
+    // Case 1: After ->>
+    #[case("SELECT * FROM events WHERE data ->> '", vec!["user", "kind", "it's"])]
+    // Case 2: After -> with a partial key, qualified by alias
+    #[case("SELECT * FROM events e WHERE e.data->'K", vec!["kind"])]
+    // Case 3: Column without registered keys
+    #[case("SELECT * FROM events WHERE raw ->> '", vec![])]
+    // Case 4: Not a JSON path
+    #[case("SELECT * FROM events WHERE data > '", vec![])]
+    #[tokio::test]
+    async fn should_suggest_registered_json_keys(#[case] sql: &str, #[case] expected: Vec<&str>) {
+        for operators in [false, true] {
+            let options = SearchOptions::default()
+                .with_tokenizer(TokenizerOptions::default().with_operators(operators));
+            let result =
+                Suggestion::search_with(sql, Cursor::new(sql.len(), None), meta().await, &options)
+                    .await
+                    .unwrap();
+            let keys = result.filter_kind(SuggestionKind::JsonKey).into_labels();
+            assert_eq!(keys, expected, "operators: {operators}");
+        }
+    }
+
+    #[rstest]
+    // Case 1: Schema-qualified table
+    #[case("SELECT * FROM app.events e WHERE e.data ->> '", vec!["app_key"])]
+    // Case 2: Unqualified table resolves through the search path
+    #[case("SELECT * FROM events WHERE data ->> '", vec!["user", "kind", "it's"])]
+    #[tokio::test]
+    async fn should_suggest_json_keys_from_written_schema(
+        #[case] sql: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = meta().await;
+        meta.insert_table(
+            "app",
+            Table::new_from_columns(
+                "events",
+                [Column::new("data", DataType::Json).with_json_keys(["app_key"])],
+            ),
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(sql.len(), None), meta)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::JsonKey).into_labels(),
+            expected
+        );
+    }
+
+    #[test]
+    fn json_key_insert_text_escapes_quotes() {
+        assert_eq!(Suggestion::JsonKey("it's".into()).insert_text(), "it''s");
+        assert_eq!(Suggestion::JsonKey("user".into()).insert_text(), "user");
+    }
+}
+
+mod composite {
+    use super::*;
+
+    async fn meta() -> Database {
+        let meta = Database::new("postgres");
+        let geo = Column::new("geo", DataType::Other("point_t".into())).with_fields([
+            Column::new("lat", DataType::Float8),
+            Column::new("lng", DataType::Float8),
+        ]);
+        let address = Column::new("address", DataType::Other("address_t".into()))
+            .with_fields([Column::new("street", DataType::Text(None)), geo]);
+        meta.insert_table(
+            "public",
+            Table::new_from_columns("users", [Column::new("id", DataType::Uuid), address]),
+        )
+        .await;
+        meta
+    }
+
+    #[rstest]
+    // Case 1: Composite column of an aliased table
+    #[case("SELECT u.address. FROM users u", 17, vec!["street", "geo"])]
+    // Case 2: Two-level chain
+    #[case("SELECT u.address.geo. FROM users u", 21, vec!["lat", "lng"])]
+    // Case 3: Two-level chain with a partial field
+    #[case("SELECT users.address.geo.l FROM users", 26, vec!["lat", "lng"])]
+    // Case 4: Segment that is not composite
+    #[case("SELECT u.id. FROM users u", 12, vec![])]
+    // Case 5: Unknown field
+    #[case("SELECT u.address.zip. FROM users u", 21, vec![])]
+    // Case 6: A single segment still qualifies a table
+    #[case("SELECT u. FROM users u", 9, vec!["id", "address"])]
+    #[tokio::test]
+    async fn should_walk_composite_chains(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta().await)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            expected
+        );
+    }
+
+    #[rstest]
+    // Case 1: Schema-qualified table
+    #[case("SELECT u.address. FROM app.users u", 17, vec!["zip"])]
+    // Case 2: Unqualified table resolves through the search path
+    #[case("SELECT u.address. FROM users u", 17, vec!["street", "geo"])]
+    #[tokio::test]
+    async fn should_walk_composite_chains_in_written_schema(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = meta().await;
+        let address = Column::new("address", DataType::Other("address_t".into()))
+            .with_fields([Column::new("zip", DataType::Text(None))]);
+        meta.insert_table("app", Table::new_from_columns("users", [address]))
+            .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(
+            result.filter_kind(SuggestionKind::Column).into_labels(),
+            expected
+        );
+    }
+}
+
+mod table_functions {
+    use super::*;
+
+    async fn meta() -> Database {
+        let meta = database("postgres", &[("t", vec![("id", DataType::Uuid)])]).await;
+        meta.insert_function(TableFunction::new(
+            "unnest",
+            [Column::new("value", DataType::Text(None))],
+        ))
+        .await;
+        meta
+    }
+
+    #[rstest]
+    // Case 1: Qualified by the alias
+    #[case("SELECT u. FROM unnest(tags) AS u", 9, vec!["value"])]
+    // Case 2: Qualified by the function name without an alias
+    #[case("SELECT unnest. FROM unnest(tags)", 14, vec!["value"])]
+    // Case 3: Unqualified, alongside a table
+    #[case("SELECT  FROM t, unnest(tags) u", 7, vec!["id", "value"])]
+    // Case 4: Unregistered functions contribute nothing
+    #[case("SELECT g. FROM generate_series(1, 10) AS g", 9, vec![])]
+    #[tokio::test]
+    async fn should_suggest_registered_function_columns(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta().await)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected);
+    }
+
+    #[tokio::test]
+    async fn should_suggest_common_series_column() {
+        let meta = meta().await;
+        for function in TableFunction::common() {
+            meta.insert_function(function).await;
+        }
+        let sql = "SELECT g. FROM generate_series(1, 10) AS g";
+        let result = Suggestion::search(sql, Cursor::new(9, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            [Suggestion::column(
+                "generate_series",
+                DataType::BigInt(None)
+            )]
+        );
+    }
+}
+
+// `search_tables` only answers at table positions.
+mod table_search {
+    use super::*;
+
+    #[rstest]
+    // Case 1: Right after FROM
+    #[case("SELECT * FROM ", 14, vec!["public", "orders", "users"])]
+    // Case 2: Partial table name after JOIN
+    #[case("SELECT * FROM users JOIN or", 27, vec!["orders"])]
+    // Case 3: Schema-qualified position
+    #[case("SELECT * FROM public.u", 22, vec!["users"])]
+    // Case 4: Projection is not a table position
+    #[case("SELECT  FROM users", 7, vec![])]
+    // Case 5: WHERE clause is not a table position
+    #[case("SELECT * FROM users WHERE ", 26, vec![])]
+    // Case 6: `TABLE name` shorthand
+    #[case("TABLE us", 8, vec!["users"])]
+    #[tokio::test]
+    async fn should_only_suggest_tables_at_table_positions(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                ("users", vec![("id", DataType::Uuid)]),
+                ("orders", vec![("id", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search_tables(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected);
+    }
+}
+
+// `search` runs inside a debug span recording per-phase timings.
+mod search_span {
+    use super::*;
+    use std::{
+        collections::HashMap,
+        fmt::Debug,
+        sync::{Arc, Mutex},
+    };
+    use tracing::{
+        Subscriber,
+        field::{Field, Visit},
+        span::{Attributes, Id, Record},
+    };
+    use tracing_subscriber::{Layer, layer::Context, layer::SubscriberExt as _};
+
+    /// Span name and the names of every field given a value.
+    type SpanFields = (String, Vec<String>);
+
+    #[derive(Clone, Default)]
+    struct Capture(Arc<Mutex<HashMap<Id, SpanFields>>>);
+
+    struct FieldNames<'a>(&'a mut Vec<String>);
+
+    impl Visit for FieldNames<'_> {
+        fn record_debug(&mut self, field: &Field, _: &dyn Debug) {
+            self.0.push(field.name().to_string());
+        }
+    }
+
+    impl<S: Subscriber> Layer<S> for Capture {
+        fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, _: Context<'_, S>) {
+            let mut fields = Vec::new();
+            attrs.record(&mut FieldNames(&mut fields));
+            let name = attrs.metadata().name().to_string();
+            self.0.lock().unwrap().insert(id.clone(), (name, fields));
+        }
+
+        fn on_record(&self, id: &Id, values: &Record<'_>, _: Context<'_, S>) {
+            if let Some((_, fields)) = self.0.lock().unwrap().get_mut(id) {
+                values.record(&mut FieldNames(fields));
+            }
+        }
+    }
+
+    #[test]
+    fn should_emit_search_span_with_timings() {
+        let capture = Capture::default();
+        let subscriber = tracing_subscriber::registry().with(capture.clone());
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .expect("runtime");
+        let result = tracing::subscriber::with_default(subscriber, || {
+            runtime.block_on(async {
+                let meta = database("postgres", &[("a", vec![("id", DataType::Uuid)])]).await;
+                Suggestion::search("SELECT  FROM a", Cursor::new(7, None), meta).await
+            })
+        });
+        assert_eq!(result.expect("search").len(), 1);
+
+        let spans = capture.0.lock().unwrap();
+        let (_, fields) = spans
+            .values()
+            .find(|(name, _)| name == "search")
+            .expect("search span emitted");
+        for field in [
+            "sql_len",
+            "cursor",
+            "tokenize_us",
+            "scope_us",
+            "lock_wait_us",
+            "suggestions",
+        ] {
+            assert!(
+                fields.iter().any(|f| f == field),
+                "{field} not recorded: {fields:?}"
+            );
+        }
+    }
+}
+
+// Contract: `Suggestion::search` never panics, whatever the buffer and cursor.
+// Guards the slicing / indexing performed on token spans and cursor offsets.
+mod search_paged {
+    use super::*;
+
+    async fn meta() -> Database {
+        let columns: Vec<_> = (0..25)
+            .map(|i| (format!("c{i:02}"), DataType::Integer(None)))
+            .collect();
+        let columns: Vec<_> = columns
+            .iter()
+            .map(|(n, t)| (n.as_str(), t.clone()))
+            .collect();
+        database("postgres", &[("wide", columns)]).await
+    }
+
+    #[tokio::test]
+    async fn pages_cover_search_in_order() {
+        let meta = meta().await;
+        let sql = "SELECT  FROM wide";
+        let all = Suggestion::search(sql, Cursor::new(7, None), &meta)
+            .await
+            .unwrap();
+
+        let mut paged = Vec::new();
+        let mut offset = 0;
+        loop {
+            let (page, has_more) =
+                Suggestion::search_paged(sql, Cursor::new(7, None), &meta, offset, 10)
+                    .await
+                    .unwrap();
+            assert!(page.len() <= 10);
+            offset += page.len();
+            paged.extend(page);
+            if !has_more {
+                break;
+            }
+        }
+        assert_eq!(paged, all);
+        assert_eq!(offset, all.len());
+    }
+
+    #[rstest]
+    // Case 1: First page
+    #[case(0, 10, 10, true)]
+    // Case 2: Last, partial page
+    #[case(20, 10, 5, false)]
+    // Case 3: Page ending exactly at the last column
+    #[case(15, 10, 10, false)]
+    // Case 4: Offset past the end
+    #[case(40, 10, 0, false)]
+    // Case 5: Zero limit
+    #[case(0, 0, 0, true)]
+    #[tokio::test]
+    async fn should_report_has_more(
+        #[case] offset: usize,
+        #[case] limit: usize,
+        #[case] len: usize,
+        #[case] has_more: bool,
+    ) {
+        let (page, more) = Suggestion::search_paged(
+            "SELECT  FROM wide",
+            Cursor::new(7, None),
+            meta().await,
+            offset,
+            limit,
+        )
+        .await
+        .unwrap();
+        assert_eq!((page.len(), more), (len, has_more));
+        if let Some(first) = page.first() {
+            assert_eq!(first.label(), format!("c{offset:02}"));
+        }
+    }
+}
+
+mod search_stream {
+    use super::*;
+    use futures::StreamExt as _;
+
+    #[rstest]
+    // Case 1: Unqualified columns of several tables, then keywords
+    #[case("SELECT  FROM a JOIN b ON true", 7, false)]
+    // Case 2: Partial word: columns followed by keywords
+    #[case("SELECT * FROM a, b WHERE i", 26, false)]
+    // Case 3: GROUP BY aliases and ordinals after the columns
+    #[case("SELECT id AS k, name FROM a, b GROUP BY ", 40, false)]
+    // Case 4: Type-ranked columns are streamed once complete
+    #[case("SELECT * FROM a, b WHERE id = ", 30, true)]
+    // Case 5: Qualified columns
+    #[case("SELECT b. FROM a, b", 9, false)]
+    // Case 6: Tables
+    #[case("SELECT * FROM ", 14, false)]
+    // Case 7: Nothing to complete
+    #[case("", 0, false)]
+    #[tokio::test]
+    async fn should_match_search_with(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] type_context: bool,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "a",
+                    vec![("id", DataType::Uuid), ("name", DataType::Text(None))],
+                ),
+                (
+                    "b",
+                    vec![("b_id", DataType::Uuid), ("note", DataType::Text(None))],
+                ),
+            ],
+        )
+        .await;
+        let options = SearchOptions::default().with_type_context(type_context);
+        let cursor = Cursor::new(cursor, None);
+        let streamed: Suggestions = Suggestion::search_stream(sql, cursor, &meta, &options)
+            .collect()
+            .await;
+        let searched = Suggestion::search_with(sql, cursor, &meta, &options)
+            .await
+            .unwrap();
+        assert_eq!(streamed, searched, "{sql}");
+    }
+}
+
+// String literals are single tokens: words inside them are not SQL.
+mod string_literals {
+    use super::*;
+
+    #[rstest]
+    // Case 1: A keyword inside a projected literal is not a FROM clause
+    #[case("SELECT 'FROM me',  FROM users", 18, vec!["id", "name"])]
+    // Case 2: Same, cursor in WHERE
+    #[case("SELECT 'FROM me' FROM users WHERE ", 34, vec!["id", "name"])]
+    // Case 3: Join syntax inside a literal adds no table
+    #[case("SELECT * FROM users WHERE name = 'JOIN b ON x' AND ", 51, vec!["id", "name"])]
+    // Case 4: Escaped quote keeps the literal closed
+    #[case("SELECT * FROM users WHERE name = 'it''s' AND ", 45, vec!["id", "name"])]
+    // Case 5: Cursor inside a literal
+    #[case("SELECT 'FROM ' FROM users", 13, vec![])]
+    // Case 6: Unterminated literal runs to the end of input
+    #[case("SELECT * FROM users WHERE name = 'x FROM b WHERE ", 49, vec![])]
+    #[tokio::test]
+    async fn should_not_read_keywords_inside_literals(
+        #[case] sql: &str,
+        #[case] cursor: usize,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                (
+                    "users",
+                    vec![("id", DataType::Uuid), ("name", DataType::Text(None))],
+                ),
+                ("me", vec![("secret", DataType::Text(None))]),
+                ("b", vec![("bid", DataType::Uuid)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(cursor, None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+}
+
+// Rarely used predicates without a `Keyword` variant, such as `OVERLAPS`.
+mod predicates {
+    use super::*;
+
+    #[rstest]
+    // Case 1: Qualified prefix inside the right-hand row
+    #[case("SELECT * FROM a, b WHERE (a.s, a.s) OVERLAPS (b.", vec!["bid", "e"])]
+    // Case 2: Qualified prefix after the predicate
+    #[case("SELECT * FROM a, b WHERE (a.s, a.s) OVERLAPS (b.s, b.e) AND a.", vec!["id", "s"])]
+    // Case 3: An operand is expected after the predicate word
+    #[case("SELECT * FROM a, b WHERE (a.s, a.s) OVERLAPS ", vec!["id", "s", "bid", "e"])]
+    // Case 4: Same inside a JOIN condition
+    #[case("SELECT * FROM a JOIN b ON (a.s, a.s) OVERLAPS (b.", vec!["bid", "e"])]
+    // Case 5: `SIMILAR TO` expects an operand too
+    #[case("SELECT * FROM a WHERE s SIMILAR TO ", vec!["id", "s"])]
+    #[tokio::test]
+    async fn should_treat_unknown_predicates_as_operators(
+        #[case] sql: &str,
+        #[case] expected: Vec<&str>,
+    ) {
+        let meta = database(
+            "postgres",
+            &[
+                ("a", vec![("id", DataType::Uuid), ("s", DataType::Date)]),
+                ("b", vec![("bid", DataType::Uuid), ("e", DataType::Date)]),
+            ],
+        )
+        .await;
+        let result = Suggestion::search(sql, Cursor::new(sql.len(), None), meta)
+            .await
+            .unwrap();
+        assert_eq!(result.into_labels(), expected, "{sql}");
+    }
+}
+
+mod line_endings {
+    use super::*;
+
+    #[rstest]
+    // Case 1: Unix line endings
+    #[case("SELECT\n\tu. -- FROM x\nFROM users u")]
+    // Case 2: Windows line endings
+    #[case("SELECT\r\n\tu. -- FROM x\r\nFROM users u")]
+    #[tokio::test]
+    async fn should_map_line_col_across_crlf(#[case] sql: &str) {
+        let meta = database("postgres", &[("users", vec![("id", DataType::Uuid)])]).await;
+        let cursor = Cursor::from_line_col(sql, 1, 3).expect("cursor");
+        assert_eq!(&sql[cursor.start() - 2..cursor.start()], "u.");
+        let result = Suggestion::search(sql, cursor, meta).await.unwrap();
+        assert_eq!(result.into_labels(), ["id"], "{sql:?}");
+    }
+}
+
+mod search_contract {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// SQL-flavoured fragments mixed with arbitrary characters (including multibyte).
+    fn sql_text() -> impl Strategy<Value = String> {
+        let fragment = prop_oneof![
+            prop::sample::select(vec![
+                "SELECT", "FROM", "JOIN", "ON", "AS", "WHERE", "GROUP", "ORDER", "LIMIT", "UNION",
+                "IN", "a", "b", "a.", "b.", ".", ",", "(", ")", "*", "'", "=", "<>", " ", "\n",
+                "é_table", "ü", "🙂",
+            ])
+            .prop_map(str::to_string),
+            any::<char>().prop_map(String::from),
+        ];
+        prop::collection::vec(fragment, 0..40).prop_map(|parts| parts.join(" "))
+    }
+
+    proptest! {
+        #[test]
+        fn search_never_panics(sql in sql_text(), cursor in any::<prop::sample::Index>()) {
+            let cursor = cursor.index(sql.len() + 1);
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("runtime");
+            let result = runtime.block_on(async {
+                let meta = database(
+                    "postgres",
+                    &[
+                        ("a", vec![("id", DataType::Uuid)]),
+                        ("b", vec![("status", DataType::Enum(vec!["x".into()]))]),
+                    ],
+                )
+                .await;
+                Suggestion::search(&sql, Cursor::new(cursor, None), meta).await
+            });
+            prop_assert!(result.is_ok(), "search returned {result:?}");
+        }
+
+        #[test]
+        fn search_never_panics_on_arbitrary_utf8(
+            sql in any::<String>(),
+            cursor in any::<prop::sample::Index>(),
+        ) {
+            let cursor = cursor.index(sql.len() + 1);
+            let runtime = tokio::runtime::Builder::new_current_thread()
+                .build()
+                .expect("runtime");
+            let result = runtime.block_on(async {
+                Suggestion::search(&sql, Cursor::new(cursor, None), Database::new("postgres")).await
+            });
+            prop_assert!(result.is_ok(), "search returned {result:?}");
+        }
+    }
 }
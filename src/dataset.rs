@@ -0,0 +1,37 @@
+//! Synthetic metadata fixtures, shared by tests and the `search` benchmark.
+
+use crate::*;
+
+/// Tables per schema in `wide_database`.
+const TABLES_PER_SCHEMA: usize = 100;
+
+/// A synthetic database of `tables` tables (`t0`, `t1`, ...) with
+/// `cols_per_table` columns each (`c0`, `c1`, ...), spread over schemas of
+/// 100 tables (`s0`, `s1`, ...) so lookups traverse several schema locks.
+pub fn wide_database(tables: usize, cols_per_table: usize) -> Database {
+    (0..tables)
+        .fold(Database::builder("bench"), |db, t| {
+            let columns = (0..cols_per_table).map(|c| (format!("c{c}"), DataType::Integer(None)));
+            db.table(
+                format!("s{}", t / TABLES_PER_SCHEMA),
+                format!("t{t}"),
+                columns,
+            )
+        })
+        .build()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn wide_database_shape() {
+        let db = wide_database(250, 3);
+        assert_eq!(db.schema_names().await, ["s0", "s1", "s2"]);
+        let schemas = db.schemas.read().await;
+        let tables = schemas["s2"].tables.read().await;
+        assert_eq!(tables.len(), 50);
+        assert_eq!(tables["t249"].column_order, ["c0", "c1", "c2"]);
+    }
+}
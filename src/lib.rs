@@ -0,0 +1,44 @@
+//! SQL autocompletion over database metadata.
+//!
+//! The `sql` tokenizer has no dependencies and is always built. Everything
+//! else (metadata, introspection, completion) needs the async and database
+//! stack of the default `full` feature; build with `--no-default-features
+//! --features sql-only` (`default-features = false, features = ["sql-only"]`
+//! as a dependency) to get the tokenizer alone. `sql-only` is a marker that
+//! enables nothing by itself: it is dropping `full` that removes the deps.
+
+pub mod sql;
+#[allow(unused_imports)]
+pub use sql::*;
+#[cfg(feature = "full")]
+#[doc(hidden)]
+pub mod dataset;
+#[cfg(feature = "full")]
+reexport!(testing, test);
+#[cfg(feature = "full")]
+reexport!(logic);
+#[cfg(feature = "full")]
+reexport!(autocomplete);
+#[cfg(feature = "full")]
+reexport!(metadata);
+#[cfg(feature = "full")]
+#[allow(unused_imports)]
+pub(crate) use tracing::{debug, error, info, span, trace, warn};
+
+#[macro_export]
+macro_rules! reexport {
+    ($module:ident) => {
+        $crate::reexport!($module, false);
+    };
+    ($module:ident, test) => {
+        $crate::reexport!($module, true);
+    };
+    ($module:ident, $is_test:literal) => {
+        #[cfg_attr($is_test, cfg(test))]
+        mod $module;
+        #[cfg_attr($is_test, cfg(test))]
+        #[allow(unused_imports)]
+        #[allow(ambiguous_glob_reexports)]
+        pub use $module::*;
+    };
+}
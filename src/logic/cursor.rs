@@ -1,4 +1,4 @@
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Cursor {
     start: usize,
     end: Option<usize>,
@@ -9,6 +9,17 @@ impl Cursor {
         Self { start, end }
     }
 
+    /// A collapsed cursor (no selection) at byte offset `pos`.
+    pub fn at(pos: usize) -> Self {
+        Self::new(pos, None)
+    }
+
+    /// A collapsed cursor at a 0-based `line` and character `col` of `sql`
+    /// (see `line_col_to_byte`). `None` if the position is outside `sql`.
+    pub fn from_line_col(sql: &str, line: usize, col: usize) -> Option<Self> {
+        crate::sql::line_col_to_byte(sql, line, col).map(Self::at)
+    }
+
     pub fn start(&self) -> usize {
         self.start
     }
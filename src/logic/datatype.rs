@@ -73,8 +73,16 @@ pub enum DataType {
         "match _0 { Some(v) => v.to_string(), None => \"None\".to_string() }"
     )]
     DateTime(Option<usize>),
-    Timestamp,
-    Timestamptz,
+    #[display(
+        "Timestamp({})",
+        "match _0 { Some(v) => v.to_string(), None => \"None\".to_string() }"
+    )]
+    Timestamp(Option<usize>),
+    #[display(
+        "Timestamptz({})",
+        "match _0 { Some(v) => v.to_string(), None => \"None\".to_string() }"
+    )]
+    Timestamptz(Option<usize>),
     #[display(
         "Time({})",
         "match _0 { Some(v) => v.to_string(), None => \"None\".to_string() }"
@@ -114,6 +122,354 @@ pub enum DataType {
     Inet4,
     Inet6,
     Uuid,
+    Interval,
+    Money,
+    /// PostGIS `geometry`, with its subtype (`Point`, `Polygon`, ...) if declared.
+    #[display("Geometry({})", _0.as_deref().unwrap_or("None"))]
+    Geometry(Option<String>),
+    /// PostGIS `geography`, with its subtype if declared.
+    #[display("Geography({})", _0.as_deref().unwrap_or("None"))]
+    Geography(Option<String>),
+    /// A type without a dedicated variant (domains, extension types such as
+    /// PostGIS `geometry`), holding the raw type name.
+    #[display("Other({_0})")]
+    Other(String),
     #[default]
     Unknown,
 }
+
+impl DataType {
+    /// Map a Postgres type name, as rendered by `format_type()`, to a `DataType`.
+    ///
+    /// Length / precision modifiers are honored where the variant carries them
+    /// (`character varying(255)`, `numeric(10,2)`, `time(3) without time zone`).
+    /// Returns `None` for types without a mapping (arrays, ranges, user-defined
+    /// types); enums are resolved by the caller from `pg_enum`, anything else
+    /// may fall back to `DataType::Other`.
+    pub fn from_pg_str(pg_type: &str) -> Option<Self> {
+        if let Some(spatial) = Self::from_postgis_str(pg_type.trim()) {
+            return Some(spatial);
+        }
+        let pg_type = pg_type.trim().to_ascii_lowercase();
+        let (base, modifiers, suffix) = match pg_type.split_once('(') {
+            Some((base, rest)) => {
+                let (args, suffix) = rest.split_once(')')?;
+                let args = args
+                    .split(',')
+                    .map(|a| a.trim().parse::<usize>().ok())
+                    .collect::<Option<Vec<_>>>()?;
+                (base.trim(), args, suffix.trim())
+            }
+            None => (pg_type.as_str(), Vec::new(), ""),
+        };
+        let first = modifiers.first().copied();
+        let data_type = match (base, suffix) {
+            ("boolean", "") => DataType::Boolean,
+            ("smallint", "") => DataType::SmallInt(None),
+            ("integer", "") => DataType::Integer(None),
+            ("bigint", "") => DataType::BigInt(None),
+            ("real", "") => DataType::Float,
+            ("double precision", "") => DataType::Float8,
+            // Unconstrained `numeric` has no declared precision / scale.
+            ("numeric", "") => {
+                DataType::Numeric(first.unwrap_or(0), modifiers.get(1).copied().unwrap_or(0))
+            }
+            ("character varying", "") => DataType::VarChar(first),
+            ("character", "") | ("\"char\"", "") => DataType::Char(first),
+            ("text", "") => DataType::Text(None),
+            ("bytea", "") => DataType::Bytea,
+            ("bit", "") => DataType::Bit(first.unwrap_or(1)),
+            ("json" | "jsonb", "") => DataType::Json,
+            ("uuid", "") => DataType::Uuid,
+            // `inet` holds both address families; v6 is the superset.
+            ("inet", "") => DataType::Inet6,
+            ("date", "") => DataType::Date,
+            // Field restrictions (`interval day to second`) are not tracked.
+            (base, _) if base.starts_with("interval") => DataType::Interval,
+            ("money", "") => DataType::Money,
+            ("timestamp" | "timestamp without time zone", "") => DataType::Timestamp(first),
+            ("timestamp", "without time zone") => DataType::Timestamp(first),
+            ("timestamp with time zone", "") | ("timestamp", "with time zone") => {
+                DataType::Timestamptz(first)
+            }
+            ("time" | "time without time zone" | "time with time zone", "") => DataType::Time(None),
+            ("time", "without time zone" | "with time zone") => DataType::Time(first),
+            _ => return None,
+        };
+        Some(data_type)
+    }
+
+    /// Map PostGIS `geometry` / `geography`, optionally with a parenthesized
+    /// subtype (`geometry(Point,4326)`). The subtype keeps its original case;
+    /// the SRID is not tracked.
+    fn from_postgis_str(pg_type: &str) -> Option<Self> {
+        let (ctor, rest): (fn(Option<String>) -> Self, _) = match pg_type.get(..9) {
+            Some(p) if p.eq_ignore_ascii_case("geography") => (DataType::Geography, &pg_type[9..]),
+            _ => match pg_type.get(..8) {
+                Some(p) if p.eq_ignore_ascii_case("geometry") => {
+                    (DataType::Geometry, &pg_type[8..])
+                }
+                _ => return None,
+            },
+        };
+        let rest = rest.trim();
+        if rest.is_empty() {
+            return Some(ctor(None));
+        }
+        let args = rest.strip_prefix('(')?.strip_suffix(')')?;
+        let subtype = args.split(',').next()?.trim();
+        Some(ctor((!subtype.is_empty()).then(|| subtype.to_string())))
+    }
+
+    /// Render the type as SQL, preferring the Postgres spelling used by
+    /// `format_type()` so that `from_pg_str(to_sql())` round-trips for every
+    /// type `from_pg_str` produces.
+    ///
+    /// MySQL-only types keep their MySQL name; `Named` renders as Postgres'
+    /// `name` and `Unknown` as the `unknown` pseudo-type.
+    pub fn to_sql(&self) -> String {
+        fn sized(name: &str, len: &Option<usize>) -> String {
+            match len {
+                Some(len) => format!("{name}({len})"),
+                None => name.to_string(),
+            }
+        }
+        fn values(name: &str, values: &[String]) -> String {
+            let values: Vec<_> = values
+                .iter()
+                .map(|v| format!("'{}'", v.replace('\'', "''")))
+                .collect();
+            format!("{name}({})", values.join(", "))
+        }
+
+        match self {
+            DataType::Boolean => "boolean".into(),
+            DataType::TinyInt(len) => sized("tinyint", len),
+            DataType::SmallInt(len) => sized("smallint", len),
+            DataType::Integer(len) => sized("integer", len),
+            DataType::Int(len) => sized("int", len),
+            DataType::BigInt(len) => sized("bigint", len),
+            DataType::Char(len) => sized("character", len),
+            DataType::VarChar(len) => sized("character varying", len),
+            DataType::TinyText(len) => sized("tinytext", len),
+            DataType::MediumText(len) => sized("mediumtext", len),
+            DataType::Text(len) => sized("text", len),
+            DataType::LongText(len) => sized("longtext", len),
+            DataType::Enum(variants) => values("enum", variants),
+            DataType::Set(variants) => values("set", variants),
+            DataType::Float8 => "double precision".into(),
+            DataType::Float => "real".into(),
+            DataType::Double(None, _) => "double".into(),
+            DataType::Double(Some(precision), scale) => format!("double({precision},{scale})"),
+            DataType::Numeric(0, 0) => "numeric".into(),
+            DataType::Numeric(precision, scale) => format!("numeric({precision},{scale})"),
+            DataType::DateTime(precision) => sized("datetime", precision),
+            DataType::Timestamp(precision) => {
+                format!("{} without time zone", sized("timestamp", precision))
+            }
+            DataType::Timestamptz(precision) => {
+                format!("{} with time zone", sized("timestamp", precision))
+            }
+            DataType::Time(precision) => format!("{} without time zone", sized("time", precision)),
+            DataType::TinyBlob(len) => sized("tinyblob", len),
+            DataType::MediumBlob(len) => sized("mediumblob", len),
+            DataType::Date => "date".into(),
+            DataType::Blob(len) => sized("blob", len),
+            DataType::LongBlob(len) => sized("longblob", len),
+            DataType::VarBinary(len) => format!("varbinary({len})"),
+            DataType::Binary(len) => sized("binary", len),
+            DataType::Named => "name".into(),
+            DataType::Json => "json".into(),
+            DataType::Bit(len) => format!("bit({len})"),
+            DataType::Bytea => "bytea".into(),
+            DataType::Inet4 | DataType::Inet6 => "inet".into(),
+            DataType::Uuid => "uuid".into(),
+            DataType::Interval => "interval".into(),
+            DataType::Money => "money".into(),
+            DataType::Geometry(subtype) => match subtype {
+                Some(subtype) => format!("geometry({subtype})"),
+                None => "geometry".into(),
+            },
+            DataType::Geography(subtype) => match subtype {
+                Some(subtype) => format!("geography({subtype})"),
+                None => "geography".into(),
+            },
+            DataType::Other(name) => name.clone(),
+            DataType::Unknown => "unknown".into(),
+        }
+    }
+
+    /// True if values of both types can be compared without an explicit
+    /// cast: both numeric, both textual, both dates / timestamps, and so on.
+    /// Sizes and precisions are ignored. `Other` types only match the same
+    /// type name and `Unknown` matches nothing.
+    pub fn is_comparable_with(&self, other: &DataType) -> bool {
+        #[derive(PartialEq)]
+        enum Family<'a> {
+            Boolean,
+            Numeric,
+            Text,
+            Temporal,
+            Time,
+            Interval,
+            Binary,
+            Json,
+            Network,
+            Uuid,
+            Spatial,
+            Other(&'a str),
+        }
+        fn family(data_type: &DataType) -> Option<Family<'_>> {
+            Some(match data_type {
+                DataType::Boolean => Family::Boolean,
+                DataType::TinyInt(_)
+                | DataType::SmallInt(_)
+                | DataType::Integer(_)
+                | DataType::Int(_)
+                | DataType::BigInt(_)
+                | DataType::Float8
+                | DataType::Float
+                | DataType::Double(..)
+                | DataType::Numeric(..)
+                | DataType::Money => Family::Numeric,
+                DataType::Char(_)
+                | DataType::VarChar(_)
+                | DataType::TinyText(_)
+                | DataType::MediumText(_)
+                | DataType::Text(_)
+                | DataType::LongText(_)
+                | DataType::Enum(_)
+                | DataType::Set(_)
+                | DataType::Named => Family::Text,
+                DataType::DateTime(_)
+                | DataType::Timestamp(_)
+                | DataType::Timestamptz(_)
+                | DataType::Date => Family::Temporal,
+                DataType::Time(_) => Family::Time,
+                DataType::Interval => Family::Interval,
+                DataType::TinyBlob(_)
+                | DataType::MediumBlob(_)
+                | DataType::Blob(_)
+                | DataType::LongBlob(_)
+                | DataType::VarBinary(_)
+                | DataType::Binary(_)
+                | DataType::Bit(_)
+                | DataType::Bytea => Family::Binary,
+                DataType::Json => Family::Json,
+                DataType::Inet4 | DataType::Inet6 => Family::Network,
+                DataType::Uuid => Family::Uuid,
+                DataType::Geometry(_) | DataType::Geography(_) => Family::Spatial,
+                DataType::Other(name) => Family::Other(name),
+                DataType::Unknown => return None,
+            })
+        }
+        family(self).is_some_and(|f| family(other) == Some(f))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("boolean", Some(DataType::Boolean))]
+    #[case("integer", Some(DataType::Integer(None)))]
+    #[case("BIGINT", Some(DataType::BigInt(None)))]
+    #[case("character varying(255)", Some(DataType::VarChar(Some(255))))]
+    #[case("character varying", Some(DataType::VarChar(None)))]
+    #[case("character(2)", Some(DataType::Char(Some(2))))]
+    #[case("numeric(10,2)", Some(DataType::Numeric(10, 2)))]
+    #[case("numeric", Some(DataType::Numeric(0, 0)))]
+    #[case("double precision", Some(DataType::Float8))]
+    #[case("timestamp without time zone", Some(DataType::Timestamp(None)))]
+    #[case("timestamp(3) with time zone", Some(DataType::Timestamptz(Some(3))))]
+    #[case("timestamp(6) with time zone", Some(DataType::Timestamptz(Some(6))))]
+    #[case("timestamp(0) without time zone", Some(DataType::Timestamp(Some(0))))]
+    #[case("time(6) without time zone", Some(DataType::Time(Some(6))))]
+    #[case("jsonb", Some(DataType::Json))]
+    #[case("uuid", Some(DataType::Uuid))]
+    #[case("bit(8)", Some(DataType::Bit(8)))]
+    #[case("interval", Some(DataType::Interval))]
+    #[case("interval day to second(3)", Some(DataType::Interval))]
+    #[case("money", Some(DataType::Money))]
+    #[case("geometry(Point,4326)", Some(DataType::Geometry(Some("Point".into()))))]
+    #[case("geometry(MultiPolygon)", Some(DataType::Geometry(Some("MultiPolygon".into()))))]
+    #[case("geometry", Some(DataType::Geometry(None)))]
+    #[case("GEOGRAPHY(Point, 4326)", Some(DataType::Geography(Some("Point".into()))))]
+    #[case("geography", Some(DataType::Geography(None)))]
+    #[case("geometry_dump", None)]
+    #[case("integer[]", None)]
+    #[case("mood", None)]
+    #[case("character varying(x)", None)]
+    fn from_pg_str(#[case] pg_type: &str, #[case] expected: Option<DataType>) {
+        assert_eq!(DataType::from_pg_str(pg_type), expected);
+    }
+
+    #[rstest]
+    #[case(DataType::Interval, "interval")]
+    #[case(DataType::Money, "money")]
+    #[case(DataType::VarChar(Some(255)), "character varying(255)")]
+    #[case(DataType::Numeric(10, 2), "numeric(10,2)")]
+    #[case(DataType::Numeric(0, 0), "numeric")]
+    #[case(DataType::Time(Some(3)), "time(3) without time zone")]
+    #[case(DataType::Timestamptz(None), "timestamp with time zone")]
+    #[case(DataType::Timestamptz(Some(6)), "timestamp(6) with time zone")]
+    #[case(DataType::Timestamp(Some(3)), "timestamp(3) without time zone")]
+    #[case(DataType::Bit(8), "bit(8)")]
+    #[case(DataType::Geometry(Some("Point".into())), "geometry(Point)")]
+    #[case(DataType::Geography(None), "geography")]
+    fn to_sql_round_trips(#[case] data_type: DataType, #[case] sql: &str) {
+        assert_eq!(data_type.to_sql(), sql);
+        assert_eq!(DataType::from_pg_str(&data_type.to_sql()), Some(data_type));
+    }
+
+    #[test]
+    fn display_interval_and_money() {
+        assert_eq!(DataType::Interval.to_string(), "Interval");
+        assert_eq!(DataType::Money.to_string(), "Money");
+    }
+
+    #[test]
+    fn to_sql_quotes_enum_labels() {
+        let data_type = DataType::Enum(vec!["ok".into(), "it's".into()]);
+        assert_eq!(data_type.to_sql(), "enum('ok', 'it''s')");
+    }
+
+    #[test]
+    fn timestamp_precision_orders_after_unconstrained() {
+        assert!(DataType::Timestamptz(None) < DataType::Timestamptz(Some(0)));
+        assert!(DataType::Timestamptz(Some(3)) < DataType::Timestamptz(Some(6)));
+        assert_ne!(DataType::Timestamp(Some(6)), DataType::Timestamptz(Some(6)));
+    }
+
+    #[rstest]
+    #[case(DataType::Timestamptz(Some(3)), DataType::Date, true)]
+    #[case(DataType::Integer(None), DataType::Numeric(10, 2), true)]
+    #[case(DataType::VarChar(Some(64)), DataType::Enum(vec!["ok".into()]), true)]
+    #[case(DataType::Other("ltree".into()), DataType::Other("ltree".into()), true)]
+    #[case(DataType::Timestamp(None), DataType::Text(None), false)]
+    #[case(DataType::Time(None), DataType::Timestamp(None), false)]
+    #[case(DataType::Other("ltree".into()), DataType::Other("hstore".into()), false)]
+    #[case(DataType::Unknown, DataType::Unknown, false)]
+    fn comparable_types(#[case] a: DataType, #[case] b: DataType, #[case] expected: bool) {
+        assert_eq!(a.is_comparable_with(&b), expected);
+        assert_eq!(b.is_comparable_with(&a), expected);
+    }
+
+    #[test]
+    fn display_spatial_types() {
+        assert_eq!(
+            DataType::Geometry(Some("Point".into())).to_string(),
+            "Geometry(Point)"
+        );
+        assert_eq!(DataType::Geography(None).to_string(), "Geography(None)");
+    }
+
+    #[test]
+    fn other_keeps_the_raw_name() {
+        let data_type = DataType::Other("geometry(Point,4326)".into());
+        assert_eq!(data_type.to_sql(), "geometry(Point,4326)");
+        assert_eq!(data_type.to_string(), "Other(geometry(Point,4326))");
+    }
+}
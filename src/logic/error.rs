@@ -12,6 +12,9 @@ pub enum Error {
     #[error("Invalid query: {0}")]
     InvalidQuery(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
     #[error("Configuration error: {0}")]
     Config(String),
 
@@ -10,6 +10,18 @@ impl Validator {
     pub async fn sql(&self, sql: impl Into<SqlStr>) -> Result<PgStatement> {
         self.pool.prepare(sql.into()).await.map_err(Into::into)
     }
+
+    /// Execute `sql` inside a transaction that is always rolled back, and
+    /// return the number of affected rows.
+    ///
+    /// Unlike `sql`, this also surfaces runtime failures (constraint
+    /// violations, bad casts of actual values) without committing anything.
+    pub async fn dry_run(&self, sql: impl Into<SqlStr>) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let result = sqlx::query(sql.into()).execute(&mut *tx).await;
+        tx.rollback().await?;
+        Ok(result?.rows_affected())
+    }
 }
 
 #[cfg(test)]
@@ -47,6 +59,33 @@ mod tests {
         assert_eq!(actual_columns, columns);
     }
 
+    #[test_context(IsolatedIntegrationTest)]
+    #[tokio::test]
+    pub async fn dry_run_rolls_back(ctx: &mut IsolatedIntegrationTest) -> Result {
+        sqlx::query("CREATE TABLE notes (id int PRIMARY KEY)")
+            .execute(&ctx.pool)
+            .await?;
+        let validate = Validator {
+            pool: ctx.pool.clone(),
+        };
+
+        let inserted = validate
+            .dry_run(SqlStr::from_static("INSERT INTO notes VALUES (1), (2)"))
+            .await?;
+        assert_eq!(inserted, 2);
+        let (count,): (i64,) = sqlx::query_as("SELECT count(*) FROM notes")
+            .fetch_one(&ctx.pool)
+            .await?;
+        assert_eq!(count, 0);
+
+        // Runtime constraint violations surface as errors.
+        let result = validate
+            .dry_run(SqlStr::from_static("INSERT INTO notes VALUES (1), (1)"))
+            .await;
+        assert!(matches!(result, Err(Error::Database(_))), "got {result:?}");
+        Ok(())
+    }
+
     #[test_context(IsolatedIntegrationTest)]
     #[rstest]
     #[case("SELECT 1!", "42601", "syntax error at end of input", Original(10))]
@@ -0,0 +1,168 @@
+use super::*;
+use crate::*;
+
+/// Synchronous, fluent construction of a `Database` (fixtures, tests,
+/// snapshots), avoiding an `.await` per `insert_*` call.
+///
+/// ```ignore
+/// let db = Database::builder("postgres")
+///     .table("public", "users", [("id", DataType::Uuid)])
+///     .table("analytics", "events", [("user_id", DataType::Uuid)])
+///     .build();
+/// ```
+#[derive(Debug)]
+pub struct DatabaseBuilder {
+    database: Database,
+}
+
+impl DatabaseBuilder {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            database: Database::new(name),
+        }
+    }
+
+    /// Add an empty schema (no-op if it already exists).
+    pub fn schema(mut self, name: impl Into<String>) -> Self {
+        self.schema_mut(name.into());
+        self
+    }
+
+    /// Add (or overwrite) a table with the given columns, in order,
+    /// creating the schema if needed.
+    pub fn table(
+        self,
+        schema: impl Into<String>,
+        name: impl Into<String>,
+        columns: impl IntoIterator<Item = (impl Into<String>, DataType)>,
+    ) -> Self {
+        self.insert_table(schema, Table::new_with_ordered(name, columns))
+    }
+
+    /// Add (or overwrite) a fully built `Table`, creating the schema if needed.
+    pub fn insert_table(mut self, schema: impl Into<String>, table: Table) -> Self {
+        self.schema_mut(schema.into())
+            .tables
+            .get_mut()
+            .insert(table.name.clone(), table);
+        self
+    }
+
+    /// Add (or overwrite) a column, creating the schema and table if needed.
+    /// New columns are appended to the table's column order.
+    pub fn column(
+        mut self,
+        schema: impl Into<String>,
+        table: impl Into<String>,
+        column: Column,
+    ) -> Self {
+        let table = table.into();
+        let table = self
+            .schema_mut(schema.into())
+            .tables
+            .get_mut()
+            .entry(table.clone())
+            .or_insert_with(|| Table::new(table));
+        if !table.column_order.contains(&column.name) {
+            table.column_order.push(column.name.clone());
+        }
+        table.columns.get_mut().insert(column.name.clone(), column);
+        self
+    }
+
+    /// Register (or overwrite) a table function.
+    pub fn function(mut self, function: TableFunction) -> Self {
+        self.database
+            .functions
+            .get_mut()
+            .insert(function.name.to_ascii_lowercase(), function);
+        self
+    }
+
+    /// Replace the search path (see `Database::search_path`).
+    pub fn search_path(mut self, path: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.database = self.database.with_search_path(path);
+        self
+    }
+
+    pub fn build(self) -> Database {
+        self.database.bump_version();
+        self.database
+    }
+
+    fn schema_mut(&mut self, name: String) -> &mut Schema {
+        self.database
+            .schemas
+            .get_mut()
+            .entry(name.clone())
+            .or_insert_with(|| Schema::new(name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn builds_two_schemas_fluently() {
+        let db = Database::builder("postgres")
+            .table(
+                "public",
+                "users",
+                [("id", DataType::Uuid), ("email", DataType::Text(None))],
+            )
+            .table("analytics", "events", [("user_id", DataType::Uuid)])
+            .column(
+                "analytics",
+                "events",
+                Column::new("at", DataType::Timestamptz(None)),
+            )
+            .column(
+                "analytics",
+                "events",
+                Column::new("user_id", DataType::Text(None)).with_nullable(false),
+            )
+            .schema("empty")
+            .build();
+
+        assert_eq!(db.name, "postgres");
+        assert_ne!(db.version(), 0);
+        assert_eq!(db.schema_names().await, ["analytics", "empty", "public"]);
+        assert!(db.table_has_column("public", "users", "email").await);
+
+        let schemas = db.schemas.read().await;
+        let tables = schemas["analytics"].tables.read().await;
+        let events = &tables["events"];
+        assert_eq!(
+            events.ordered_columns().await,
+            [
+                ("user_id".to_string(), DataType::Text(None)),
+                ("at".to_string(), DataType::Timestamptz(None)),
+            ]
+        );
+        assert!(!events.columns.read().await["user_id"].nullable);
+    }
+
+    #[tokio::test]
+    async fn builds_functions_and_search_path() {
+        let db = Database::builder("postgres")
+            .function(TableFunction::new(
+                "Unnest",
+                [Column::new("value", DataType::Text(None))],
+            ))
+            .search_path(["app", "public"])
+            .build();
+        assert!(db.function_columns("unnest").await.is_some());
+        assert_eq!(db.search_path, ["app", "public"]);
+    }
+
+    #[tokio::test]
+    async fn defaults_are_empty() {
+        let db = Database::default();
+        assert_eq!(db.name, "");
+        assert!(db.schema_names().await.is_empty());
+        let schema = Schema::default();
+        assert_eq!(schema.name, "");
+        assert!(schema.tables.read().await.is_empty());
+    }
+}
@@ -5,6 +5,22 @@ use crate::*;
 pub struct Column {
     pub name: String,
     pub data_type: DataType,
+    /// Whether the column accepts `NULL` (columns are nullable unless stated otherwise).
+    pub nullable: bool,
+    /// Whether the column is part of the table's primary key.
+    pub primary_key: bool,
+    /// The column's default expression as written in the schema, if any.
+    pub default: Option<String>,
+    /// The column's comment (`COMMENT ON COLUMN ...`), if any.
+    pub comment: Option<String>,
+    /// Whether the column is marked deprecated (see `is_deprecated_comment`).
+    pub deprecated: bool,
+    /// Known keys of a JSON / JSONB column, suggested after `->` / `->>`.
+    /// Not introspected: registered by the caller (e.g. from sampled rows).
+    pub json_keys: Vec<String>,
+    /// Fields of a composite-typed column, suggested after `t.col.`; a field
+    /// may itself be composite. Not introspected: registered by the caller.
+    pub fields: Vec<Column>,
 }
 
 impl Column {
@@ -12,9 +28,58 @@ impl Column {
         Self {
             name: name.into(),
             data_type: data_type.into(),
+            nullable: true,
+            primary_key: false,
+            default: None,
+            comment: None,
+            deprecated: false,
+            json_keys: Vec::new(),
+            fields: Vec::new(),
         }
     }
 
+    pub fn with_nullable(mut self, nullable: bool) -> Self {
+        self.nullable = nullable;
+        self
+    }
+
+    pub fn with_primary_key(mut self, primary_key: bool) -> Self {
+        self.primary_key = primary_key;
+        self
+    }
+
+    pub fn with_default(mut self, default: impl Into<String>) -> Self {
+        self.default = Some(default.into());
+        self
+    }
+
+    pub fn with_comment(mut self, comment: impl Into<String>) -> Self {
+        self.comment = Some(comment.into());
+        self
+    }
+
+    pub fn with_deprecated(mut self, deprecated: bool) -> Self {
+        self.deprecated = deprecated;
+        self
+    }
+
+    pub fn with_json_keys(mut self, keys: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.json_keys = keys.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_fields(mut self, fields: impl IntoIterator<Item = Column>) -> Self {
+        self.fields = fields.into_iter().collect();
+        self
+    }
+
+    /// True if a column comment marks the column deprecated: it starts with
+    /// `DEPRECATED:` or contains an `@deprecated` tag (both case-insensitive).
+    pub fn is_deprecated_comment(comment: &str) -> bool {
+        let comment = comment.trim_start().to_ascii_lowercase();
+        comment.starts_with("deprecated:") || comment.contains("@deprecated")
+    }
+
     pub fn new_map(columns: impl Into<HashMap<String, DataType>>) -> HashMap<String, Self> {
         columns
             .into()
@@ -23,3 +88,21 @@ impl Column {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("DEPRECATED: use email_address", true)]
+    #[case("  deprecated: gone in v2", true)]
+    #[case("Legacy login. @deprecated", true)]
+    #[case("@Deprecated since 2024", true)]
+    #[case("Not deprecated: still in use", false)]
+    #[case("Display name", false)]
+    #[case("", false)]
+    fn deprecated_comment_convention(#[case] comment: &str, #[case] expected: bool) {
+        assert_eq!(Column::is_deprecated_comment(comment), expected);
+    }
+}
@@ -1,9 +1,24 @@
 use super::*;
+use crate::{Error, Result};
+use std::sync::atomic::{AtomicU64, Ordering};
 
 #[derive(Debug)]
 pub struct Database {
     pub name: String,
     pub schemas: Data<Schema>,
+    /// Table functions usable as FROM sources, keyed by lower-cased name.
+    pub functions: Data<TableFunction>,
+    /// Schemas searched first, in order, when a name is not schema-qualified.
+    /// Defaults to `public`, like Postgres.
+    pub search_path: Vec<String>,
+    /// Bumped on every mutation made through the `insert_*` methods.
+    version: AtomicU64,
+}
+
+impl Default for Database {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
 }
 
 impl Database {
@@ -11,11 +26,140 @@ impl Database {
         Self {
             name: name.into(),
             schemas: Data::new(HashMap::new()),
+            functions: Data::new(HashMap::new()),
+            search_path: vec!["public".into()],
+            version: AtomicU64::new(0),
+        }
+    }
+
+    pub fn with_search_path(mut self, path: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.search_path = path.into_iter().map(Into::into).collect();
+        self
+    }
+
+    /// The schemas of a `schemas` read guard in lookup order: those on the
+    /// search path in path order, then the rest sorted by name. Iterate in
+    /// this order (not `HashMap` order) so output is stable run-to-run.
+    pub fn search_order<'a>(&self, schemas: &'a HashMap<String, Schema>) -> Vec<&'a Schema> {
+        let on_path = self.search_path.iter().filter_map(|name| schemas.get(name));
+        let mut rest: Vec<&Schema> = schemas
+            .values()
+            .filter(|s| !self.search_path.contains(&s.name))
+            .collect();
+        rest.sort_by(|a, b| a.name.cmp(&b.name));
+        on_path.chain(rest).collect()
+    }
+
+    /// Construct a database from already built schemas, without locking.
+    pub fn with_schemas(
+        name: impl Into<String>,
+        schemas: impl IntoIterator<Item = Schema>,
+    ) -> Self {
+        let mut database = Self::new(name);
+        database
+            .schemas
+            .get_mut()
+            .extend(schemas.into_iter().map(|s| (s.name.clone(), s)));
+        database
+    }
+
+    /// Start a synchronous `DatabaseBuilder`.
+    pub fn builder(name: impl Into<String>) -> DatabaseBuilder {
+        DatabaseBuilder::new(name)
+    }
+
+    /// Current metadata version. Any change made through the `insert_*`
+    /// methods yields a different value, so it can key derived caches.
+    pub fn version(&self) -> u64 {
+        self.version.load(Ordering::Acquire)
+    }
+
+    /// Mark the metadata as changed. Call this after mutating `schemas` (or
+    /// nested tables / columns) directly instead of through `insert_*`.
+    pub fn bump_version(&self) {
+        self.version.fetch_add(1, Ordering::AcqRel);
+    }
+
+    /// Names of all schemas, sorted.
+    pub async fn schema_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.schemas.read().await.keys().cloned().collect();
+        names.sort();
+        names
+    }
+
+    /// True if a schema with exactly this name exists.
+    pub async fn has_schema(&self, name: &str) -> bool {
+        self.schemas.read().await.contains_key(name)
+    }
+
+    /// The schema a reference to `table` resolves to: `schema` if the query
+    /// qualifies it, else the first schema in `search_order` declaring the
+    /// table. `None` if there is no such table.
+    pub async fn table_schema(&self, schema: Option<&str>, table: &str) -> Option<String> {
+        let schemas = self.schemas.read().await;
+        let candidates = match schema {
+            Some(name) => schemas.get(name).into_iter().collect(),
+            None => self.search_order(&schemas),
+        };
+        for candidate in candidates {
+            if candidate.tables.read().await.contains_key(table) {
+                return Some(candidate.name.clone());
+            }
+        }
+        None
+    }
+
+    /// A copy of `schema.table` with its columns in order.
+    ///
+    /// A missing schema or table is `Error::NotFound`, so callers can tell it
+    /// apart from a table that exists without columns (empty `columns`).
+    pub async fn get_table(&self, schema: &str, table: &str) -> Result<TableView> {
+        let not_found = || Error::NotFound(format!("table {schema}.{table}"));
+        let schemas = self.schemas.read().await;
+        let tables = schemas
+            .get(schema)
+            .ok_or_else(not_found)?
+            .tables
+            .read()
+            .await;
+        let t = tables.get(table).ok_or_else(not_found)?;
+        let columns = t.columns.read().await;
+        Ok(TableView {
+            schema: schema.to_string(),
+            name: t.name.clone(),
+            columns: t
+                .column_order
+                .iter()
+                .filter_map(|n| columns.get(n).cloned())
+                .collect(),
+        })
+    }
+
+    /// True if `schema.table` exists and has column `column` (see
+    /// `Table::has_column` for case folding).
+    pub async fn table_has_column(&self, schema: &str, table: &str, column: &str) -> bool {
+        let schemas = self.schemas.read().await;
+        let Some(schema) = schemas.get(schema) else {
+            return false;
+        };
+        let tables = schema.tables.read().await;
+        match tables.get(table) {
+            Some(table) => table.has_column(column).await,
+            None => false,
         }
     }
 
+    /// The output columns of the table function `name` (case-insensitive),
+    /// if registered.
+    pub async fn function_columns(&self, name: &str) -> Option<Vec<Column>> {
+        let functions = self.functions.read().await;
+        functions
+            .get(&name.to_ascii_lowercase())
+            .map(|f| f.columns.clone())
+    }
+
     /// Add (or create) schema/table and insert the column.
-    pub async fn insert_column(&mut self, schema_name: String, table_name: String, column: Column) {
+    pub async fn insert_column(&self, schema_name: String, table_name: String, column: Column) {
         let mut schemas = self.schemas.write().await;
         schemas
             .entry(schema_name.clone())
@@ -29,10 +173,11 @@ impl Database {
             .write()
             .await
             .insert(column.name.clone(), column); // Insert / overwrite column
+        self.bump_version();
     }
 
     /// Add (or create) schema and insert the table.
-    pub async fn insert_table(&mut self, schema_name: impl Display, table: Table) {
+    pub async fn insert_table(&self, schema_name: impl Display, table: Table) {
         let mut schemas = self.schemas.write().await;
         schemas
             .entry(schema_name.to_string())
@@ -41,13 +186,243 @@ impl Database {
             .write()
             .await
             .insert(table.name.clone(), table); // Insert / overwrite table
+        self.bump_version();
+    }
+
+    /// Register (or overwrite) a table function.
+    pub async fn insert_function(&self, function: TableFunction) {
+        self.functions
+            .write()
+            .await
+            .insert(function.name.to_ascii_lowercase(), function);
+        self.bump_version();
+    }
+
+    /// Rename schema `old` to `new`, moving all of its tables and updating
+    /// `Schema::name` and any `search_path` entry.
+    ///
+    /// Returns `false` without changing anything when `old` does not exist or
+    /// another schema is already named `new` (schemas are never merged).
+    pub async fn rename_schema(&mut self, old: &str, new: &str) -> bool {
+        let schemas = self.schemas.get_mut();
+        if old == new {
+            return schemas.contains_key(old);
+        }
+        if schemas.contains_key(new) {
+            return false;
+        }
+        let Some(mut schema) = schemas.remove(old) else {
+            return false;
+        };
+        schema.name = new.to_string();
+        schemas.insert(new.to_string(), schema);
+        for entry in self.search_path.iter_mut().filter(|p| *p == old) {
+            *entry = new.to_string();
+        }
+        self.bump_version();
+        true
     }
 
     /// Insert (or overwrite) a schema.
-    pub async fn insert_schema(&mut self, schema: Schema) {
+    pub async fn insert_schema(&self, schema: Schema) {
         self.schemas
             .write()
             .await
             .insert(schema.name.clone(), schema);
+        self.bump_version();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DataType;
+
+    async fn multi_schema() -> Database {
+        let db = Database::new("postgres");
+        db.insert_table(
+            "public",
+            Table::new_with_ordered("users", [("id", DataType::Uuid)]),
+        )
+        .await;
+        db.insert_table("analytics", Table::new("events")).await;
+        db.insert_schema(Schema::new("empty")).await;
+        db
+    }
+
+    #[tokio::test]
+    async fn with_schemas_builds_synchronously() {
+        let db = Database::with_schemas(
+            "postgres",
+            [
+                Schema::with_tables(
+                    "public",
+                    [Table::new_with_ordered("users", [("id", DataType::Uuid)])],
+                ),
+                Schema::with_tables(
+                    "analytics",
+                    [
+                        Table::new("events"),
+                        Table::new_with_ordered("visits", [("at", DataType::Date)]),
+                    ],
+                ),
+            ],
+        );
+        assert_eq!(db.schema_names().await, ["analytics", "public"]);
+        assert!(db.table_has_column("public", "users", "id").await);
+        assert!(db.table_has_column("analytics", "visits", "at").await);
+        let schemas = db.schemas.read().await;
+        let mut tables: Vec<_> = schemas["analytics"]
+            .tables
+            .read()
+            .await
+            .keys()
+            .cloned()
+            .collect();
+        tables.sort();
+        assert_eq!(tables, ["events", "visits"]);
+    }
+
+    #[tokio::test]
+    async fn schema_names_are_sorted() {
+        let db = multi_schema().await;
+        assert_eq!(db.schema_names().await, ["analytics", "empty", "public"]);
+        assert!(Database::new("postgres").schema_names().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn search_order_follows_path_then_name() {
+        let order = |db: &Database, schemas: &HashMap<String, Schema>| {
+            db.search_order(schemas)
+                .iter()
+                .map(|s| s.name.clone())
+                .collect::<Vec<_>>()
+        };
+        let db = multi_schema().await;
+        assert_eq!(
+            order(&db, &*db.schemas.read().await),
+            ["public", "analytics", "empty"]
+        );
+
+        let db = multi_schema()
+            .await
+            .with_search_path(["empty", "missing", "public"]);
+        assert_eq!(
+            order(&db, &*db.schemas.read().await),
+            ["empty", "public", "analytics"]
+        );
+    }
+
+    #[tokio::test]
+    async fn table_schema_skips_empty_schemas() {
+        let db = multi_schema().await.with_search_path(["empty", "public"]);
+        assert_eq!(
+            db.table_schema(None, "users").await.as_deref(),
+            Some("public")
+        );
+        assert_eq!(
+            db.table_schema(None, "events").await.as_deref(),
+            Some("analytics")
+        );
+        assert_eq!(db.table_schema(Some("empty"), "users").await, None);
+        assert_eq!(db.table_schema(Some("missing"), "users").await, None);
+        assert_eq!(db.table_schema(None, "missing").await, None);
+    }
+
+    #[tokio::test]
+    async fn has_schema_is_exact() {
+        let db = multi_schema().await;
+        assert!(db.has_schema("analytics").await);
+        assert!(db.has_schema("empty").await);
+        assert!(!db.has_schema("Analytics").await);
+        assert!(!db.has_schema("users").await);
+    }
+
+    #[tokio::test]
+    async fn function_columns_are_case_insensitive() {
+        let db = Database::new("postgres");
+        let version = db.version();
+        db.insert_function(TableFunction::new(
+            "unnest",
+            [Column::new("value", DataType::Text(None))],
+        ))
+        .await;
+        assert_ne!(db.version(), version);
+
+        let names =
+            |c: Option<Vec<Column>>| c.map(|c| c.into_iter().map(|c| c.name).collect::<Vec<_>>());
+        assert_eq!(
+            names(db.function_columns("unnest").await),
+            Some(vec!["value".into()])
+        );
+        assert_eq!(
+            names(db.function_columns("UNNEST").await),
+            Some(vec!["value".into()])
+        );
+        assert_eq!(names(db.function_columns("generate_series").await), None);
+    }
+
+    #[tokio::test]
+    async fn rename_schema_moves_tables() {
+        let mut db = multi_schema().await;
+        let version = db.version();
+        assert!(db.rename_schema("public", "app").await);
+        assert_ne!(db.version(), version);
+        assert_eq!(db.schema_names().await, ["analytics", "app", "empty"]);
+        assert_eq!(db.schemas.read().await["app"].name, "app");
+        assert!(db.table_has_column("app", "users", "id").await);
+        assert!(!db.table_has_column("public", "users", "id").await);
+        assert_eq!(db.search_path, ["app"]);
+        assert_eq!(db.table_schema(None, "users").await.as_deref(), Some("app"));
+    }
+
+    #[tokio::test]
+    async fn rename_schema_rejects_missing_and_taken_names() {
+        let mut db = multi_schema().await;
+        let version = db.version();
+        assert!(!db.rename_schema("missing", "other").await);
+        assert!(!db.rename_schema("analytics", "public").await);
+        assert!(db.rename_schema("analytics", "analytics").await);
+        assert_eq!(db.version(), version);
+        assert_eq!(db.schema_names().await, ["analytics", "empty", "public"]);
+        assert!(db.table_has_column("public", "users", "id").await);
+    }
+
+    #[tokio::test]
+    async fn get_table_distinguishes_missing_from_empty() {
+        let db = multi_schema().await;
+        let users = db.get_table("public", "users").await.expect("populated");
+        assert_eq!(
+            (users.schema.as_str(), users.name.as_str()),
+            ("public", "users")
+        );
+        let names: Vec<_> = users.columns.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["id"]);
+
+        let events = db.get_table("analytics", "events").await.expect("empty");
+        assert!(events.columns.is_empty());
+
+        for (schema, table) in [
+            ("public", "missing"),
+            ("missing", "users"),
+            ("empty", "users"),
+        ] {
+            let result = db.get_table(schema, table).await;
+            assert!(
+                matches!(result, Err(Error::NotFound(_))),
+                "{schema}.{table}: {result:?}"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn table_has_column_checks_schema_and_table() {
+        let db = multi_schema().await;
+        assert!(db.table_has_column("public", "users", "id").await);
+        assert!(db.table_has_column("public", "users", "Id").await);
+        assert!(!db.table_has_column("public", "users", "email").await);
+        assert!(!db.table_has_column("analytics", "users", "id").await);
+        assert!(!db.table_has_column("public", "events", "id").await);
+        assert!(!db.table_has_column("missing", "users", "id").await);
     }
 }
@@ -0,0 +1,586 @@
+//! Replaying DDL statements onto in-memory metadata.
+//!
+//! `Database::apply_ddl` keeps completion current while migrations are
+//! authored, without a live connection. Only the statements that change
+//! what completion can see are understood; everything else is skipped.
+
+use super::*;
+use crate::sql::{token::Token, token_kind::TokenKind, tokenizer::tokenize};
+use crate::*;
+
+/// Words that end a column's type and start one of its constraints.
+const COLUMN_CONSTRAINTS: [&str; 10] = [
+    "not",
+    "null",
+    "default",
+    "primary",
+    "unique",
+    "references",
+    "check",
+    "constraint",
+    "collate",
+    "generated",
+];
+
+/// Words opening a table constraint rather than a column definition.
+const TABLE_CONSTRAINTS: [&str; 7] = [
+    "constraint",
+    "primary",
+    "unique",
+    "foreign",
+    "check",
+    "exclude",
+    "like",
+];
+
+impl Database {
+    /// Apply the effect of the `;`-separated DDL statements in `ddl` to this
+    /// metadata, in order.
+    ///
+    /// Supported statements:
+    /// - `CREATE TABLE [IF NOT EXISTS] name (column type [constraints], ...)`
+    /// - `ALTER TABLE [IF EXISTS] [ONLY] name ADD [COLUMN] [IF NOT EXISTS] ...`
+    ///   and `DROP [COLUMN] [IF EXISTS] column`, comma separated
+    /// - `DROP TABLE [IF EXISTS] name, ...`
+    ///
+    /// Unqualified names go to the first `search_path` schema on creation and
+    /// are looked up along the search path otherwise; unquoted names fold to
+    /// lowercase. Types map through `DataType::from_pg_str` (accepting common
+    /// aliases such as `int`, `varchar` and `timestamptz`), falling back to
+    /// `DataType::Other`. `NOT NULL`, `PRIMARY KEY` and serial types make a
+    /// column non-nullable, a column's `PRIMARY KEY` also marks it
+    /// `Column::primary_key`, and `DEFAULT` sets its default.
+    ///
+    /// Other statements, table constraints and other `ALTER TABLE` actions are
+    /// skipped. Altering or dropping a missing table or column without `IF
+    /// EXISTS` is an `Error::InvalidQuery`; statements before the failing
+    /// one stay applied.
+    pub fn apply_ddl(&mut self, ddl: &str) -> Result<()> {
+        let tokens = tokenize(ddl);
+        let mut changed = false;
+        let mut result = Ok(());
+        for statement in split_top_level(&tokens, TokenKind::Other(';')) {
+            match self.apply_statement(&mut Parser::new(ddl, statement)) {
+                Ok(applied) => changed |= applied,
+                Err(e) => {
+                    result = Err(e);
+                    break;
+                }
+            }
+        }
+        if changed {
+            self.bump_version();
+        }
+        result
+    }
+
+    /// Apply one statement, returning whether the metadata changed.
+    fn apply_statement(&mut self, p: &mut Parser) -> Result<bool> {
+        if p.eat(&["create"]) {
+            p.eat_any(&["temp", "temporary", "unlogged"]);
+            if !p.eat(&["table"]) {
+                return Ok(false);
+            }
+            let if_not_exists = p.eat(&["if", "not", "exists"]);
+            let (schema, name) = p.qualified_name()?;
+            let schema = schema.unwrap_or_else(|| self.default_schema());
+            let exists = self.table_mut(&schema, &name).is_some();
+            if exists && if_not_exists {
+                return Ok(false);
+            }
+            let Some(body) = p.group() else {
+                return Err(Error::InvalidQuery(format!(
+                    "CREATE TABLE {name} has no column list"
+                )));
+            };
+            let columns = split_top_level(body, TokenKind::Comma)
+                .into_iter()
+                .filter_map(|def| column_definition(&mut Parser::new(p.sql, def)))
+                .collect::<Vec<_>>();
+            self.schemas
+                .get_mut()
+                .entry(schema.clone())
+                .or_insert_with(|| Schema::new(schema))
+                .tables
+                .get_mut()
+                .insert(name.clone(), Table::new_from_columns(name, columns));
+            return Ok(true);
+        }
+
+        if p.eat(&["alter", "table"]) {
+            let if_exists = p.eat(&["if", "exists"]);
+            p.eat(&["only"]);
+            let (schema, name) = p.qualified_name()?;
+            let Some(table) = self.lookup_table(schema.as_deref(), &name) else {
+                return match if_exists {
+                    true => Ok(false),
+                    false => Err(Error::InvalidQuery(format!(
+                        "ALTER TABLE: unknown table {name}"
+                    ))),
+                };
+            };
+            let sql = p.sql;
+            let actions = split_top_level(p.rest(), TokenKind::Comma);
+            let mut changed = false;
+            for action in actions {
+                changed |= alter_column(table, &mut Parser::new(sql, action))?;
+            }
+            return Ok(changed);
+        }
+
+        if p.eat(&["drop", "table"]) {
+            let if_exists = p.eat(&["if", "exists"]);
+            let sql = p.sql;
+            let mut changed = false;
+            for item in split_top_level(p.rest(), TokenKind::Comma) {
+                let (schema, name) = Parser::new(sql, item).qualified_name()?;
+                let schema = schema.or_else(|| self.schema_of(&name));
+                let dropped = schema
+                    .and_then(|s| self.schemas.get_mut().get_mut(&s))
+                    .and_then(|s| s.tables.get_mut().remove(&name))
+                    .is_some();
+                if !dropped && !if_exists {
+                    return Err(Error::InvalidQuery(format!(
+                        "DROP TABLE: unknown table {name}"
+                    )));
+                }
+                changed |= dropped;
+            }
+            return Ok(changed);
+        }
+        Ok(false)
+    }
+
+    /// Schema new unqualified tables are created in.
+    fn default_schema(&self) -> String {
+        self.search_path
+            .first()
+            .cloned()
+            .unwrap_or_else(|| "public".to_string())
+    }
+
+    /// First schema along the search path (then any other) holding `table`.
+    fn schema_of(&mut self, table: &str) -> Option<String> {
+        let path = &self.search_path;
+        self.schemas
+            .get_mut()
+            .iter_mut()
+            .filter_map(|(name, schema)| {
+                schema.tables.get_mut().contains_key(table).then_some(name)
+            })
+            .min_by_key(|name| {
+                (
+                    path.iter().position(|p| p == *name).unwrap_or(usize::MAX),
+                    *name,
+                )
+            })
+            .cloned()
+    }
+
+    fn lookup_table(&mut self, schema: Option<&str>, name: &str) -> Option<&mut Table> {
+        let schema = match schema {
+            Some(schema) => schema.to_string(),
+            None => self.schema_of(name)?,
+        };
+        self.table_mut(&schema, name)
+    }
+
+    fn table_mut(&mut self, schema: &str, name: &str) -> Option<&mut Table> {
+        self.schemas
+            .get_mut()
+            .get_mut(schema)?
+            .tables
+            .get_mut()
+            .get_mut(name)
+    }
+}
+
+/// Apply one `ALTER TABLE` action to `table`, returning whether it changed.
+fn alter_column(table: &mut Table, p: &mut Parser) -> Result<bool> {
+    if p.eat(&["add"]) {
+        if p.word()
+            .is_some_and(|w| TABLE_CONSTRAINTS.contains(&w.as_str()))
+        {
+            return Ok(false);
+        }
+        p.eat(&["column"]);
+        let if_not_exists = p.eat(&["if", "not", "exists"]);
+        let Some(column) = column_definition(p) else {
+            return Err(Error::InvalidQuery(
+                "ALTER TABLE ADD: missing column".to_string(),
+            ));
+        };
+        let exists = table.columns.get_mut().contains_key(&column.name);
+        if exists && if_not_exists {
+            return Ok(false);
+        }
+        if !exists {
+            table.column_order.push(column.name.clone());
+        }
+        table.columns.get_mut().insert(column.name.clone(), column);
+        return Ok(true);
+    }
+    if p.eat(&["drop"]) {
+        if p.eat(&["constraint"]) {
+            return Ok(false);
+        }
+        p.eat(&["column"]);
+        let if_exists = p.eat(&["if", "exists"]);
+        let name = p.name()?;
+        if table.columns.get_mut().remove(&name).is_none() {
+            return match if_exists {
+                true => Ok(false),
+                false => Err(Error::InvalidQuery(format!(
+                    "ALTER TABLE DROP: unknown column {name}"
+                ))),
+            };
+        }
+        table.column_order.retain(|c| *c != name);
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// Parse `name type [constraints]`; `None` for table constraints.
+fn column_definition(p: &mut Parser) -> Option<Column> {
+    if p.word()
+        .is_some_and(|w| TABLE_CONSTRAINTS.contains(&w.as_str()))
+    {
+        return None;
+    }
+    let name = p.name().ok()?;
+    let type_tokens = p.until(&COLUMN_CONSTRAINTS);
+    let type_text = p.text(type_tokens);
+    let data_type = ddl_type(&type_text);
+    let mut column = Column::new(name, data_type);
+    column.nullable = !is_serial(&type_text);
+    while let Some(word) = p.word() {
+        match word.as_str() {
+            "not" if p.eat(&["not", "null"]) => column.nullable = false,
+            "primary" if p.eat(&["primary", "key"]) => {
+                column.nullable = false;
+                column.primary_key = true;
+            }
+            "null" => {
+                p.bump();
+                column.nullable = true;
+            }
+            "default" => {
+                p.bump();
+                let expr = p.until(&COLUMN_CONSTRAINTS);
+                column.default = Some(p.text(expr)).filter(|d| !d.is_empty());
+            }
+            _ => {
+                p.bump();
+                p.until(&COLUMN_CONSTRAINTS);
+            }
+        }
+    }
+    Some(column)
+}
+
+fn is_serial(type_text: &str) -> bool {
+    matches!(
+        type_text.to_ascii_lowercase().as_str(),
+        "serial" | "serial2" | "serial4" | "serial8" | "smallserial" | "bigserial"
+    )
+}
+
+/// Map a type as written in DDL to a `DataType`, translating the aliases
+/// `format_type()` never prints.
+fn ddl_type(type_text: &str) -> DataType {
+    let lower = type_text.to_ascii_lowercase();
+    let (base, rest) = match lower.find('(') {
+        Some(i) => (lower[..i].trim(), &lower[i..]),
+        None => (lower.as_str(), ""),
+    };
+    let canonical = match base {
+        "int" | "int4" | "serial" | "serial4" => "integer",
+        "int2" | "smallserial" | "serial2" => "smallint",
+        "int8" | "bigserial" | "serial8" => "bigint",
+        "bool" => "boolean",
+        "varchar" => "character varying",
+        "char" | "bpchar" => "character",
+        "float" | "float8" => "double precision",
+        "float4" => "real",
+        "decimal" => "numeric",
+        "timestamptz" => "timestamp with time zone",
+        "timetz" => "time with time zone",
+        other => other,
+    };
+    DataType::from_pg_str(&format!("{canonical}{rest}"))
+        .unwrap_or_else(|| DataType::Other(type_text.to_string()))
+}
+
+/// Split `tokens` on `separator` outside parentheses.
+fn split_top_level(tokens: &[Token], separator: TokenKind) -> Vec<&[Token]> {
+    let mut parts = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, t) in tokens.iter().enumerate() {
+        match &t.kind {
+            TokenKind::ParenOpen => depth += 1,
+            TokenKind::ParenClose => depth -= 1,
+            kind if depth == 0 && *kind == separator => {
+                parts.push(&tokens[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    parts.push(&tokens[start..]);
+    parts.retain(|p| !p.is_empty());
+    parts
+}
+
+/// Cursor over the tokens of one statement (or part of one).
+struct Parser<'a> {
+    sql: &'a str,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn new(sql: &'a str, tokens: &'a [Token]) -> Self {
+        Self {
+            sql,
+            tokens,
+            pos: 0,
+        }
+    }
+
+    /// The current token as a lowercase word (identifier or keyword).
+    fn word(&self) -> Option<String> {
+        match &self.tokens.get(self.pos)?.kind {
+            TokenKind::Ident(w) => Some(w.to_ascii_lowercase()),
+            TokenKind::Keyword(k) => Some(k.as_str().to_string()),
+            _ => None,
+        }
+    }
+
+    fn bump(&mut self) {
+        self.pos += 1;
+    }
+
+    /// Consume `words` if the input continues with all of them.
+    fn eat(&mut self, words: &[&str]) -> bool {
+        let start = self.pos;
+        for w in words {
+            if self.word().as_deref() != Some(*w) {
+                self.pos = start;
+                return false;
+            }
+            self.bump();
+        }
+        true
+    }
+
+    /// Consume one of `words`, if present.
+    fn eat_any(&mut self, words: &[&str]) {
+        if self.word().is_some_and(|w| words.contains(&w.as_str())) {
+            self.bump();
+        }
+    }
+
+    /// An identifier: `"Quoted"` keeps its case, a bare word is lowercased.
+    fn name(&mut self) -> Result<String> {
+        let missing = || Error::InvalidQuery(format!("expected a name in {:?}", self.sql));
+        let token = self.tokens.get(self.pos).ok_or_else(missing)?;
+        if token.kind != TokenKind::Other('"') {
+            let word = self.word().ok_or_else(missing)?;
+            self.bump();
+            return Ok(word);
+        }
+        // `""` inside a quoted name is an escaped quote.
+        let mut close = self.pos + 1;
+        loop {
+            let t = self.tokens.get(close).ok_or_else(missing)?;
+            if t.kind == TokenKind::Other('"') {
+                match self.tokens.get(close + 1) {
+                    Some(next) if next.kind == TokenKind::Other('"') && next.start == t.end => {
+                        close += 2
+                    }
+                    _ => break,
+                }
+            } else {
+                close += 1;
+            }
+        }
+        let name = self.sql[token.end..self.tokens[close].start].replace("\"\"", "\"");
+        self.pos = close + 1;
+        Ok(name)
+    }
+
+    /// `name` or `schema.name`.
+    fn qualified_name(&mut self) -> Result<(Option<String>, String)> {
+        let first = self.name()?;
+        if self
+            .tokens
+            .get(self.pos)
+            .is_some_and(|t| t.kind == TokenKind::Dot)
+        {
+            self.bump();
+            return Ok((Some(first), self.name()?));
+        }
+        Ok((None, first))
+    }
+
+    /// Consume a parenthesized group, returning the tokens inside it.
+    fn group(&mut self) -> Option<&'a [Token]> {
+        if self.tokens.get(self.pos)?.kind != TokenKind::ParenOpen {
+            return None;
+        }
+        let mut depth = 0;
+        for (i, t) in self.tokens.iter().enumerate().skip(self.pos) {
+            match t.kind {
+                TokenKind::ParenOpen => depth += 1,
+                TokenKind::ParenClose => depth -= 1,
+                _ => {}
+            }
+            if depth == 0 {
+                let inner = &self.tokens[self.pos + 1..i];
+                self.pos = i + 1;
+                return Some(inner);
+            }
+        }
+        None
+    }
+
+    /// Consume tokens up to (not including) the next top-level word in `stop`.
+    fn until(&mut self, stop: &[&str]) -> &'a [Token] {
+        let start = self.pos;
+        let mut depth = 0;
+        while let Some(t) = self.tokens.get(self.pos) {
+            match t.kind {
+                TokenKind::ParenOpen => depth += 1,
+                TokenKind::ParenClose => depth -= 1,
+                _ if depth == 0 && self.word().is_some_and(|w| stop.contains(&w.as_str())) => break,
+                _ => {}
+            }
+            self.bump();
+        }
+        &self.tokens[start..self.pos]
+    }
+
+    /// The remaining tokens, consuming them.
+    fn rest(&mut self) -> &'a [Token] {
+        let rest = &self.tokens[self.pos.min(self.tokens.len())..];
+        self.pos = self.tokens.len();
+        rest
+    }
+
+    /// Source text spanned by `tokens`, with whitespace runs collapsed.
+    fn text(&self, tokens: &[Token]) -> String {
+        match (tokens.first(), tokens.last()) {
+            (Some(first), Some(last)) => self.sql[first.start..last.end]
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" "),
+            _ => String::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn columns(db: &Database, schema: &str, table: &str) -> Vec<(String, DataType)> {
+        let schemas = db.schemas.read().await;
+        let tables = schemas[schema].tables.read().await;
+        tables[table].ordered_columns().await
+    }
+
+    #[tokio::test]
+    async fn applies_create_then_alter() {
+        let mut db = Database::new("postgres");
+        db.apply_ddl(
+            "CREATE TABLE users (
+                id serial PRIMARY KEY,
+                email varchar(255) NOT NULL UNIQUE,
+                -- legacy column
+                nickname text DEFAULT 'n/a, none',
+                created_at timestamptz DEFAULT now(),
+                CONSTRAINT email_lower CHECK (email = lower(email))
+            );",
+        )
+        .unwrap();
+        let version = db.version();
+        db.apply_ddl(
+            "ALTER TABLE public.users ADD COLUMN score numeric(10, 2), DROP COLUMN nickname;
+             ALTER TABLE users ADD IF NOT EXISTS score int;",
+        )
+        .unwrap();
+        assert!(db.version() > version);
+
+        assert_eq!(
+            columns(&db, "public", "users").await,
+            [
+                ("id".to_string(), DataType::Integer(None)),
+                ("email".to_string(), DataType::VarChar(Some(255))),
+                ("created_at".to_string(), DataType::Timestamptz(None)),
+                ("score".to_string(), DataType::Numeric(10, 2)),
+            ]
+        );
+        let schemas = db.schemas.read().await;
+        let tables = schemas["public"].tables.read().await;
+        let cols = tables["users"].columns.read().await;
+        assert!(!cols["id"].nullable);
+        assert!(cols["id"].primary_key);
+        assert!(!cols["email"].nullable);
+        assert!(!cols["email"].primary_key);
+        assert!(cols["score"].nullable);
+        assert_eq!(cols["created_at"].default.as_deref(), Some("now()"));
+    }
+
+    #[tokio::test]
+    async fn creates_in_schema_and_drops_tables() {
+        let mut db = Database::new("postgres");
+        db.apply_ddl(
+            r#"CREATE TABLE IF NOT EXISTS app."Events" ("Kind" "MyEnum", payload jsonb);
+               CREATE TABLE logs (line text);
+               CREATE INDEX logs_line ON logs (line);"#,
+        )
+        .unwrap();
+        assert_eq!(
+            columns(&db, "app", "Events").await,
+            [
+                ("Kind".to_string(), DataType::Other("\"MyEnum\"".into())),
+                ("payload".to_string(), DataType::Json),
+            ]
+        );
+        db.apply_ddl("DROP TABLE IF EXISTS logs, missing CASCADE")
+            .unwrap();
+        assert!(
+            db.schemas.read().await["public"]
+                .tables
+                .read()
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn rejects_unknown_targets() {
+        let mut db = Database::new("postgres");
+        db.apply_ddl("CREATE TABLE a (id int)").unwrap();
+        for ddl in [
+            "ALTER TABLE missing ADD COLUMN x int",
+            "ALTER TABLE a DROP COLUMN missing",
+            "DROP TABLE missing",
+        ] {
+            assert!(
+                matches!(db.apply_ddl(ddl), Err(Error::InvalidQuery(_))),
+                "{ddl}"
+            );
+        }
+        db.apply_ddl(
+            "ALTER TABLE IF EXISTS missing ADD x int; ALTER TABLE a DROP IF EXISTS missing",
+        )
+        .unwrap();
+        assert_eq!(
+            columns(&db, "public", "a").await,
+            [("id".to_string(), DataType::Integer(None))]
+        );
+    }
+}
@@ -0,0 +1,51 @@
+use super::*;
+use crate::*;
+
+/// A set-returning function usable as a FROM source (`FROM unnest(arr) u`)
+/// and the columns its rows have.
+#[derive(Clone, Debug)]
+pub struct TableFunction {
+    pub name: String,
+    /// Output columns in order.
+    pub columns: Vec<Column>,
+}
+
+impl TableFunction {
+    pub fn new(name: impl Into<String>, columns: impl IntoIterator<Item = Column>) -> Self {
+        Self {
+            name: name.into(),
+            columns: columns.into_iter().collect(),
+        }
+    }
+
+    /// Common Postgres table functions whose output shape does not depend on
+    /// a column definition list. A single-column function names its column
+    /// after itself (`generate_series(1, 10) g` yields `g.generate_series`).
+    pub fn common() -> Vec<Self> {
+        vec![
+            Self::new(
+                "generate_series",
+                [Column::new("generate_series", DataType::BigInt(None))],
+            ),
+            Self::new(
+                "generate_subscripts",
+                [Column::new("generate_subscripts", DataType::Integer(None))],
+            ),
+            Self::new("unnest", [Column::new("unnest", DataType::Unknown)]),
+            Self::new(
+                "json_each",
+                [
+                    Column::new("key", DataType::Text(None)),
+                    Column::new("value", DataType::Json),
+                ],
+            ),
+            Self::new(
+                "json_each_text",
+                [
+                    Column::new("key", DataType::Text(None)),
+                    Column::new("value", DataType::Text(None)),
+                ],
+            ),
+        ]
+    }
+}
@@ -0,0 +1,226 @@
+//! Loading metadata from a live Postgres database.
+//!
+//! Columns are read from `pg_catalog` (tables, views, materialized views and
+//! foreign tables) in declaration order, together with nullability, default
+//! and comment; a comment following the deprecation convention (see
+//! `Column::is_deprecated_comment`) marks the column deprecated. Enum types
+//! are resolved to their labels through `pg_enum`.
+
+use super::*;
+use crate::*;
+use sqlx::PgPool;
+
+/// Non-system schemas of the connected database.
+const USER_SCHEMAS: &str = "
+    SELECT nspname
+    FROM pg_namespace
+    WHERE nspname NOT IN ('pg_catalog', 'information_schema')
+      AND nspname NOT LIKE 'pg\\_toast%'
+      AND nspname NOT LIKE 'pg\\_temp\\_%'
+    ORDER BY nspname";
+
+/// Which of the requested schemas exist.
+const EXISTING_SCHEMAS: &str = "
+    SELECT nspname FROM pg_namespace WHERE nspname = ANY($1) ORDER BY nspname";
+
+/// Enum types (as rendered by `format_type`) with their labels in sort order.
+const ENUM_TYPES: &str = "
+    SELECT format_type(t.oid, NULL), array_agg(e.enumlabel::text ORDER BY e.enumsortorder)
+    FROM pg_enum e
+    JOIN pg_type t ON t.oid = e.enumtypid
+    GROUP BY t.oid";
+
+/// Columns of the requested schemas in declaration order.
+const COLUMNS: &str = "
+    SELECT n.nspname, c.relname, a.attname,
+           format_type(a.atttypid, a.atttypmod),
+           NOT a.attnotnull,
+           EXISTS (
+               SELECT 1 FROM pg_index i
+               WHERE i.indrelid = c.oid AND i.indisprimary AND a.attnum = ANY(i.indkey)
+           ),
+           pg_get_expr(d.adbin, d.adrelid),
+           col_description(c.oid, a.attnum)
+    FROM pg_attribute a
+    JOIN pg_class c ON c.oid = a.attrelid
+    JOIN pg_namespace n ON n.oid = c.relnamespace
+    LEFT JOIN pg_attrdef d ON d.adrelid = a.attrelid AND d.adnum = a.attnum
+    WHERE a.attnum > 0
+      AND NOT a.attisdropped
+      AND c.relkind IN ('r', 'p', 'v', 'm', 'f')
+      AND n.nspname = ANY($1)
+    ORDER BY n.nspname, c.relname, a.attnum";
+
+type ColumnRow = (
+    String,
+    String,
+    String,
+    String,
+    bool,
+    bool,
+    Option<String>,
+    Option<String>,
+);
+
+impl Database {
+    /// Introspect every non-system schema of the database `pool` connects to.
+    pub async fn introspect(pool: &PgPool) -> Result<Self> {
+        let schemas: Vec<String> = sqlx::query_scalar(USER_SCHEMAS).fetch_all(pool).await?;
+        let schemas: Vec<&str> = schemas.iter().map(String::as_str).collect();
+        Self::introspect_schemas(pool, &schemas).await
+    }
+
+    /// Introspect only the named schemas. Names that do not exist are ignored;
+    /// existing schemas without tables are still registered.
+    ///
+    /// Columns whose type has no `DataType` mapping keep their raw type name
+    /// as `DataType::Other`.
+    pub async fn introspect_schemas(pool: &PgPool, schemas: &[&str]) -> Result<Self> {
+        let names: Vec<String> = schemas.iter().map(|s| s.to_string()).collect();
+        let name: String = sqlx::query_scalar("SELECT current_database()")
+            .fetch_one(pool)
+            .await?;
+        let database = Database::new(name);
+
+        let existing: Vec<String> = sqlx::query_scalar(EXISTING_SCHEMAS)
+            .bind(&names)
+            .fetch_all(pool)
+            .await?;
+        for schema in existing {
+            database.insert_schema(Schema::new(schema)).await;
+        }
+
+        let enums: HashMap<String, Vec<String>> = sqlx::query_as(ENUM_TYPES)
+            .fetch_all(pool)
+            .await?
+            .into_iter()
+            .collect();
+
+        let rows: Vec<ColumnRow> = sqlx::query_as(COLUMNS).bind(&names).fetch_all(pool).await?;
+        let mut tables: Vec<((String, String), Vec<Column>)> = Vec::new();
+        for (schema, table, column, pg_type, nullable, primary_key, default, comment) in rows {
+            let data_type = match enums.get(&pg_type) {
+                Some(labels) => DataType::Enum(labels.clone()),
+                None => DataType::from_pg_str(&pg_type).unwrap_or_else(|| {
+                    debug!("{schema}.{table}.{column}: unmapped type {pg_type}, using Other");
+                    DataType::Other(pg_type)
+                }),
+            };
+            let column = Column {
+                nullable,
+                primary_key,
+                default,
+                deprecated: comment
+                    .as_deref()
+                    .is_some_and(Column::is_deprecated_comment),
+                comment,
+                ..Column::new(column, data_type)
+            };
+            let key = (schema, table);
+            match tables.last_mut() {
+                Some((last, columns)) if *last == key => columns.push(column),
+                _ => tables.push((key, vec![column])),
+            }
+        }
+
+        for ((schema, table), columns) in tables {
+            database
+                .insert_table(schema, Table::new_from_columns(table, columns))
+                .await;
+        }
+        Ok(database)
+    }
+}
+
+#[cfg(test)]
+mod isolated_integration_tests {
+    use super::*;
+
+    async fn setup(pool: &PgPool) -> Result {
+        for sql in [
+            "CREATE SCHEMA wanted",
+            "CREATE SCHEMA ignored",
+            "CREATE SCHEMA empty",
+            "CREATE TYPE wanted.mood AS ENUM ('sad', 'ok', 'happy')",
+            "CREATE TABLE wanted.people (
+                id uuid PRIMARY KEY,
+                name varchar(64) NOT NULL DEFAULT 'anon',
+                mood wanted.mood
+            )",
+            "COMMENT ON COLUMN wanted.people.name IS 'Display name'",
+            "COMMENT ON COLUMN wanted.people.mood IS 'DEPRECATED: derive from activity'",
+            "CREATE TABLE ignored.noise (id int)",
+        ] {
+            sqlx::query(sql).execute(pool).await?;
+        }
+        Ok(())
+    }
+
+    #[test_context(IsolatedIntegrationTest)]
+    #[tokio::test]
+    async fn introspects_only_the_requested_schemas(ctx: &mut IsolatedIntegrationTest) -> Result {
+        setup(&ctx.pool).await?;
+        let db = Database::introspect_schemas(&ctx.pool, &["wanted", "empty", "missing"]).await?;
+        assert_eq!(db.name, ctx.database);
+
+        let schemas = db.schemas.read().await;
+        let mut names: Vec<_> = schemas.keys().cloned().collect();
+        names.sort();
+        assert_eq!(names, ["empty", "wanted"]);
+
+        let tables = schemas["wanted"].tables.read().await;
+        let people = &tables["people"];
+        assert_eq!(people.column_order, ["id", "name", "mood"]);
+        let columns = people.columns.read().await;
+        assert!(!columns["id"].nullable);
+        assert!(columns["id"].primary_key);
+        assert!(!columns["name"].primary_key);
+        assert_eq!(columns["name"].data_type, DataType::VarChar(Some(64)));
+        assert_eq!(columns["name"].comment.as_deref(), Some("Display name"));
+        assert!(!columns["name"].deprecated);
+        assert!(columns["mood"].deprecated);
+        assert!(columns["name"].default.is_some());
+        assert_eq!(
+            columns["mood"].data_type,
+            DataType::Enum(vec!["sad".into(), "ok".into(), "happy".into()])
+        );
+        Ok(())
+    }
+
+    #[test_context(IsolatedIntegrationTest)]
+    #[tokio::test]
+    async fn unmapped_types_fall_back_to_other(ctx: &mut IsolatedIntegrationTest) -> Result {
+        for sql in [
+            "CREATE EXTENSION IF NOT EXISTS postgis",
+            "CREATE TABLE places (id int, location geometry(Point,4326), extent box2d)",
+        ] {
+            sqlx::query(sql).execute(&ctx.pool).await?;
+        }
+        let db = Database::introspect_schemas(&ctx.pool, &["public"]).await?;
+        let schemas = db.schemas.read().await;
+        let tables = schemas["public"].tables.read().await;
+        let places = &tables["places"];
+        assert_eq!(places.column_order, ["id", "location", "extent"]);
+        let columns = places.columns.read().await;
+        assert_eq!(
+            columns["location"].data_type,
+            DataType::Geometry(Some("Point".into()))
+        );
+        assert_eq!(columns["extent"].data_type, DataType::Other("box2d".into()));
+        Ok(())
+    }
+
+    #[test_context(IsolatedIntegrationTest)]
+    #[tokio::test]
+    async fn introspect_loads_all_user_schemas(ctx: &mut IsolatedIntegrationTest) -> Result {
+        setup(&ctx.pool).await?;
+        let db = Database::introspect(&ctx.pool).await?;
+        let schemas = db.schemas.read().await;
+        for schema in ["public", "wanted", "ignored", "empty"] {
+            assert!(schemas.contains_key(schema), "{schema} should be loaded");
+        }
+        assert!(!schemas.contains_key("pg_catalog"));
+        assert!(!schemas.contains_key("information_schema"));
+        Ok(())
+    }
+}
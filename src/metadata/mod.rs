@@ -2,6 +2,11 @@ crate::reexport!(column);
 crate::reexport!(table);
 crate::reexport!(schema);
 crate::reexport!(database);
+crate::reexport!(builder);
+crate::reexport!(function);
+crate::reexport!(ddl);
+crate::reexport!(introspect);
+crate::reexport!(snapshot);
 
 use std::{collections::HashMap, fmt::Display, sync::LazyLock};
 use tokio::sync::RwLock;
@@ -6,6 +6,12 @@ pub struct Schema {
     pub tables: Data<Table>,
 }
 
+impl Default for Schema {
+    fn default() -> Self {
+        Self::new(String::new())
+    }
+}
+
 impl Schema {
     pub fn new(name: impl Into<String>) -> Self {
         Self {
@@ -13,4 +19,12 @@ impl Schema {
             tables: Data::new(HashMap::new()),
         }
     }
+
+    /// Construct a schema from already built tables, without locking.
+    pub fn with_tables(name: impl Into<String>, tables: impl IntoIterator<Item = Table>) -> Self {
+        Self {
+            name: name.into(),
+            tables: Data::new(tables.into_iter().map(|t| (t.name.clone(), t)).collect()),
+        }
+    }
 }
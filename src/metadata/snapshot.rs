@@ -0,0 +1,161 @@
+//! Lock-free metadata snapshots.
+//!
+//! `METADATA` sits behind a `tokio::sync::RwLock`, so every completion takes a
+//! read lock and a refresh holding the write lock stalls all readers.
+//! `SharedMetadata` keeps an immutable `Snapshot` in an `ArcSwap` instead:
+//! readers load the current snapshot without ever blocking, and refreshes
+//! build a new snapshot and swap it in atomically. Readers holding the old
+//! snapshot keep using it until they drop it.
+
+use super::*;
+use arc_swap::ArcSwap;
+use std::sync::Arc;
+
+/// Immutable map of database name to its metadata.
+pub type Snapshot = HashMap<String, Arc<Database>>;
+
+/// Read-mostly alternative to `METADATA` backed by `arc-swap`.
+pub static SHARED_METADATA: LazyLock<SharedMetadata> = LazyLock::new(SharedMetadata::default);
+
+/// Atomically swappable `Snapshot` of all known databases.
+#[derive(Debug, Default)]
+pub struct SharedMetadata {
+    current: ArcSwap<Snapshot>,
+}
+
+impl SharedMetadata {
+    /// Start from an existing map of databases.
+    pub fn new(databases: impl IntoIterator<Item = (String, Database)>) -> Self {
+        let snapshot = databases
+            .into_iter()
+            .map(|(k, v)| (k, Arc::new(v)))
+            .collect();
+        Self {
+            current: ArcSwap::from_pointee(snapshot),
+        }
+    }
+
+    /// Migrate from the lock-based representation by draining `data`.
+    ///
+    /// `Database` is not `Clone`, so the entries are moved out and `data` is
+    /// left empty.
+    pub async fn migrate_from(data: &Data<Database>) -> Self {
+        Self::new(data.write().await.drain())
+    }
+
+    /// The current snapshot. Never blocks; the snapshot stays valid (and
+    /// unchanged) for as long as the caller holds it.
+    pub fn load(&self) -> Arc<Snapshot> {
+        self.current.load_full()
+    }
+
+    /// The current metadata of database `name`.
+    pub fn get(&self, name: &str) -> Option<Arc<Database>> {
+        self.current.load().get(name).cloned()
+    }
+
+    /// Replace the whole snapshot.
+    pub fn store(&self, snapshot: Snapshot) {
+        self.current.store(Arc::new(snapshot));
+    }
+
+    /// Insert (or replace) one database, copying the rest of the snapshot.
+    pub fn insert(&self, database: Database) {
+        let database = Arc::new(database);
+        self.current.rcu(|current| {
+            let mut next = Snapshot::clone(current);
+            next.insert(database.name.clone(), database.clone());
+            next
+        });
+    }
+
+    /// Remove database `name`, returning its last metadata.
+    pub fn remove(&self, name: &str) -> Option<Arc<Database>> {
+        let previous = self.current.rcu(|current| {
+            let mut next = Snapshot::clone(current);
+            next.remove(name);
+            next
+        });
+        previous.get(name).cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn migrates_from_locked_map() {
+        let data: Data<Database> = Data::new(HashMap::new());
+        data.write()
+            .await
+            .insert("app".into(), Database::new("app"));
+
+        let shared = SharedMetadata::migrate_from(&data).await;
+        assert!(data.read().await.is_empty());
+        assert_eq!(
+            shared.get("app").map(|db| db.name.clone()),
+            Some("app".into())
+        );
+        assert!(shared.get("other").is_none());
+    }
+
+    #[tokio::test]
+    async fn insert_and_remove_swap_snapshots() {
+        let shared = SharedMetadata::default();
+        let before = shared.load();
+        shared.insert(Database::new("a"));
+        shared.insert(Database::new("b"));
+        assert!(before.is_empty(), "held snapshots never change");
+        assert_eq!(shared.load().len(), 2);
+        assert_eq!(
+            shared.remove("a").map(|db| db.name.clone()),
+            Some("a".into())
+        );
+        assert!(shared.remove("a").is_none());
+        assert_eq!(shared.load().keys().collect::<Vec<_>>(), ["b"]);
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn readers_see_consistent_snapshots_during_refreshes() {
+        const REFRESHES: usize = 200;
+        let shared = Arc::new(SharedMetadata::default());
+
+        let readers: Vec<_> = (0..8)
+            .map(|_| {
+                let shared = shared.clone();
+                tokio::spawn(async move {
+                    let mut seen = 0;
+                    while seen < REFRESHES {
+                        // Snapshot `n` holds databases `0..n`, all or nothing.
+                        let snapshot = shared.load();
+                        let n = snapshot.len();
+                        assert!(n >= seen, "snapshots never go back in time");
+                        assert!((0..n).all(|i| snapshot.contains_key(&i.to_string())));
+                        seen = n;
+                        tokio::task::yield_now().await;
+                    }
+                })
+            })
+            .collect();
+
+        let writer = {
+            let shared = shared.clone();
+            tokio::spawn(async move {
+                for n in 1..=REFRESHES {
+                    let snapshot = (0..n)
+                        .map(|i| (i.to_string(), Arc::new(Database::new(i.to_string()))))
+                        .collect();
+                    shared.store(snapshot);
+                    tokio::task::yield_now().await;
+                }
+            })
+        };
+
+        writer.await.unwrap();
+        for reader in readers {
+            reader.await.unwrap();
+        }
+        assert_eq!(shared.load().len(), REFRESHES);
+    }
+}
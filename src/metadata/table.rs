@@ -9,6 +9,16 @@ pub struct Table {
     pub column_order: Vec<String>,
 }
 
+/// An owned copy of one table's metadata, detached from the metadata locks
+/// (see `Database::get_table`).
+#[derive(Debug, Clone)]
+pub struct TableView {
+    pub schema: String,
+    pub name: String,
+    /// Columns in preserved order; empty for a table without columns.
+    pub columns: Vec<Column>,
+}
+
 impl Default for Table {
     fn default() -> Self {
         Table {
@@ -61,6 +71,32 @@ impl Table {
         }
     }
 
+    /// Construct a table from full `Column` values, preserving their order and
+    /// any extra metadata (nullability, default, comment) they carry.
+    pub fn new_from_columns(
+        name: impl Into<String>,
+        columns: impl IntoIterator<Item = Column>,
+    ) -> Self {
+        let mut map = HashMap::new();
+        let mut order = Vec::new();
+        for column in columns {
+            order.push(column.name.clone());
+            map.insert(column.name.clone(), column);
+        }
+        Self {
+            name: name.into(),
+            columns: Data::new(map),
+            column_order: order,
+        }
+    }
+
+    /// True if the table has a column named `name`. Matching is exact first
+    /// and then ASCII case-insensitive, as unquoted identifiers fold case.
+    pub async fn has_column(&self, name: &str) -> bool {
+        let columns = self.columns.read().await;
+        columns.contains_key(name) || columns.keys().any(|c| c.eq_ignore_ascii_case(name))
+    }
+
     /// Convenience accessor returning columns in preserved order.
     pub async fn ordered_columns(&self) -> Vec<(String, DataType)> {
         let guard = self.columns.read().await;
@@ -70,3 +106,53 @@ impl Table {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn new_from_columns_keeps_order_and_metadata() {
+        let table = Table::new_from_columns(
+            "users",
+            [
+                Column::new("id", DataType::Uuid)
+                    .with_nullable(false)
+                    .with_default("gen_random_uuid()"),
+                Column::new("email", DataType::Text(None)).with_comment("Login address"),
+            ],
+        );
+
+        assert_eq!(table.column_order, ["id", "email"]);
+        let ordered: Vec<_> = table
+            .ordered_columns()
+            .await
+            .into_iter()
+            .map(|c| c.0)
+            .collect();
+        assert_eq!(ordered, ["id", "email"]);
+
+        let columns = table.columns.read().await;
+        let id = &columns["id"];
+        assert!(!id.nullable);
+        assert_eq!(id.default.as_deref(), Some("gen_random_uuid()"));
+        assert_eq!(id.comment, None);
+        let email = &columns["email"];
+        assert!(email.nullable);
+        assert_eq!(email.comment.as_deref(), Some("Login address"));
+    }
+
+    #[tokio::test]
+    async fn has_column_folds_case() {
+        let table = Table::new_with_ordered(
+            "users",
+            [("id", DataType::Uuid), ("Email", DataType::Text(None))],
+        );
+        assert!(table.has_column("id").await);
+        assert!(table.has_column("ID").await);
+        assert!(table.has_column("Email").await);
+        assert!(table.has_column("email").await);
+        assert!(!table.has_column("name").await);
+        assert!(!table.has_column("").await);
+    }
+}
@@ -0,0 +1,73 @@
+//! Parenthesis depth tracking over a token stream.
+//!
+//! Scope detection repeatedly needs to know how deeply nested a token or a
+//! cursor position is (subqueries, function calls, tuples). Every caller
+//! shares `depth_delta` so nesting rules are defined in a single place.
+
+use crate::sql::{token::Token, token_kind::TokenKind};
+
+/// Depth change contributed by a single token: `+1` for `(`, `-1` for `)`.
+pub fn depth_delta(token: &Token) -> i32 {
+    match token.kind {
+        TokenKind::ParenOpen => 1,
+        TokenKind::ParenClose => -1,
+        _ => 0,
+    }
+}
+
+/// Parenthesis depth at byte offset `cursor_pos`, counting every token that
+/// starts before it.
+///
+/// Unbalanced input is tolerated; stray closing parens may yield a negative
+/// depth.
+pub fn depth_at(tokens: &[Token], cursor_pos: usize) -> i32 {
+    tokens
+        .iter()
+        .take_while(|t| t.start < cursor_pos)
+        .map(depth_delta)
+        .sum()
+}
+
+/// Parenthesis depth in effect just before `tokens[index]`.
+pub fn depth_before(tokens: &[Token], index: usize) -> i32 {
+    tokens.iter().take(index).map(depth_delta).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::tokenizer::tokenize;
+
+    const SQL: &str = "SELECT (SELECT f(x) FROM a) FROM b";
+
+    #[test]
+    fn depth_at_positions() {
+        let tokens = tokenize(SQL);
+        assert_eq!(depth_at(&tokens, 0), 0);
+        assert_eq!(depth_at(&tokens, 7), 0); // on the opening paren
+        assert_eq!(depth_at(&tokens, 8), 1); // inside the subquery
+        assert_eq!(depth_at(&tokens, 17), 2); // inside f(...)
+        assert_eq!(depth_at(&tokens, 19), 1); // after f(x)
+        assert_eq!(depth_at(&tokens, 27), 0); // after the subquery
+        assert_eq!(depth_at(&tokens, SQL.len()), 0);
+    }
+
+    #[test]
+    fn depth_before_index() {
+        let tokens = tokenize(SQL);
+        let froms: Vec<_> = tokens
+            .iter()
+            .enumerate()
+            .filter(|(_, t)| t.is_keyword(crate::sql::keyword::Keyword::From))
+            .map(|(i, _)| depth_before(&tokens, i))
+            .collect();
+        assert_eq!(froms, [1, 0]);
+    }
+
+    #[test]
+    fn unbalanced_input() {
+        let tokens = tokenize("a)) (");
+        assert_eq!(depth_at(&tokens, 3), -2);
+        assert_eq!(depth_at(&tokens, 5), -1);
+    }
+}
@@ -32,22 +32,126 @@ pub enum Keyword {
     Union,
     Except,
     Intersect,
+    In,
+    Between,
+    Inner,
+    Left,
+    Right,
+    Full,
+    Outer,
+    Cross,
+    Lateral,
+    Natural,
+    Insert,
+    Into,
+    Values,
+    Collate,
+    Update,
+    Set,
+    Table,
+    Merge,
+    Using,
+    Matched,
+    By,
+    With,
+    Having,
 }
 
 impl Keyword {
-    /// Keywords that terminate a statement.
+    /// Every keyword, in declaration order.
+    pub const ALL: [Self; 36] = [
+        Keyword::Select,
+        Keyword::From,
+        Keyword::Join,
+        Keyword::On,
+        Keyword::As,
+        Keyword::Where,
+        Keyword::Group,
+        Keyword::Order,
+        Keyword::Limit,
+        Keyword::Offset,
+        Keyword::Union,
+        Keyword::Except,
+        Keyword::Intersect,
+        Keyword::In,
+        Keyword::Between,
+        Keyword::Inner,
+        Keyword::Left,
+        Keyword::Right,
+        Keyword::Full,
+        Keyword::Outer,
+        Keyword::Cross,
+        Keyword::Lateral,
+        Keyword::Natural,
+        Keyword::Insert,
+        Keyword::Into,
+        Keyword::Values,
+        Keyword::Collate,
+        Keyword::Update,
+        Keyword::Set,
+        Keyword::Table,
+        Keyword::Merge,
+        Keyword::Using,
+        Keyword::Matched,
+        Keyword::By,
+        Keyword::With,
+        Keyword::Having,
+    ];
+
+    /// Keywords that terminate a statement's FROM clause.
+    ///
+    /// `ON` is deliberately absent: a join condition is followed by further
+    /// joins belonging to the same FROM clause.
     pub const TERMINATORS: [Self; 9] = [
         Keyword::Where,
         Keyword::Group,
+        Keyword::Having,
         Keyword::Order,
         Keyword::Limit,
         Keyword::Offset,
         Keyword::Union,
         Keyword::Except,
         Keyword::Intersect,
-        Keyword::On,
     ];
 
+    /// Words that may precede `JOIN` (or, for `LATERAL`, a joined table) and
+    /// modify the join type: `INNER`, `LEFT`, `RIGHT`, `FULL`, `OUTER`,
+    /// `CROSS`, `LATERAL` and `NATURAL`.
+    pub const fn is_join_modifier(self) -> bool {
+        matches!(
+            self,
+            Keyword::Inner
+                | Keyword::Left
+                | Keyword::Right
+                | Keyword::Full
+                | Keyword::Outer
+                | Keyword::Cross
+                | Keyword::Lateral
+                | Keyword::Natural
+        )
+    }
+
+    /// Keywords that begin a clause of a statement: `SELECT`, `FROM`,
+    /// `WHERE`, `GROUP`, `HAVING`, `ORDER`, `LIMIT`, `OFFSET`, `VALUES` and
+    /// `SET`. Words inside a clause (`AS`, `ON`, `BY`, `JOIN` and its
+    /// modifiers), set operations, statement verbs (`INSERT`, `UPDATE`, ...)
+    /// and the `WITH` prefix are not.
+    pub const fn is_clause_start(self) -> bool {
+        matches!(
+            self,
+            Keyword::Select
+                | Keyword::From
+                | Keyword::Where
+                | Keyword::Group
+                | Keyword::Having
+                | Keyword::Order
+                | Keyword::Limit
+                | Keyword::Offset
+                | Keyword::Values
+                | Keyword::Set
+        )
+    }
+
     /// Attempt to classify a *lower‑cased* word slice into a `Keyword`.
     /// Returns `None` if the word is not a recognized keyword.
     ///
@@ -70,6 +174,29 @@ impl Keyword {
             "union" => Union,
             "except" => Except,
             "intersect" => Intersect,
+            "in" => In,
+            "between" => Between,
+            "inner" => Inner,
+            "left" => Left,
+            "right" => Right,
+            "full" => Full,
+            "outer" => Outer,
+            "cross" => Cross,
+            "lateral" => Lateral,
+            "natural" => Natural,
+            "insert" => Insert,
+            "into" => Into,
+            "values" => Values,
+            "collate" => Collate,
+            "update" => Update,
+            "set" => Set,
+            "table" => Table,
+            "merge" => Merge,
+            "using" => Using,
+            "matched" => Matched,
+            "by" => By,
+            "with" => With,
+            "having" => Having,
             _ => return None,
         };
         Some(kw)
@@ -92,6 +219,29 @@ impl Keyword {
             Union => "union",
             Except => "except",
             Intersect => "intersect",
+            In => "in",
+            Between => "between",
+            Inner => "inner",
+            Left => "left",
+            Right => "right",
+            Full => "full",
+            Outer => "outer",
+            Cross => "cross",
+            Lateral => "lateral",
+            Natural => "natural",
+            Insert => "insert",
+            Into => "into",
+            Values => "values",
+            Collate => "collate",
+            Update => "update",
+            Set => "set",
+            Table => "table",
+            Merge => "merge",
+            Using => "using",
+            Matched => "matched",
+            By => "by",
+            With => "with",
+            Having => "having",
         }
     }
 }
@@ -122,14 +272,67 @@ mod tests {
             "union",
             "except",
             "intersect",
+            "in",
+            "between",
+            "inner",
+            "left",
+            "right",
+            "full",
+            "outer",
+            "cross",
+            "lateral",
+            "natural",
+            "insert",
+            "into",
+            "values",
+            "collate",
+            "update",
+            "set",
+            "table",
+            "merge",
+            "using",
+            "matched",
+            "by",
+            "with",
+            "having",
         ] {
             assert!(Keyword::from_lower(w).is_some(), "{w} should be recognized");
         }
     }
 
+    #[test]
+    fn join_modifiers() {
+        let modifiers: Vec<_> = [
+            "inner", "left", "right", "full", "outer", "cross", "lateral", "natural",
+        ]
+        .into_iter()
+        .filter_map(Keyword::from_lower)
+        .collect();
+        assert_eq!(modifiers.len(), 8);
+        assert!(modifiers.iter().all(|k| k.is_join_modifier()));
+        assert!(!Keyword::Join.is_join_modifier());
+        assert!(!Keyword::On.is_join_modifier());
+    }
+
+    #[test]
+    fn clause_starts() {
+        use Keyword::*;
+        for kw in Keyword::ALL {
+            let expected = match kw {
+                Select | From | Where | Group | Having | Order | Limit | Offset | Values | Set => {
+                    true
+                }
+                Join | On | As | By | Union | Except | Intersect | In | Between | Inner | Left
+                | Right | Full | Outer | Cross | Lateral | Natural | Insert | Into | Collate
+                | Update | Table | Merge | Using | Matched | With => false,
+            };
+            assert_eq!(kw.is_clause_start(), expected, "{kw}");
+        }
+    }
+
     #[test]
     fn rejects_unknown_words() {
-        for w in ["foo", "bar", "inner", "outer", "cross", "random"] {
+        for w in ["foo", "bar", "when", "random"] {
             assert!(
                 Keyword::from_lower(w).is_none(),
                 "{w} should NOT be recognized"
@@ -137,6 +340,61 @@ mod tests {
         }
     }
 
+    #[test]
+    fn all_round_trips_through_from_lower() {
+        for kw in Keyword::ALL {
+            assert_eq!(Keyword::from_lower(kw.as_str()), Some(kw));
+        }
+    }
+
+    #[test]
+    fn all_lists_every_variant() {
+        use Keyword::*;
+        // Exhaustive: a new variant does not compile until it is given its
+        // slot in `ALL` here, so it cannot be left out of suggestions.
+        let index = |kw: Keyword| match kw {
+            Select => 0,
+            From => 1,
+            Join => 2,
+            On => 3,
+            As => 4,
+            Where => 5,
+            Group => 6,
+            Order => 7,
+            Limit => 8,
+            Offset => 9,
+            Union => 10,
+            Except => 11,
+            Intersect => 12,
+            In => 13,
+            Between => 14,
+            Inner => 15,
+            Left => 16,
+            Right => 17,
+            Full => 18,
+            Outer => 19,
+            Cross => 20,
+            Lateral => 21,
+            Natural => 22,
+            Insert => 23,
+            Into => 24,
+            Values => 25,
+            Collate => 26,
+            Update => 27,
+            Set => 28,
+            Table => 29,
+            Merge => 30,
+            Using => 31,
+            Matched => 32,
+            By => 33,
+            With => 34,
+            Having => 35,
+        };
+        for (i, kw) in Keyword::ALL.into_iter().enumerate() {
+            assert_eq!(index(kw), i, "{kw} is out of place in ALL");
+        }
+    }
+
     #[test]
     fn display_matches_as_str() {
         for kw in [
@@ -153,6 +411,29 @@ mod tests {
             Keyword::Union,
             Keyword::Except,
             Keyword::Intersect,
+            Keyword::In,
+            Keyword::Between,
+            Keyword::Inner,
+            Keyword::Left,
+            Keyword::Right,
+            Keyword::Full,
+            Keyword::Outer,
+            Keyword::Cross,
+            Keyword::Lateral,
+            Keyword::Natural,
+            Keyword::Insert,
+            Keyword::Into,
+            Keyword::Values,
+            Keyword::Collate,
+            Keyword::Update,
+            Keyword::Set,
+            Keyword::Table,
+            Keyword::Merge,
+            Keyword::Using,
+            Keyword::Matched,
+            Keyword::By,
+            Keyword::With,
+            Keyword::Having,
         ] {
             assert_eq!(kw.to_string(), kw.as_str());
         }
@@ -0,0 +1,86 @@
+//! Runtime-extensible keyword table.
+//!
+//! The built-in `Keyword` enum deliberately covers only what completion needs.
+//! Teams targeting SQL supersets (e.g. `ILIKE`, `MATERIALIZED`) can register
+//! additional words here without recompiling; the tokenizer consults the set
+//! after the built-in table and classifies matches as
+//! `TokenKind::ExtraKeyword`.
+//!
+//! Words are stored lower-cased so lookups stay case-insensitive, mirroring
+//! `Keyword::from_lower`.
+
+use std::collections::HashSet;
+
+/// A set of additional keywords recognized by the tokenizer at runtime.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeywordSet {
+    words: HashSet<String>,
+}
+
+impl KeywordSet {
+    /// Create an empty set (no extra keywords).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a keyword. Returns `false` if it was already present.
+    pub fn insert(&mut self, word: impl AsRef<str>) -> bool {
+        self.words.insert(word.as_ref().to_ascii_lowercase())
+    }
+
+    /// Builder-style variant of `insert`.
+    pub fn with(mut self, word: impl AsRef<str>) -> Self {
+        self.insert(word);
+        self
+    }
+
+    /// True if the *lower‑cased* word is registered.
+    ///
+    /// NOTE: Like `Keyword::from_lower`, the caller is responsible for
+    /// lower-casing the input.
+    pub fn contains_lower(&self, word: &str) -> bool {
+        self.words.contains(word)
+    }
+
+    /// Number of registered extra keywords.
+    pub fn len(&self) -> usize {
+        self.words.len()
+    }
+
+    /// True if no extra keywords are registered.
+    pub fn is_empty(&self) -> bool {
+        self.words.is_empty()
+    }
+}
+
+impl<S: AsRef<str>> FromIterator<S> for KeywordSet {
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut set = Self::new();
+        for word in iter {
+            set.insert(word);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn insert_is_case_insensitive() {
+        let mut set = KeywordSet::new();
+        assert!(set.insert("ILIKE"));
+        assert!(!set.insert("ilike"));
+        assert!(set.contains_lower("ilike"));
+        assert_eq!(set.len(), 1);
+    }
+
+    #[test]
+    fn collects_from_iterator() {
+        let set: KeywordSet = ["Materialized", "ILIKE"].into_iter().collect();
+        assert!(set.contains_lower("materialized"));
+        assert!(set.contains_lower("ilike"));
+        assert!(!set.contains_lower("select"));
+    }
+}
@@ -6,9 +6,16 @@
 //!
 //! Modules:
 //! - `keyword`    : Small enum of only the keywords needed for suggestions.
+//! - `keyword_set`: Runtime-registered extra keywords consulted by the tokenizer.
 //! - `token_kind` : Classification of lexical atoms (identifiers, punctuation, keywords).
 //! - `token`      : Token struct pairing a `TokenKind` with source span offsets.
-//! - `tokenizer`  : Single pass O(n) tokenizer producing a `Vec<Token>` from raw SQL.
+//! - `tokenizer`  : Single pass O(n) tokenizer producing a `Vec<Token>` from raw SQL
+//!   (dialect options cover `#`/`@` sigils and extra keywords).
+//! - `trivia`     : Whitespace / comment regions skipped by the tokenizer.
+//! - `position`   : Byte <-> character / line-column offset conversion for editor cursors.
+//! - `depth`      : Parenthesis depth tracking shared by scope detection.
+//! - `statement`  : `;` statement separators outside string literals.
+//! - `quote`      : Identifier quoting for names inserted into SQL.
 //!
 //! Design Principles:
 //! 1. Accept incomplete / syntactically invalid SQL (robust for live editing).
@@ -37,22 +44,36 @@
 //! NOTE: This is **not** a full SQL parser and intentionally ignores many
 //! constructs that are not needed for current autocomplete heuristics.
 
+pub mod depth;
 pub mod keyword;
+pub mod keyword_set;
+pub mod position;
+pub mod quote;
+pub mod statement;
 pub mod token;
 pub mod token_kind;
 pub mod tokenizer;
+pub mod trivia;
 
+pub use depth::{depth_at, depth_before, depth_delta};
 pub use keyword::Keyword;
+pub use keyword_set::KeywordSet;
+pub use position::{byte_to_char, char_to_byte, line_col_to_byte};
+pub use quote::{needs_quoting, quote_ident};
+pub use statement::statement_separators;
 pub use token::Token;
 pub use token_kind::TokenKind;
-pub use tokenizer::tokenize;
+pub use tokenizer::{SigilMode, TokenizerOptions, tokenize, tokenize_with, tokenize_with_trivia};
+pub use trivia::{Trivia, TriviaKind};
 
 /// Convenience prelude re‑exporting the most commonly used items.
 ///
 /// Import with:
 /// `use qview::sql::prelude::*;`
 pub mod prelude {
-    pub use super::{Keyword, Token, TokenKind, tokenize};
+    pub use super::{
+        Keyword, KeywordSet, SigilMode, Token, TokenKind, TokenizerOptions, tokenize, tokenize_with,
+    };
 }
 
 #[cfg(test)]
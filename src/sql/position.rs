@@ -0,0 +1,94 @@
+//! Conversions between byte offsets and character offsets.
+//!
+//! The tokenizer (and therefore every `Token` span and `Cursor`) works in
+//! UTF-8 byte offsets, while editors frequently report positions as character
+//! counts. These helpers translate between the two so non-ASCII SQL (e.g.
+//! `'café'` or `é_table`) maps cursors reliably. `line_col_to_byte` handles
+//! editors reporting a line and column instead.
+
+/// Convert a byte offset into a character offset.
+///
+/// A `byte` that falls inside a multi-byte character rounds up to the next
+/// character; offsets past the end clamp to the total character count.
+pub fn byte_to_char(sql: &str, byte: usize) -> usize {
+    sql.char_indices().take_while(|(i, _)| *i < byte).count()
+}
+
+/// Convert a character offset into a byte offset.
+///
+/// `ch == sql.chars().count()` maps to `sql.len()` (the end-of-input cursor).
+/// Returns `None` when `ch` lies beyond the end of the string.
+pub fn char_to_byte(sql: &str, ch: usize) -> Option<usize> {
+    sql.char_indices()
+        .map(|(i, _)| i)
+        .chain(std::iter::once(sql.len()))
+        .nth(ch)
+}
+
+/// Convert a 0-based line and character column into a byte offset.
+///
+/// Both `\n` and `\r\n` end a line, so buffers from Windows editors map
+/// like Unix ones; a tab is a single character. `col` may point at the end
+/// of its line (before the line break). Returns `None` when `line` or `col`
+/// lies beyond the buffer or line.
+pub fn line_col_to_byte(sql: &str, line: usize, col: usize) -> Option<usize> {
+    let start = match line {
+        0 => 0,
+        _ => sql.match_indices('\n').nth(line - 1)?.0 + 1,
+    };
+    let text = sql[start..].split('\n').next().unwrap_or("");
+    let text = text.strip_suffix('\r').unwrap_or(text);
+    char_to_byte(text, col).map(|byte| start + byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ascii_is_identity() {
+        let sql = "SELECT a FROM t";
+        for i in 0..=sql.len() {
+            assert_eq!(byte_to_char(sql, i), i);
+            assert_eq!(char_to_byte(sql, i), Some(i));
+        }
+    }
+
+    #[test]
+    fn multibyte_round_trip() {
+        let sql = "SELECT 'café', é_table.  FROM é_table";
+        for (ch, (byte, _)) in sql.char_indices().enumerate() {
+            assert_eq!(char_to_byte(sql, ch), Some(byte));
+            assert_eq!(byte_to_char(sql, byte), ch);
+        }
+        let chars = sql.chars().count();
+        assert_eq!(char_to_byte(sql, chars), Some(sql.len()));
+        assert_eq!(byte_to_char(sql, sql.len()), chars);
+    }
+
+    #[test]
+    fn mid_character_byte_rounds_up() {
+        let sql = "é"; // two bytes
+        assert_eq!(byte_to_char(sql, 1), 1);
+        assert_eq!(byte_to_char(sql, 0), 0);
+    }
+
+    #[test]
+    fn line_col_with_crlf_and_tabs() {
+        let sql = "SELECT a,\r\n\tb -- x\r\nFROM t";
+        assert_eq!(line_col_to_byte(sql, 0, 0), Some(0));
+        assert_eq!(line_col_to_byte(sql, 0, 9), Some(9)); // end of line, before `\r`
+        assert_eq!(line_col_to_byte(sql, 0, 10), None);
+        assert_eq!(line_col_to_byte(sql, 1, 1), Some(12)); // after the tab
+        assert_eq!(&sql[12..13], "b");
+        assert_eq!(line_col_to_byte(sql, 2, 6), Some(sql.len()));
+        assert_eq!(line_col_to_byte(sql, 3, 0), None);
+    }
+
+    #[test]
+    fn out_of_range() {
+        let sql = "aé";
+        assert_eq!(char_to_byte(sql, 3), None);
+        assert_eq!(byte_to_char(sql, 100), 2);
+    }
+}
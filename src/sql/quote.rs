@@ -0,0 +1,165 @@
+//! Identifier quoting for inserted SQL.
+//!
+//! Completion inserts bare names, which is only valid SQL when the name is a
+//! plain lower-case identifier that is not a reserved word. Anything else
+//! (`order`, `my col`, `Email`) must be written as a quoted identifier.
+
+use crate::sql::keyword::Keyword;
+use std::borrow::Cow;
+
+/// Postgres key words that are reserved (including those only allowed as
+/// function or type names), lower-cased and sorted.
+const RESERVED: &[&str] = &[
+    "all",
+    "analyse",
+    "analyze",
+    "and",
+    "any",
+    "array",
+    "as",
+    "asc",
+    "asymmetric",
+    "authorization",
+    "binary",
+    "both",
+    "case",
+    "cast",
+    "check",
+    "collate",
+    "collation",
+    "column",
+    "concurrently",
+    "constraint",
+    "create",
+    "cross",
+    "current_catalog",
+    "current_date",
+    "current_role",
+    "current_schema",
+    "current_time",
+    "current_timestamp",
+    "current_user",
+    "default",
+    "deferrable",
+    "desc",
+    "distinct",
+    "do",
+    "else",
+    "end",
+    "except",
+    "false",
+    "fetch",
+    "for",
+    "foreign",
+    "freeze",
+    "from",
+    "full",
+    "grant",
+    "group",
+    "having",
+    "ilike",
+    "in",
+    "initially",
+    "inner",
+    "intersect",
+    "into",
+    "is",
+    "isnull",
+    "join",
+    "lateral",
+    "leading",
+    "left",
+    "like",
+    "limit",
+    "localtime",
+    "localtimestamp",
+    "natural",
+    "not",
+    "notnull",
+    "null",
+    "offset",
+    "on",
+    "only",
+    "or",
+    "order",
+    "outer",
+    "overlaps",
+    "placing",
+    "primary",
+    "references",
+    "returning",
+    "right",
+    "select",
+    "session_user",
+    "similar",
+    "some",
+    "symmetric",
+    "system_user",
+    "table",
+    "tablesample",
+    "then",
+    "to",
+    "trailing",
+    "true",
+    "union",
+    "unique",
+    "user",
+    "using",
+    "variadic",
+    "verbose",
+    "when",
+    "where",
+    "window",
+    "with",
+];
+
+/// True if `name` must be quoted to be used as an identifier: it is empty,
+/// does not start with a lower-case letter or `_`, contains anything other
+/// than lower-case letters, digits and `_` (unquoted names fold to lower
+/// case), or is a reserved word or a `Keyword`.
+pub fn needs_quoting(name: &str) -> bool {
+    let mut bytes = name.bytes();
+    let plain = bytes
+        .next()
+        .is_some_and(|b| b.is_ascii_lowercase() || b == b'_')
+        && bytes.all(|b| b.is_ascii_lowercase() || b.is_ascii_digit() || b == b'_');
+    !plain || RESERVED.binary_search(&name).is_ok() || Keyword::from_lower(name).is_some()
+}
+
+/// `name` as an identifier: unchanged when possible, otherwise wrapped in
+/// double quotes with embedded quotes doubled.
+pub fn quote_ident(name: &str) -> Cow<'_, str> {
+    if needs_quoting(name) {
+        Cow::Owned(format!("\"{}\"", name.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[test]
+    fn reserved_words_are_sorted() {
+        assert!(RESERVED.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[rstest]
+    #[case("id", "id")]
+    #[case("_tmp1", "_tmp1")]
+    #[case("order", "\"order\"")]
+    #[case("select", "\"select\"")]
+    #[case("user", "\"user\"")]
+    #[case("values", "\"values\"")]
+    #[case("my col", "\"my col\"")]
+    #[case("Email", "\"Email\"")]
+    #[case("1st", "\"1st\"")]
+    #[case("say \"hi\"", "\"say \"\"hi\"\"\"")]
+    #[case("", "\"\"")]
+    fn quotes_when_needed(#[case] name: &str, #[case] expected: &str) {
+        assert_eq!(quote_ident(name), expected);
+        assert_eq!(needs_quoting(name), name != expected);
+    }
+}
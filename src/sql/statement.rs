@@ -0,0 +1,55 @@
+//! Statement boundaries in a token stream.
+//!
+//! String literals are single `String` tokens, but quoted identifiers are not
+//! lexed: their quotes and contents tokenize as ordinary tokens, so a `;`
+//! inside `"a;b"` is an `Other(';')` like a real separator.
+//! `statement_separators` tells the two apart by tracking quotes.
+
+use crate::sql::{token::Token, token_kind::TokenKind};
+
+/// Indices of the `;` tokens that end a statement: those outside quoted
+/// identifiers (`"..."`). String literals (`'...'`) are single tokens.
+///
+/// Doubled quotes (`"a""b"`) close and reopen the identifier, so they keep
+/// its contents inside. An unterminated identifier runs to the end of input.
+pub fn statement_separators(tokens: &[Token]) -> Vec<usize> {
+    let mut quoted = false;
+    let mut separators = Vec::new();
+    for (idx, t) in tokens.iter().enumerate() {
+        match &t.kind {
+            TokenKind::Other('"') => quoted = !quoted,
+            TokenKind::Other(';') if !quoted => separators.push(idx),
+            _ => {}
+        }
+    }
+    separators
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sql::tokenizer::tokenize;
+    use rstest::rstest;
+
+    #[rstest]
+    // Case 1: Plain separators
+    #[case("SELECT 1; SELECT 2;", vec![8, 18])]
+    // Case 2: Inside a string literal
+    #[case("SELECT ';' ; SELECT 2", vec![11])]
+    // Case 3: Doubled quote inside a literal
+    #[case("SELECT 'it''s;' ; SELECT 2", vec![16])]
+    // Case 4: Inside a quoted identifier
+    #[case(r#"SELECT "a;b" ; SELECT 2"#, vec![13])]
+    // Case 5: Unterminated literal
+    #[case("SELECT '; SELECT 2", vec![])]
+    // Case 6: Unterminated quoted identifier
+    #[case(r#"SELECT "a; SELECT 2"#, vec![])]
+    fn finds_statement_separators(#[case] sql: &str, #[case] expected: Vec<usize>) {
+        let tokens = tokenize(sql);
+        let starts: Vec<_> = statement_separators(&tokens)
+            .into_iter()
+            .map(|i| tokens[i].start)
+            .collect();
+        assert_eq!(starts, expected, "{sql}");
+    }
+}
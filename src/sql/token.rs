@@ -53,6 +53,14 @@ impl Token {
         self.kind.ident()
     }
 
+    /// Returns the keyword if this token is a `Keyword`.
+    pub fn keyword(&self) -> Option<Keyword> {
+        match self.kind {
+            TokenKind::Keyword(k) => Some(k),
+            _ => None,
+        }
+    }
+
     /// Returns true if this token represents a given keyword.
     pub fn is_keyword(&self, kw: Keyword) -> bool {
         self.kind.is_keyword(kw)
@@ -65,10 +73,25 @@ impl Token {
         cursor >= self.start && cursor < self.end
     }
 
-    /// True if the token starts before `cursor` and ends at or after it.
-    /// Semantically equivalent to `contains(cursor)`.
+    /// Returns true if the cursor sits right after this token (`cursor == end`),
+    /// e.g. `users|`. Empty tokens never end at a cursor.
+    pub fn ends_at(&self, cursor: usize) -> bool {
+        !self.is_empty() && cursor == self.end
+    }
+
+    /// True if the cursor is inside this token or right at its end:
+    /// `contains(cursor) || ends_at(cursor)`.
     pub fn touches(&self, cursor: usize) -> bool {
-        self.contains(cursor)
+        self.contains(cursor) || self.ends_at(cursor)
+    }
+
+    /// True if this is a string literal missing its closing quote (`'abc`
+    /// mid-edit), which the tokenizer runs to the end of input.
+    pub fn is_unterminated_string(&self) -> bool {
+        match &self.kind {
+            TokenKind::String(value) => self.len() < value.len() + value.matches('\'').count() + 2,
+            _ => false,
+        }
     }
 
     /// Convenience: convert to a `(start, end)` tuple.
@@ -97,6 +120,16 @@ mod tests {
         assert!(!t.contains(5)); // end exclusive
     }
 
+    #[test]
+    fn inside_versus_at_end() {
+        let t = Token::new(TokenKind::Ident("name".into()), 7, 11);
+        assert!(t.contains(9) && !t.ends_at(9)); // na|me
+        assert!(!t.contains(11) && t.ends_at(11)); // name|
+        assert!(t.touches(9) && t.touches(11));
+        assert!(!t.touches(6) && !t.touches(12));
+        assert!(!Token::new(TokenKind::Comma, 3, 3).ends_at(3));
+    }
+
     #[test]
     fn keyword_detection() {
         let t = Token::new(TokenKind::Keyword(Keyword::Select), 0, 6);
@@ -104,6 +137,14 @@ mod tests {
         assert!(!t.is_keyword(Keyword::From));
     }
 
+    #[test]
+    fn keyword_access() {
+        let t = Token::new(TokenKind::Keyword(Keyword::From), 0, 4);
+        assert_eq!(t.keyword(), Some(Keyword::From));
+        let t = Token::new(TokenKind::Ident("users".into()), 5, 10);
+        assert_eq!(t.keyword(), None);
+    }
+
     #[test]
     fn span_method() {
         let t = Token::new(TokenKind::Dot, 10, 11);
@@ -7,7 +7,8 @@
 //! Design goals:
 //! - Preserve original identifier casing via `Ident(String)` for downstream
 //!   display and matching.
-//! - Keep the set of structural punctuation minimal (comma, dot, parens) as
+//! - Keep the set of structural punctuation minimal (comma, dot, parens,
+//!   brackets) as
 //!   that's sufficient for current completion heuristics.
 //! - Provide ergonomic helpers (`is_keyword`, `ident`) to avoid verbose pattern
 //!   matches at call sites.
@@ -25,6 +26,16 @@ pub enum TokenKind {
     Ident(String),
     /// Recognized SQL keyword.
     Keyword(Keyword),
+    /// Keyword registered at runtime through a `KeywordSet` (stored lower-cased).
+    ExtraKeyword(String),
+    /// Single-quoted string literal, holding its value without the quotes and
+    /// with doubled quotes (`''`) unescaped. The token spans the quotes.
+    String(String),
+    /// Numeric literal (`123`, `3.14`, `.5`, `1e-3`), holding its source text.
+    Number(String),
+    /// Variable / bind placeholder such as `@var` (text includes the sigil).
+    /// Only produced when enabled through `TokenizerOptions`.
+    Placeholder(String),
     /// Comma `,` (used to separate table items in FROM, list items, etc.).
     Comma,
     /// Dot `.` (used for qualified names like `table.column`).
@@ -33,8 +44,19 @@ pub enum TokenKind {
     ParenOpen,
     /// Closing parenthesis `)`.
     ParenClose,
+    /// Opening bracket `[` (array constructors and subscripts).
+    BracketOpen,
+    /// Closing bracket `]`.
+    BracketClose,
+    /// Multi-character symbolic operator (`::`, `<=`, `<>`, `||`). The other
+    /// operators (`->>`, `@>`, `&&`, `?`) are only produced when enabled
+    /// through `TokenizerOptions`.
+    Operator(String),
     /// Any other single punctuation / symbol we do not specially classify.
     Other(char),
+    /// Zero-width end-of-input sentinel at `sql.len()`.
+    /// Only produced when enabled through `TokenizerOptions`.
+    Eof,
 }
 
 impl TokenKind {
@@ -51,6 +73,51 @@ impl TokenKind {
         }
     }
 
+    /// Returns the literal's value if this token is a `String`.
+    pub fn string(&self) -> Option<&str> {
+        match self {
+            TokenKind::String(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the literal's source text if this token is a `Number`.
+    pub fn number(&self) -> Option<&str> {
+        match self {
+            TokenKind::Number(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the lower-cased word if this token is a runtime-registered keyword.
+    pub fn extra_keyword(&self) -> Option<&str> {
+        match self {
+            TokenKind::ExtraKeyword(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the placeholder text (including its sigil) if this token is a `Placeholder`.
+    pub fn placeholder(&self) -> Option<&str> {
+        match self {
+            TokenKind::Placeholder(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// Returns the operator text if this token is an `Operator`.
+    pub fn operator(&self) -> Option<&str> {
+        match self {
+            TokenKind::Operator(s) => Some(s.as_str()),
+            _ => None,
+        }
+    }
+
+    /// True if this is the end-of-input sentinel.
+    pub fn is_eof(&self) -> bool {
+        matches!(self, TokenKind::Eof)
+    }
+
     /// Convenience: returns true if this token represents any identifier.
     pub fn is_ident(&self) -> bool {
         matches!(self, TokenKind::Ident(_))
@@ -96,6 +163,38 @@ mod tests {
         assert!(!TokenKind::Keyword(Keyword::From).is_punctuation());
     }
 
+    #[test]
+    fn extra_keyword_access() {
+        let tk = TokenKind::ExtraKeyword("ilike".into());
+        assert_eq!(tk.extra_keyword(), Some("ilike"));
+        assert!(tk.ident().is_none());
+        assert!(!tk.is_punctuation());
+    }
+
+    #[test]
+    fn placeholder_access() {
+        let tk = TokenKind::Placeholder("@id".into());
+        assert_eq!(tk.placeholder(), Some("@id"));
+        assert!(!tk.is_ident());
+        assert!(!tk.is_punctuation());
+    }
+
+    #[test]
+    fn string_access() {
+        let tk = TokenKind::String("it's".into());
+        assert_eq!(tk.string(), Some("it's"));
+        assert!(tk.ident().is_none());
+        assert!(!tk.is_punctuation());
+    }
+
+    #[test]
+    fn number_access() {
+        let tk = TokenKind::Number("1.0".into());
+        assert_eq!(tk.number(), Some("1.0"));
+        assert!(!tk.is_ident());
+        assert!(!tk.is_punctuation());
+    }
+
     #[test]
     fn other_variant() {
         let tk = TokenKind::Other(';');
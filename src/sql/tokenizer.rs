@@ -1,4 +1,117 @@
-use crate::sql::{keyword::Keyword, token::Token, token_kind::TokenKind};
+use crate::sql::{
+    keyword::Keyword,
+    keyword_set::KeywordSet,
+    token::Token,
+    token_kind::TokenKind,
+    trivia::{Trivia, TriviaKind},
+};
+
+/// How a sigil character (`#`, `@`) directly followed by a word is tokenized.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SigilMode {
+    /// The sigil is a plain `Other` token and the word a separate token (Postgres).
+    #[default]
+    Symbol,
+    /// The sigil starts an identifier, e.g. SQL Server temp tables (`#temp`, `##global`).
+    Identifier,
+    /// The sigil starts a `Placeholder`, e.g. SQL Server variables (`@var`).
+    Placeholder,
+}
+
+/// Options altering how `tokenize_with` classifies input.
+///
+/// The default value reproduces `tokenize` exactly.
+#[derive(Debug, Clone, Default)]
+pub struct TokenizerOptions {
+    /// Extra keywords consulted after the built-in `Keyword` table.
+    pub keywords: KeywordSet,
+    /// Treatment of a `#` prefix.
+    pub hash: SigilMode,
+    /// Treatment of an `@` prefix.
+    pub at: SigilMode,
+    /// Append a zero-width `TokenKind::Eof` token at `sql.len()`.
+    pub eof: bool,
+    /// Emit every symbolic operator in `OPERATORS` as a single
+    /// `TokenKind::Operator` token. Without it only `BASIC_OPERATORS` are,
+    /// and other symbols are one `Other` per character.
+    pub operators: bool,
+    /// Read `[name]` as a quoted identifier (SQL Server) instead of bracket
+    /// tokens.
+    pub bracket_identifiers: bool,
+}
+
+impl TokenizerOptions {
+    /// SQL Server conventions: `#temp` is an identifier, `@var` a placeholder
+    /// and `[name]` a quoted identifier.
+    pub fn sql_server() -> Self {
+        Self::default()
+            .with_hash(SigilMode::Identifier)
+            .with_at(SigilMode::Placeholder)
+            .with_bracket_identifiers(true)
+    }
+
+    /// Replace the set of extra keywords.
+    pub fn with_keywords(mut self, keywords: KeywordSet) -> Self {
+        self.keywords = keywords;
+        self
+    }
+
+    /// Set how a `#` prefix is tokenized.
+    pub fn with_hash(mut self, mode: SigilMode) -> Self {
+        self.hash = mode;
+        self
+    }
+
+    /// Set how an `@` prefix is tokenized.
+    pub fn with_at(mut self, mode: SigilMode) -> Self {
+        self.at = mode;
+        self
+    }
+
+    /// Enable or disable the trailing `TokenKind::Eof` sentinel.
+    pub fn with_eof(mut self, eof: bool) -> Self {
+        self.eof = eof;
+        self
+    }
+
+    /// Enable or disable `TokenKind::Operator` tokens.
+    pub fn with_operators(mut self, operators: bool) -> Self {
+        self.operators = operators;
+        self
+    }
+
+    /// Enable or disable `[name]` quoted identifiers.
+    pub fn with_bracket_identifiers(mut self, bracket_identifiers: bool) -> Self {
+        self.bracket_identifiers = bracket_identifiers;
+        self
+    }
+
+    fn sigil_mode(&self, c: char) -> SigilMode {
+        match c {
+            '#' => self.hash,
+            '@' => self.at,
+            _ => SigilMode::Symbol,
+        }
+    }
+}
+
+/// Postgres operators recognized with `TokenizerOptions::operators`, longest
+/// first so matching is greedy (`->>` before `->`). Covers comparison, cast,
+/// JSON / JSONB path and containment, array overlap, text search, regex and
+/// geometric / network operators. Single-character operators other than `?`
+/// (JSONB key existence) stay `Other` tokens.
+const OPERATORS: &[&str] = &[
+    "->>", "#>>", "<->", "!~*", "<<=", ">>=", "->", "#>", "#-", "@>", "<@", "&&", "||", "?|", "?&",
+    "@@", "@?", "<=", ">=", "<>", "!=", "::", "~*", "!~", "<<", ">>", "&<", "&>", "?",
+];
+
+/// Operators always emitted as single `TokenKind::Operator` tokens: casts,
+/// comparisons and concatenation, longest first.
+const BASIC_OPERATORS: &[&str] = &["<=", ">=", "<>", "!=", "::", "||"];
+
+fn is_word_byte(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b == b'_'
+}
 
 /// Lenient SQL tokenizer producing a flat stream of `Token`s.
 ///
@@ -8,36 +121,158 @@ use crate::sql::{keyword::Keyword, token::Token, token_kind::TokenKind};
 /// - Classifies only the minimal keyword set defined in `keyword.rs`.
 ///
 /// Behavior:
-/// - Skips ASCII whitespace.
-/// - Aggregates `[A-Za-z0-9_]` runs into identifiers, preserving original case.
+/// - Skips ASCII whitespace, `-- line` comments (to the end of the line) and
+///   `/* block */` comments (nesting, running to the end of input when
+///   unterminated); comments produce no tokens.
+/// - Reads numeric literals (`123`, `3.14`, `.5`, `10.`, `1e-3`) as one
+///   `Number` token, so their dot is never a `Dot`.
+/// - Aggregates `[A-Za-z_][A-Za-z0-9_]*` runs into identifiers, preserving original case.
 /// - Lowercases an identifier once to attempt keyword classification (no allocation
 ///   unless keyword match fails and we must store the original String).
-/// - Emits single-character tokens for comma, dot, parentheses; everything else is `Other(char)`.
+/// - Reads a single-quoted string literal as one `String` token, up to the
+///   matching closing quote (`''` is an escaped quote); an unterminated
+///   literal runs to the end of input.
+/// - Emits the casts, comparisons and concatenation in `BASIC_OPERATORS`
+///   (`::`, `<=`, `>=`, `<>`, `!=`, `||`) as single `Operator` tokens.
+/// - Emits single-character tokens for comma, dot, parentheses, brackets;
+///   everything else is `Other(char)` (spanning every byte of a non-ASCII
+///   character).
 ///
 /// Guarantees:
-/// - Never panics on valid UTF-8 & bounded indices.
+/// - Never panics on valid UTF-8 & bounded indices; every span lies on
+///   character boundaries, so `sql[start..end]` never panics.
 /// - Never returns an error (malformed constructs still yield tokens).
 ///
 /// Complexity:
 /// - O(n) time, O(t) space where `t` is number of tokens.
 pub fn tokenize(sql: &str) -> Vec<Token> {
+    tokenize_with(sql, &TokenizerOptions::default())
+}
+
+/// Tokenize with caller supplied `TokenizerOptions`.
+///
+/// Identical to `tokenize`, except words registered in `options.keywords`
+/// are emitted as `TokenKind::ExtraKeyword` instead of `TokenKind::Ident`.
+/// Built-in keywords always take precedence.
+///
+/// With a non-default `SigilMode`, a run of `#`/`@` directly followed by a
+/// word becomes one `Ident` or `Placeholder` token spanning sigils and word;
+/// a sigil not followed by a word stays an `Other` token.
+///
+/// With `options.operators` all symbolic operators (`data->>'key'`, `tags &&
+/// '{a}'`), not only `BASIC_OPERATORS`, are matched greedily into single
+/// `Operator` tokens.
+///
+/// With `options.bracket_identifiers` a closed `[name]` becomes one `Ident`
+/// token holding the unquoted name; an unclosed `[` stays a bracket token.
+///
+/// With `options.eof` the stream ends in a zero-width `TokenKind::Eof` token
+/// at `sql.len()`, so lookahead never runs off the end.
+pub fn tokenize_with(sql: &str, options: &TokenizerOptions) -> Vec<Token> {
+    scan(sql, options, None)
+}
+
+/// Tokenize with default options, also returning every skipped region.
+///
+/// Whitespace runs, `-- line` comments and `/* block */` comments are
+/// reported as `Trivia`. Tokens and trivia
+/// are each in source order, and together they cover `sql` without gaps or
+/// overlap.
+pub fn tokenize_with_trivia(sql: &str) -> (Vec<Token>, Vec<Trivia>) {
+    let mut trivia = Vec::new();
+    let tokens = scan(sql, &TokenizerOptions::default(), Some(&mut trivia));
+    (tokens, trivia)
+}
+
+/// Shared tokenizer loop. Trivia is only recorded when a `trivia` sink is
+/// supplied.
+fn scan(sql: &str, options: &TokenizerOptions, mut trivia: Option<&mut Vec<Trivia>>) -> Vec<Token> {
     let mut out = Vec::new();
     let bytes = sql.as_bytes();
     let mut i = 0;
 
     while i < bytes.len() {
         let c = bytes[i] as char;
+        let start = i;
 
         // Skip whitespace quickly
         if c.is_ascii_whitespace() {
             i += 1;
+            if let Some(trivia) = trivia.as_deref_mut() {
+                while i < bytes.len() && bytes[i].is_ascii_whitespace() {
+                    i += 1;
+                }
+                trivia.push(Trivia::new(TriviaKind::Whitespace, start, i));
+            }
             continue;
         }
 
-        let start = i;
+        // String literal path (`'it''s'`)
+        if c == '\'' {
+            let (value, end) = string_literal(sql, i);
+            out.push(Token::new(TokenKind::String(value), start, end));
+            i = end;
+            continue;
+        }
+
+        // Comments (`-- line`, `/* block */`) produce no tokens
+        let comment = match bytes.get(i..i + 2) {
+            Some(b"--") => {
+                let len = bytes[i..]
+                    .iter()
+                    .position(|&b| matches!(b, b'\n' | b'\r'))
+                    .unwrap_or(bytes.len() - i);
+                Some((TriviaKind::LineComment, i + len))
+            }
+            Some(b"/*") => Some((TriviaKind::BlockComment, block_comment_end(bytes, i))),
+            _ => None,
+        };
+        if let Some((kind, end)) = comment {
+            if let Some(trivia) = trivia.as_deref_mut() {
+                trivia.push(Trivia::new(kind, start, end));
+            }
+            i = end;
+            continue;
+        }
+
+        // Dialect sigil path (`#temp`, `@var`)
+        let mode = options.sigil_mode(c);
+        if mode != SigilMode::Symbol {
+            let mut j = i;
+            while j < bytes.len() && bytes[j] == bytes[i] {
+                j += 1;
+            }
+            if j < bytes.len() && is_word_byte(bytes[j]) {
+                while j < bytes.len() && is_word_byte(bytes[j]) {
+                    j += 1;
+                }
+                let text = sql[start..j].to_string();
+                let kind = match mode {
+                    SigilMode::Placeholder => TokenKind::Placeholder(text),
+                    _ => TokenKind::Ident(text),
+                };
+                out.push(Token::new(kind, start, j));
+                i = j;
+                continue;
+            }
+        }
+
+        // Numeric literal path (`123`, `1.0`, `.5`, `1e10`)
+        let after_word = i > 0 && is_word_byte(bytes[i - 1]);
+        let leading_dot =
+            c == '.' && !after_word && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if c.is_ascii_digit() || leading_dot {
+            i = number_end(bytes, i);
+            out.push(Token::new(
+                TokenKind::Number(sql[start..i].to_string()),
+                start,
+                i,
+            ));
+            continue;
+        }
 
         // Identifier path
-        if c.is_ascii_alphanumeric() || c == '_' {
+        if c.is_ascii_alphabetic() || c == '_' {
             i += 1;
             while i < bytes.len() {
                 let cc = bytes[i] as char;
@@ -49,33 +284,144 @@ pub fn tokenize(sql: &str) -> Vec<Token> {
             }
             let text = &sql[start..i];
             let lower = text.to_ascii_lowercase();
-            let kind = Keyword::from_lower(&lower)
-                .map(TokenKind::Keyword)
-                .unwrap_or_else(|| TokenKind::Ident(text.to_string()));
+            let kind = match Keyword::from_lower(&lower) {
+                Some(kw) => TokenKind::Keyword(kw),
+                None if options.keywords.contains_lower(&lower) => TokenKind::ExtraKeyword(lower),
+                None => TokenKind::Ident(text.to_string()),
+            };
             out.push(Token::new(kind, start, i));
             continue;
         }
 
-        // Single-character tokens
-        i += 1;
+        // Bracket-quoted identifier path (`[Order Details]`, `]]` escapes `]`)
+        if options.bracket_identifiers
+            && c == '['
+            && let Some((name, end)) = bracket_identifier(sql, i)
+        {
+            out.push(Token::new(TokenKind::Ident(name), start, end));
+            i = end;
+            continue;
+        }
+
+        // Operator path (`::`, `<>`, and with `options.operators` `->>`, `@>`, `&&`, ...)
+        let operators = match options.operators {
+            true => OPERATORS,
+            false => BASIC_OPERATORS,
+        };
+        if let Some(op) = operators
+            .iter()
+            .find(|op| bytes[i..].starts_with(op.as_bytes()))
+        {
+            i += op.len();
+            out.push(Token::new(TokenKind::Operator(op.to_string()), start, i));
+            continue;
+        }
+
+        // Single-character tokens; a non-ASCII character spans all its bytes
+        let c = sql[i..].chars().next().unwrap_or(c);
+        i += c.len_utf8();
         let kind = match c {
             ',' => TokenKind::Comma,
             '.' => TokenKind::Dot,
             '(' => TokenKind::ParenOpen,
             ')' => TokenKind::ParenClose,
+            '[' => TokenKind::BracketOpen,
+            ']' => TokenKind::BracketClose,
             other => TokenKind::Other(other),
         };
         out.push(Token::new(kind, start, i));
     }
 
+    if options.eof {
+        out.push(Token::new(TokenKind::Eof, sql.len(), sql.len()));
+    }
     out
 }
 
+/// End offset of the numeric literal starting at `bytes[start]`: digits, an
+/// optional `.` and fraction digits (`10.` and `.5` included), then an
+/// optional exponent (`e`, optional sign, digits). An `e` not followed by
+/// digits is not part of the literal.
+fn number_end(bytes: &[u8], start: usize) -> usize {
+    let digits = |i: usize| i + bytes[i..].iter().take_while(|b| b.is_ascii_digit()).count();
+    let mut i = digits(start);
+    if bytes.get(i) == Some(&b'.') {
+        i = digits(i + 1);
+    }
+    if matches!(bytes.get(i), Some(b'e' | b'E')) {
+        let sign = usize::from(matches!(bytes.get(i + 1), Some(b'+' | b'-')));
+        if bytes.get(i + 1 + sign).is_some_and(u8::is_ascii_digit) {
+            i = digits(i + 1 + sign);
+        }
+    }
+    i
+}
+
+/// End offset of the block comment opening at `bytes[start]`. Comments nest
+/// as in Postgres (`/* a /* b */ c */` is one comment); an unterminated
+/// comment runs to the end of input.
+fn block_comment_end(bytes: &[u8], start: usize) -> usize {
+    let mut depth = 0;
+    let mut i = start;
+    while i < bytes.len() {
+        match bytes.get(i..i + 2) {
+            Some(b"/*") => {
+                depth += 1;
+                i += 2;
+            }
+            Some(b"*/") => {
+                depth -= 1;
+                i += 2;
+                if depth == 0 {
+                    return i;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Read the string literal opening at `sql[start]`, returning its unescaped
+/// value and end offset (`sql.len()` if it is never closed).
+fn string_literal(sql: &str, start: usize) -> (String, usize) {
+    let mut value = String::new();
+    let mut chars = sql[start + 1..].char_indices().peekable();
+    while let Some((offset, ch)) = chars.next() {
+        if ch != '\'' {
+            value.push(ch);
+        } else if chars.next_if(|&(_, next)| next == '\'').is_some() {
+            value.push('\'');
+        } else {
+            return (value, start + 1 + offset + 1);
+        }
+    }
+    (value, sql.len())
+}
+
+/// Read the bracket-quoted identifier opening at `sql[start]`, returning its
+/// unescaped name and end offset. `None` if it is never closed (mid-edit).
+fn bracket_identifier(sql: &str, start: usize) -> Option<(String, usize)> {
+    let mut name = String::new();
+    let mut chars = sql[start + 1..].char_indices().peekable();
+    while let Some((offset, ch)) = chars.next() {
+        if ch != ']' {
+            name.push(ch);
+        } else if chars.next_if(|&(_, next)| next == ']').is_some() {
+            name.push(']');
+        } else {
+            return Some((name, start + 1 + offset + 1));
+        }
+    }
+    None
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::sql::keyword::Keyword;
     use crate::sql::token_kind::TokenKind;
+    use rstest::rstest;
 
     #[test]
     fn basic_select_sequence() {
@@ -126,11 +472,440 @@ mod tests {
         assert!(toks.iter().any(|t| matches!(t.kind, TokenKind::ParenClose)));
     }
 
+    #[test]
+    fn bracket_tokens() {
+        let toks = tokenize("ARRAY[a.x, b][1]");
+        let brackets: Vec<_> = toks
+            .iter()
+            .filter(|t| matches!(t.kind, TokenKind::BracketOpen | TokenKind::BracketClose))
+            .map(Token::span)
+            .collect();
+        assert_eq!(brackets, [(5, 6), (12, 13), (13, 14), (15, 16)]);
+        assert!(
+            toks.iter()
+                .all(|t| !matches!(t.kind, TokenKind::Other('[' | ']')))
+        );
+    }
+
     #[test]
     fn other_characters() {
         let toks = tokenize("SELECT * FROM t;");
         assert!(toks.iter().any(|t| t.is_keyword(Keyword::Select)));
         assert!(toks.iter().any(|t| matches!(t.kind, TokenKind::Other('*'))));
         assert!(toks.iter().any(|t| matches!(t.kind, TokenKind::Other(';'))));
+
+        let toks = tokenize("é🙂");
+        assert_eq!(
+            toks,
+            [
+                Token::new(TokenKind::Other('é'), 0, 2),
+                Token::new(TokenKind::Other('🙂'), 2, 6)
+            ]
+        );
+    }
+
+    #[rstest]
+    // Case 1: Keywords inside a literal stay in it
+    #[case("SELECT 'FROM me' FROM t", "FROM me", (7, 16), false)]
+    // Case 2: Doubled quotes are an escaped quote
+    #[case("SELECT 'it''s' FROM t", "it's", (7, 14), false)]
+    // Case 3: Empty literal
+    #[case("SELECT '' FROM t", "", (7, 9), false)]
+    // Case 4: Unterminated literal runs to the end of input
+    #[case("SELECT 'FROM t", "FROM t", (7, 14), true)]
+    // Case 5: Unterminated after an escaped quote
+    #[case("SELECT 'it''", "it'", (7, 12), true)]
+    // Case 6: Multibyte contents
+    #[case("SELECT 'héllo' FROM t", "héllo", (7, 15), false)]
+    fn string_literals_are_single_tokens(
+        #[case] sql: &str,
+        #[case] value: &str,
+        #[case] span: (usize, usize),
+        #[case] unterminated: bool,
+    ) {
+        let toks = tokenize(sql);
+        let literal = toks
+            .iter()
+            .find(|t| t.kind.string().is_some())
+            .expect("literal");
+        assert_eq!(literal.kind.string(), Some(value), "{sql}");
+        assert_eq!(literal.span(), span, "{sql}");
+        assert_eq!(literal.is_unterminated_string(), unterminated, "{sql}");
+        assert!(literal.ident().is_none());
+        let clauses = toks.iter().filter(|t| t.is_keyword(Keyword::From)).count();
+        assert_eq!(clauses, usize::from(!unterminated), "{sql}");
+    }
+
+    #[rstest]
+    // Case 1: Decimal
+    #[case("SELECT 3.14 FROM t", "3.14", (7, 11))]
+    // Case 2: Exponent with a sign
+    #[case("SELECT 1e-3 FROM t", "1e-3", (7, 11))]
+    // Case 3: Bare leading dot
+    #[case("SELECT .5 FROM t", ".5", (7, 9))]
+    // Case 4: Trailing dot at end of input
+    #[case("SELECT 10.", "10.", (7, 10))]
+    // Case 5: Integer
+    #[case("LIMIT 123", "123", (6, 9))]
+    fn numbers_are_single_tokens(
+        #[case] sql: &str,
+        #[case] text: &str,
+        #[case] span: (usize, usize),
+    ) {
+        let toks = tokenize(sql);
+        let number = toks
+            .iter()
+            .find(|t| t.kind.number().is_some())
+            .expect("number");
+        assert_eq!(number.kind.number(), Some(text), "{sql}");
+        assert_eq!(number.span(), span, "{sql}");
+        assert!(
+            toks.iter().all(|t| !matches!(t.kind, TokenKind::Dot)),
+            "{sql}"
+        );
+    }
+
+    #[test]
+    fn numbers_end_before_words() {
+        let texts: Vec<_> = tokenize("t1.x2 1e 2x")
+            .into_iter()
+            .map(|t| match t.kind {
+                TokenKind::Number(n) => format!("n:{n}"),
+                TokenKind::Ident(s) => format!("id:{s}"),
+                other => format!("{other:?}"),
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            ["id:t1", "Dot", "id:x2", "n:1", "id:e", "n:2", "id:x"]
+        );
+    }
+
+    #[test]
+    fn extra_keywords_are_classified() {
+        let options = TokenizerOptions::default()
+            .with_keywords(KeywordSet::new().with("ILIKE").with("materialized"));
+        let toks = tokenize_with("SELECT a FROM t WHERE a ILike 'x' MATERIALIZED", &options);
+        let extras: Vec<_> = toks.iter().filter_map(|t| t.kind.extra_keyword()).collect();
+        assert_eq!(extras, ["ilike", "materialized"]);
+        assert!(toks.iter().any(|t| t.ident() == Some("a")));
+    }
+
+    #[test]
+    fn extra_keywords_absent_by_default() {
+        let toks = tokenize("a ILIKE b");
+        assert!(toks.iter().any(|t| t.ident() == Some("ILIKE")));
+        assert!(toks.iter().all(|t| t.kind.extra_keyword().is_none()));
+    }
+
+    #[test]
+    fn builtin_keywords_take_precedence() {
+        let options = TokenizerOptions::default().with_keywords(KeywordSet::new().with("select"));
+        let toks = tokenize_with("SELECT", &options);
+        assert!(toks[0].is_keyword(Keyword::Select));
+    }
+
+    #[test]
+    fn sigils_are_symbols_by_default() {
+        let toks = tokenize("SELECT * FROM #temp WHERE id = @id");
+        assert!(toks.iter().any(|t| matches!(t.kind, TokenKind::Other('#'))));
+        assert!(toks.iter().any(|t| matches!(t.kind, TokenKind::Other('@'))));
+        assert!(toks.iter().any(|t| t.ident() == Some("temp")));
+    }
+
+    #[test]
+    fn sql_server_sigils() {
+        let sql = "SELECT * FROM #temp JOIN ##shared ON x = @id WHERE y = @";
+        let toks = tokenize_with(sql, &TokenizerOptions::sql_server());
+        let idents: Vec<_> = toks.iter().filter_map(Token::ident).collect();
+        assert_eq!(idents, ["#temp", "##shared", "x", "y"]);
+        let temp = toks.iter().find(|t| t.ident() == Some("#temp")).unwrap();
+        assert_eq!(&sql[temp.start..temp.end], "#temp");
+        let placeholders: Vec<_> = toks.iter().filter_map(|t| t.kind.placeholder()).collect();
+        assert_eq!(placeholders, ["@id"]);
+        assert!(matches!(toks.last().unwrap().kind, TokenKind::Other('@')));
+    }
+
+    #[test]
+    fn sql_server_bracket_identifiers() {
+        let sql = "SELECT [Order Id], [a]]b] FROM [dbo].[Order Details] WHERE x = [unterminated";
+        let toks = tokenize_with(sql, &TokenizerOptions::sql_server());
+        let idents: Vec<_> = toks.iter().filter_map(Token::ident).collect();
+        assert_eq!(
+            idents,
+            [
+                "Order Id",
+                "a]b",
+                "dbo",
+                "Order Details",
+                "x",
+                "unterminated"
+            ]
+        );
+        let quoted = toks.iter().find(|t| t.ident() == Some("a]b")).unwrap();
+        assert_eq!(&sql[quoted.start..quoted.end], "[a]]b]");
+        assert!(matches!(toks[toks.len() - 2].kind, TokenKind::BracketOpen));
+
+        // Off by default
+        let toks = tokenize("[dbo]");
+        assert_eq!(
+            toks.iter().filter_map(Token::ident).collect::<Vec<_>>(),
+            ["dbo"]
+        );
+        assert!(matches!(toks[0].kind, TokenKind::BracketOpen));
+    }
+
+    #[test]
+    fn eof_sentinel_is_opt_in() {
+        let sql = "SELECT a ";
+        assert!(tokenize(sql).iter().all(|t| !t.kind.is_eof()));
+
+        let toks = tokenize_with(sql, &TokenizerOptions::default().with_eof(true));
+        assert_eq!(toks.len(), 3);
+        let eof = toks.last().unwrap();
+        assert!(eof.kind.is_eof());
+        assert_eq!(eof.span(), (sql.len(), sql.len()));
+        assert!(eof.is_empty());
+
+        let toks = tokenize_with("", &TokenizerOptions::default().with_eof(true));
+        assert_eq!(toks, [Token::new(TokenKind::Eof, 0, 0)]);
+    }
+
+    /// Interleave tokens and trivia by offset and check they tile `sql`.
+    fn reconstruct(sql: &str) -> String {
+        let (tokens, trivia) = tokenize_with_trivia(sql);
+        let mut spans: Vec<_> = tokens
+            .iter()
+            .map(Token::span)
+            .chain(trivia.iter().map(Trivia::span))
+            .collect();
+        spans.sort();
+        let mut pos = 0;
+        let mut out = String::new();
+        for (start, end) in spans {
+            assert_eq!(start, pos, "gap or overlap at {start} in {sql:?}");
+            out.push_str(&sql[start..end]);
+            pos = end;
+        }
+        assert_eq!(pos, sql.len());
+        out
+    }
+
+    #[test]
+    fn trivia_reconstructs_input() {
+        for sql in [
+            "",
+            "SELECT a, b FROM t",
+            "  SELECT a -- pick a\n\tFROM t /* all rows */ WHERE a - 1 > 0\n",
+            "SELECT /* unterminated",
+            "SELECT 1 --",
+            "SELECT a/b, c-d FROM t",
+            "SELECT a -- x\r\nFROM t\r\n",
+        ] {
+            assert_eq!(reconstruct(sql), sql);
+        }
+    }
+
+    #[test]
+    fn trivia_kinds_and_spans() {
+        let sql = "SELECT a -- x\n/* y */ b";
+        let (tokens, trivia) = tokenize_with_trivia(sql);
+        let kinds: Vec<_> = trivia.iter().map(|t| (t.kind, t.span())).collect();
+        assert_eq!(
+            kinds,
+            [
+                (TriviaKind::Whitespace, (6, 7)),
+                (TriviaKind::Whitespace, (8, 9)),
+                (TriviaKind::LineComment, (9, 13)),
+                (TriviaKind::Whitespace, (13, 14)),
+                (TriviaKind::BlockComment, (14, 21)),
+                (TriviaKind::Whitespace, (21, 22)),
+            ]
+        );
+        let idents: Vec<_> = tokens.iter().filter_map(Token::ident).collect();
+        assert_eq!(idents, ["a", "b"]);
+    }
+
+    /// Tokens rendered compactly: operators and `Other` as their text,
+    /// identifiers as `id:name`.
+    fn operator_tokens(sql: &str) -> Vec<String> {
+        let options = TokenizerOptions::default().with_operators(true);
+        tokenize_with(sql, &options)
+            .iter()
+            .map(|t| match &t.kind {
+                TokenKind::Operator(op) => op.clone(),
+                TokenKind::Other(c) => c.to_string(),
+                TokenKind::Ident(s) => format!("id:{s}"),
+                TokenKind::String(s) => format!("'{s}'"),
+                other => format!("{other:?}"),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn json_path_operators_are_single_tokens() {
+        assert_eq!(operator_tokens("data->>'key'"), ["id:data", "->>", "'key'"]);
+        assert_eq!(
+            operator_tokens("data->'a'#>>'{b}'"),
+            ["id:data", "->", "'a'", "#>>", "'{b}'"]
+        );
+        assert_eq!(operator_tokens("doc#>'{a}'"), ["id:doc", "#>", "'{a}'"]);
+        assert_eq!(operator_tokens("doc ? 'k'"), ["id:doc", "?", "'k'"]);
+        assert_eq!(operator_tokens("doc ?| keys"), ["id:doc", "?|", "id:keys"]);
+    }
+
+    #[test]
+    fn containment_and_overlap_operators_are_single_tokens() {
+        assert_eq!(operator_tokens("tags && '{a}'"), ["id:tags", "&&", "'{a}'"]);
+        assert_eq!(
+            operator_tokens("a@>b<@c"),
+            ["id:a", "@>", "id:b", "<@", "id:c"]
+        );
+        assert_eq!(
+            operator_tokens("x::int <= y"),
+            ["id:x", "::", "id:int", "<=", "id:y"]
+        );
+        assert_eq!(
+            operator_tokens("a = b * c"),
+            ["id:a", "=", "id:b", "*", "id:c"]
+        );
+        let spans: Vec<_> =
+            tokenize_with("a->>b", &TokenizerOptions::default().with_operators(true))
+                .iter()
+                .map(Token::span)
+                .collect();
+        assert_eq!(spans, [(0, 1), (1, 4), (4, 5)]);
+    }
+
+    #[test]
+    fn operators_are_opt_in() {
+        let kinds: Vec<_> = tokenize("a->>b").into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Ident("a".into()),
+                TokenKind::Other('-'),
+                TokenKind::Other('>'),
+                TokenKind::Other('>'),
+                TokenKind::Ident("b".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn basic_operators_are_single_tokens_by_default() {
+        let kinds: Vec<_> = tokenize("a::b").into_iter().map(|t| t.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                TokenKind::Ident("a".into()),
+                TokenKind::Operator("::".into()),
+                TokenKind::Ident("b".into()),
+            ]
+        );
+        let toks = tokenize("a <> b");
+        assert_eq!(toks[1].kind.operator(), Some("<>"));
+        assert_eq!(toks[1].span(), (2, 4));
+        for op in ["<=", ">=", "!=", "||"] {
+            let toks = tokenize(&format!("a {op} b"));
+            assert_eq!(toks.len(), 3, "{op}");
+            assert_eq!(toks[1].kind.operator(), Some(op));
+        }
+        assert!(tokenize("a.b").iter().all(|t| t.kind.operator().is_none()));
+    }
+
+    mod fuzz {
+        use super::*;
+        use proptest::prelude::*;
+
+        /// Tokenizer-hostile fragments (comment and literal openers, numbers,
+        /// operators, sigils) mixed with arbitrary characters.
+        fn sql_text() -> impl Strategy<Value = String> {
+            let fragment = prop_oneof![
+                prop::sample::select(vec![
+                    "SELECT", "FROM", "a", "1", "1.", ".5", "1e", "1e-", "e", "'", "''", "\"",
+                    "--", "/*", "*/", "-", "/", "*", ":", "::", "<", ">", "!", "|", "->>", "#",
+                    "@", "[", "]", "(", ")", ".", ",", ";", " ", "\r", "\n", "\t", "é", "🙂",
+                ])
+                .prop_map(str::to_string),
+                any::<char>().prop_map(String::from),
+            ];
+            prop::collection::vec(fragment, 0..60).prop_map(|parts| parts.concat())
+        }
+
+        /// Every span is a valid, in-order slice of `sql`.
+        fn assert_spans(sql: &str, tokens: &[Token]) -> Result<(), TestCaseError> {
+            let mut pos = 0;
+            for t in tokens {
+                prop_assert!(
+                    t.start <= t.end && t.end <= sql.len(),
+                    "{t:?} out of bounds in {sql:?}"
+                );
+                prop_assert!(
+                    t.start >= pos,
+                    "{t:?} overlaps the previous token in {sql:?}"
+                );
+                prop_assert!(
+                    sql.get(t.start..t.end).is_some(),
+                    "{t:?} splits a character in {sql:?}"
+                );
+                pos = t.end;
+            }
+            Ok(())
+        }
+
+        fn check(sql: &str) -> Result<(), TestCaseError> {
+            for options in [
+                TokenizerOptions::default(),
+                TokenizerOptions::sql_server(),
+                TokenizerOptions::default()
+                    .with_operators(true)
+                    .with_eof(true),
+            ] {
+                assert_spans(sql, &tokenize_with(sql, &options))?;
+            }
+            let (tokens, trivia) = tokenize_with_trivia(sql);
+            assert_spans(sql, &tokens)?;
+            prop_assert_eq!(tokens, tokenize(sql));
+            prop_assert!(trivia.iter().all(|t| sql.get(t.start..t.end).is_some()));
+            Ok(())
+        }
+
+        proptest! {
+            #[test]
+            fn spans_stay_in_bounds(sql in sql_text()) {
+                check(&sql)?;
+            }
+
+            #[test]
+            fn spans_stay_in_bounds_on_arbitrary_utf8(sql in any::<String>()) {
+                check(&sql)?;
+            }
+        }
+    }
+
+    #[test]
+    fn plain_tokenize_is_unchanged_by_trivia_mode() {
+        for sql in ["SELECT a - b FROM t", "SELECT a -- x\n/* y */ b"] {
+            assert_eq!(tokenize_with_trivia(sql).0, tokenize(sql));
+        }
+    }
+
+    #[rstest]
+    // Case 1: A clause keyword inside a line comment is not a token
+    #[case("SELECT -- FROM old\n  FROM users", &["SELECT", "FROM", "users"])]
+    // Case 2: Unterminated block comment runs to the end of input
+    #[case("SELECT a /* FROM t", &["SELECT", "a"])]
+    // Case 3: Nested block comments close at the outermost `*/`
+    #[case("SELECT /* a /* b */ FROM x */ c FROM t", &["SELECT", "c", "FROM", "t"])]
+    // Case 4: Comment markers inside a string literal are text
+    #[case("SELECT '--x' FROM t", &["SELECT", "'--x'", "FROM", "t"])]
+    // Case 5: A line comment ends at a CRLF line break
+    #[case("SELECT a -- x\r\n\tFROM t", &["SELECT", "a", "FROM", "t"])]
+    // Case 6: Single `-` and `/` stay operators
+    #[case("SELECT a-b/c", &["SELECT", "a", "-", "b", "/", "c"])]
+    fn comments_produce_no_tokens(#[case] sql: &str, #[case] expected: &[&str]) {
+        let texts: Vec<_> = tokenize(sql).iter().map(|t| &sql[t.start..t.end]).collect();
+        assert_eq!(texts, expected, "{sql}");
     }
 }
@@ -0,0 +1,37 @@
+//! Trivia: the source regions the tokenizer skips.
+//!
+//! `tokenize_with_trivia` reports every whitespace run and comment alongside
+//! the tokens, so formatters and range mapping can account for each byte of
+//! the input. Interleaving tokens and trivia by offset reconstructs the input.
+
+/// Kind of a skipped source region.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TriviaKind {
+    /// A run of ASCII whitespace.
+    Whitespace,
+    /// `-- ...` up to (not including) the line break (`\n` or `\r\n`) or end
+    /// of input.
+    LineComment,
+    /// `/* ... */`, or up to the end of input when unterminated.
+    BlockComment,
+}
+
+/// A skipped region with its inclusive start and exclusive end byte offsets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Trivia {
+    pub kind: TriviaKind,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Trivia {
+    /// Construct a new trivia region.
+    pub const fn new(kind: TriviaKind, start: usize, end: usize) -> Self {
+        Self { kind, start, end }
+    }
+
+    /// Returns `(start, end)` as a tuple.
+    pub const fn span(&self) -> (usize, usize) {
+        (self.start, self.end)
+    }
+}
@@ -0,0 +1,37 @@
+//! The `sql-only` feature must build the tokenizer without the async and
+//! database dependencies of `full`.
+
+use std::process::Command;
+
+use qview::sql::prelude::*;
+
+#[test]
+fn tokenizer_is_usable_from_outside_the_crate() {
+    let tokens = tokenize("SELECT a FROM t");
+    assert!(tokens[0].is_keyword(Keyword::Select));
+    assert_eq!(tokens[3].ident(), Some("t"));
+}
+
+#[test]
+fn sql_only_feature_builds_standalone() {
+    let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    // A separate target dir: the outer `cargo test` may hold the default one.
+    let target = concat!(env!("CARGO_MANIFEST_DIR"), "/target/sql-only");
+    let output = Command::new(env!("CARGO"))
+        .args([
+            "check",
+            "--lib",
+            "--offline",
+            "--no-default-features",
+            "--features",
+            "sql-only",
+        ])
+        .args(["--manifest-path", manifest, "--target-dir", target])
+        .output()
+        .expect("cargo should run");
+    assert!(
+        output.status.success(),
+        "{}",
+        String::from_utf8_lossy(&output.stderr)
+    );
+}